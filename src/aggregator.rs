@@ -1,40 +1,91 @@
 //! 通知聚合模块
 //!
 //! 管理通知的聚合和批量发送
+//!
+//! 多个会话可能同时调用 `ccn notify`，状态文件的读-改-写都在文件锁内完成
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::fs;
 use std::path::PathBuf;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use crate::duration::format_secs;
+use crate::state_file::{self, StateLock};
+
+/// 聚合状态文件格式版本
+///
+/// - 1：时间戳为秒（没有 `version` 字段）
+/// - 2：时间戳为毫秒
+const STATE_VERSION: u32 = 2;
+
 /// 聚合状态文件
 #[derive(Debug, Serialize, Deserialize)]
 struct AggregationState {
+    #[serde(default)]
+    version: u32,
     notifications: Vec<AggregatedNotification>,
+    /// 窗口开始时间（毫秒时间戳）
     window_start: u64,
+    #[serde(flatten)]
+    target: WindowTarget,
+}
+
+/// 聚合窗口的发送目标，由开启窗口的通知决定，窗口结束时按它加载配置和选择渠道
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowTarget {
+    /// 项目目录，其中的 `.ccn.yaml` 覆盖全局配置
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_path: Option<String>,
+    /// 命中规则指定的渠道，为空表示全部渠道
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sinks: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AggregatedNotification {
-    status: String,
-    duration: u64,
-    cmd: String,
-    timestamp: u64,
+    pub status: String,
+    pub duration: u64,
+    pub cmd: String,
+    /// 加入时间（毫秒时间戳）
+    pub timestamp: u64,
 }
 
 impl AggregationState {
     fn new(_window: u64) -> Self {
         Self {
+            version: STATE_VERSION,
             notifications: Vec::new(),
-            window_start: now(),
+            window_start: now_ms(),
+            target: WindowTarget::default(),
+        }
+    }
+
+    /// 解析状态文件，旧版本格式迁移到当前版本
+    fn parse(content: &str) -> Result<Self> {
+        let value: Value = serde_json::from_str(content)?;
+        let version = value.get("version").and_then(Value::as_u64).unwrap_or(1);
+
+        match version {
+            1 => {
+                let mut state: Self = serde_json::from_value(value)?;
+                state.version = STATE_VERSION;
+                state.window_start = state.window_start.saturating_mul(1000);
+                for notification in &mut state.notifications {
+                    notification.timestamp = notification.timestamp.saturating_mul(1000);
+                }
+                Ok(state)
+            }
+            2 => Ok(serde_json::from_value(value)?),
+            other => bail!("{}", t!("aggregate.unsupported_version", version = other)),
         }
     }
 
     /// 检查窗口是否过期
     fn is_expired(&self, window_ms: u64) -> bool {
-        let elapsed = now().saturating_sub(self.window_start);
-        elapsed > window_ms / 1000
+        let elapsed = now_ms().saturating_sub(self.window_start);
+        elapsed > window_ms
     }
 
     /// 添加通知
@@ -43,7 +94,7 @@ impl AggregationState {
             status: status.to_string(),
             duration,
             cmd: cmd.to_string(),
-            timestamp: now(),
+            timestamp: now_ms(),
         });
     }
 
@@ -61,6 +112,7 @@ pub struct NotificationAggregator {
     state_file: PathBuf,
     window_ms: u64,
     max_toasts: usize,
+    target: WindowTarget,
 }
 
 impl NotificationAggregator {
@@ -69,76 +121,94 @@ impl NotificationAggregator {
             state_file,
             window_ms,
             max_toasts,
+            target: WindowTarget::default(),
         }
     }
 
+    /// 设置本次通知开启新窗口时记录的发送目标
+    pub fn with_target(mut self, target: WindowTarget) -> Self {
+        self.target = target;
+        self
+    }
+
     /// 添加通知到聚合器
-    pub fn add_notification(&self, status: &str, duration: u64, cmd: &str) -> Result<Option<AggregatedResult>> {
-        let mut state = self.load_state().unwrap_or_else(|_| AggregationState::new(self.window_ms));
+    ///
+    /// 上一个窗口已过期但未发送的通知不会被丢弃，而是随结果返回；达到 `max_toasts` 时清空缓冲区
+    pub fn add_notification(&self, status: &str, duration: u64, cmd: &str) -> Result<AddResult> {
+        let _lock = StateLock::acquire(&self.state_file)?;
+        let mut state = self.load_state_or_reset()?;
+        let mut ready = Vec::new();
 
         // 检查窗口是否过期
-        if state.is_expired(self.window_ms) {
-            // 窗口过期，重置状态
+        if state.is_expired(self.window_ms) || state.notifications.is_empty() {
+            if !state.notifications.is_empty() {
+                ready.push(self.build_result(&state));
+            }
+            // 开启新窗口
             state = AggregationState::new(self.window_ms);
+            state.target = self.target.clone();
         }
 
         // 添加新通知
         state.add(status, duration, cmd);
+        let new_window = (state.notifications.len() == 1).then_some(state.window_start);
 
         // 检查是否达到最大数量
-        let should_send = state.notifications.len() >= self.max_toasts;
-
-        if should_send {
-            // 保存状态并发送聚合通知
-            self.save_state(&state)?;
-            Ok(Some(self.build_result(&state)))
-        } else {
-            // 保存状态但不发送
-            self.save_state(&state)?;
-            Ok(None)
+        if state.notifications.len() >= self.max_toasts {
+            ready.push(self.build_result(&state));
+            self.clear_state()?;
+            return Ok(AddResult {
+                ready,
+                new_window: None,
+            });
         }
+
+        // 保存状态但不发送
+        self.save_state(&state)?;
+        Ok(AddResult { ready, new_window })
     }
 
-    /// 刷新待发送的通知
-    #[allow(dead_code)]
-    pub fn flush(&self) -> Result<Option<AggregatedResult>> {
-        let state = self.load_state()?;
+    /// 刷新指定窗口的通知（由定时刷新进程在窗口结束时调用）
+    ///
+    /// 缓冲区已被发送或已开启新窗口时返回 None，避免重复发送
+    pub fn flush_window(&self, window_start: u64) -> Result<Option<AggregatedResult>> {
+        let _lock = StateLock::acquire(&self.state_file)?;
+        let state = self.load_state_or_reset()?;
 
-        if state.notifications.is_empty() {
+        if state.window_start != window_start || state.notifications.is_empty() {
             return Ok(None);
         }
 
-        // 清空状态
         self.clear_state()?;
         Ok(Some(self.build_result(&state)))
     }
 
-    /// 检查是否有待发送的通知
-    #[allow(dead_code)]
-    pub fn has_pending(&self) -> bool {
-        if let Ok(state) = self.load_state() {
-            !state.notifications.is_empty() && !state.is_expired(self.window_ms)
-        } else {
-            false
-        }
-    }
-
-    fn load_state(&self) -> Result<AggregationState> {
+    /// 读取状态，文件损坏时备份后重新开始（读取失败仍返回错误）
+    fn load_state_or_reset(&self) -> Result<AggregationState> {
         if !self.state_file.exists() {
             return Ok(AggregationState::new(self.window_ms));
         }
 
-        let content = fs::read_to_string(&self.state_file)?;
-        serde_json::from_str(&content).map_err(Into::into)
+        let content = fs::read_to_string(&self.state_file)
+            .with_context(|| t!("aggregate.read_state", path = format!("{:?}", self.state_file)))?;
+        match AggregationState::parse(&content) {
+            Ok(state) => Ok(state),
+            Err(e) => {
+                log::warn!("{}", t!("aggregate.parse_state", path = format!("{:?}", self.state_file), error = e));
+                state_file::quarantine(&self.state_file);
+                Ok(AggregationState::new(self.window_ms))
+            }
+        }
     }
 
     fn save_state(&self, state: &AggregationState) -> Result<()> {
+        if let Some(parent) = self.state_file.parent() {
+            fs::create_dir_all(parent)?;
+        }
         let content = serde_json::to_string(state)?;
-        fs::write(&self.state_file, content)?;
-        Ok(())
+        state_file::write_atomic(&self.state_file, content.as_bytes())
     }
 
-    #[allow(dead_code)]
     fn clear_state(&self) -> Result<()> {
         if self.state_file.exists() {
             fs::remove_file(&self.state_file)?;
@@ -153,10 +223,79 @@ impl NotificationAggregator {
             success,
             error,
             notifications: state.notifications.clone(),
+            target: state.target.clone(),
         }
     }
 }
 
+/// 添加通知的结果
+#[derive(Debug)]
+pub struct AddResult {
+    /// 需要立即发送的聚合结果：上一个窗口过期未发送的通知，或达到 `max_toasts` 的本窗口通知
+    pub ready: Vec<AggregatedResult>,
+    /// 本次通知开启了新窗口时为窗口开始时间，需要安排窗口结束时的刷新
+    pub new_window: Option<u64>,
+}
+
+/// 定时刷新任务
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlushTask {
+    /// 聚合窗口结束，参数为窗口开始时间
+    Aggregation(u64),
+    /// defer 时段结束，发送暂存通知的摘要
+    Deferred,
+}
+
+/// 免打扰期间暂存的通知队列
+pub struct DeferredQueue {
+    state_file: PathBuf,
+}
+
+impl DeferredQueue {
+    pub fn new(state_file: PathBuf) -> Self {
+        Self { state_file }
+    }
+
+    /// 暂存一条通知，返回队列中的通知数
+    pub fn push(&self, status: &str, duration: u64, cmd: &str) -> Result<usize> {
+        let _lock = StateLock::acquire(&self.state_file)?;
+        let mut notifications = self.load()?;
+        notifications.push(AggregatedNotification {
+            status: status.to_string(),
+            duration,
+            cmd: cmd.to_string(),
+            timestamp: now_ms(),
+        });
+
+        if let Some(parent) = self.state_file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        state_file::write_atomic(&self.state_file, serde_json::to_string(&notifications)?.as_bytes())?;
+        Ok(notifications.len())
+    }
+
+    /// 取出全部暂存的通知并清空队列，队列为空时返回 None
+    pub fn take(&self) -> Result<Option<AggregatedResult>> {
+        let _lock = StateLock::acquire(&self.state_file)?;
+        let notifications = self.load()?;
+        if notifications.is_empty() {
+            return Ok(None);
+        }
+
+        fs::remove_file(&self.state_file)?;
+        Ok(Some(AggregatedResult::from_notifications(notifications)))
+    }
+
+    fn load(&self) -> Result<Vec<AggregatedNotification>> {
+        if !self.state_file.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read_to_string(&self.state_file)?;
+        serde_json::from_str(&content).map_err(Into::into)
+    }
+}
+
 /// 聚合结果
 #[derive(Debug, Clone)]
 pub struct AggregatedResult {
@@ -164,15 +303,29 @@ pub struct AggregatedResult {
     pub success: usize,
     pub error: usize,
     pub notifications: Vec<AggregatedNotification>,
+    /// 聚合窗口的发送目标，免打扰摘要为默认值
+    pub target: WindowTarget,
 }
 
 impl AggregatedResult {
+    pub fn from_notifications(notifications: Vec<AggregatedNotification>) -> Self {
+        let success = notifications.iter().filter(|n| n.status == "success").count();
+        let error = notifications.iter().filter(|n| n.status == "error").count();
+        Self {
+            total: notifications.len(),
+            success,
+            error,
+            notifications,
+            target: WindowTarget::default(),
+        }
+    }
+
     /// 生成聚合通知的标题
     pub fn title(&self) -> String {
         if self.error > 0 {
-            format!("{} 个任务完成 ({} 成功, {} 失败)", self.total, self.success, self.error)
+            t!("aggregate.title_with_errors", total = self.total, success = self.success, error = self.error)
         } else {
-            format!("{} 个任务已完成", self.total)
+            t!("aggregate.title", total = self.total)
         }
     }
 
@@ -181,17 +334,17 @@ impl AggregatedResult {
         let mut lines = Vec::new();
 
         if self.success > 0 {
-            lines.push(format!("✅ 成功: {} 个", self.success));
+            lines.push(format!("✅ {}", t!("aggregate.success", count = self.success)));
         }
         if self.error > 0 {
-            lines.push(format!("❌ 失败: {} 个", self.error));
+            lines.push(format!("❌ {}", t!("aggregate.error", count = self.error)));
         }
 
         if !self.notifications.is_empty() && self.notifications.len() <= 5 {
-            lines.push(String::from("\n最近的任务:"));
+            lines.push(format!("\n{}", t!("aggregate.recent")));
             for notif in self.notifications.iter().take(5) {
                 let icon = if notif.status == "success" { "✅" } else { "❌" };
-                lines.push(format!("  {} {} ({}秒)", icon, notif.cmd, notif.duration));
+                lines.push(format!("  {} {}", icon, t!("aggregate.item", cmd = notif.cmd, duration = format_secs(notif.duration))));
             }
         }
 
@@ -204,12 +357,12 @@ impl AggregatedResult {
     }
 }
 
-/// 获取当前时间戳（秒）
-fn now() -> u64 {
+/// 获取当前时间戳（毫秒）
+fn now_ms() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap()
-        .as_secs()
+        .as_millis() as u64
 }
 
 /// 获取聚合状态文件路径
@@ -226,6 +379,11 @@ pub fn get_state_file_path() -> PathBuf {
     PathBuf::from(base).join("claude-code-notify").join("aggregation.json")
 }
 
+/// 获取免打扰暂存队列文件路径（与聚合状态文件位于同一目录）
+pub fn get_deferred_file_path() -> PathBuf {
+    get_state_file_path().with_file_name("deferred.json")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -250,11 +408,226 @@ mod tests {
             success: 3,
             error: 2,
             notifications: vec![],
+            target: WindowTarget::default(),
         };
 
         let title = result.title();
-        assert!(title.contains("5"));
-        assert!(title.contains("3 成功"));
-        assert!(title.contains("2 失败"));
+        assert_eq!(title, t!("aggregate.title_with_errors", total = 5, success = 3, error = 2));
+    }
+
+    #[test]
+    fn test_deferred_queue() {
+        let state_file = std::env::temp_dir().join(format!("ccn-test-deferred-{}.json", std::process::id()));
+        let queue = DeferredQueue::new(state_file.clone());
+
+        assert!(queue.take().unwrap().is_none());
+
+        assert_eq!(queue.push("success", 30, "npm test").unwrap(), 1);
+        assert_eq!(queue.push("error", 12, "cargo build").unwrap(), 2);
+
+        let result = queue.take().unwrap().unwrap();
+        assert_eq!((result.total, result.success, result.error), (2, 1, 1));
+        assert_eq!(result.notifications[1].cmd, "cargo build");
+
+        // 取出后队列清空
+        assert!(queue.take().unwrap().is_none());
+        assert!(!state_file.exists());
+    }
+
+    fn temp_aggregator(name: &str, max_toasts: usize) -> (NotificationAggregator, PathBuf) {
+        let state_file = std::env::temp_dir().join(format!("ccn-test-{}-{}.json", name, std::process::id()));
+        let _ = fs::remove_file(&state_file);
+        (NotificationAggregator::new(state_file.clone(), 5000, max_toasts), state_file)
+    }
+
+    #[test]
+    fn test_add_until_max_toasts() {
+        let (aggregator, state_file) = temp_aggregator("aggregate-max", 2);
+
+        let first = aggregator.add_notification("success", 10, "npm test").unwrap();
+        assert!(first.ready.is_empty());
+        assert!(first.new_window.is_some());
+
+        let second = aggregator.add_notification("error", 10, "cargo build").unwrap();
+        assert_eq!(second.ready.len(), 1);
+        assert_eq!(second.ready[0].total, 2);
+
+        // 发送后缓冲区清空，下一条开启新窗口
+        assert!(!state_file.exists());
+        let third = aggregator.add_notification("success", 10, "make").unwrap();
+        assert!(third.ready.is_empty());
+        assert!(third.new_window.is_some());
+
+        let _ = fs::remove_file(&state_file);
+    }
+
+    #[test]
+    fn test_expired_window_not_discarded() {
+        let (aggregator, state_file) = temp_aggregator("aggregate-expired", 5);
+
+        let mut state = AggregationState::new(5000);
+        state.add("success", 10, "npm test");
+        state.window_start -= 60_000;
+        aggregator.save_state(&state).unwrap();
+
+        let result = aggregator.add_notification("success", 10, "make").unwrap();
+        assert_eq!(result.ready.len(), 1);
+        assert_eq!(result.ready[0].notifications[0].cmd, "npm test");
+        assert!(result.new_window.is_some());
+
+        let _ = fs::remove_file(&state_file);
+    }
+
+    #[test]
+    fn test_flush_window() {
+        let (aggregator, state_file) = temp_aggregator("aggregate-flush", 5);
+
+        let window_start = aggregator.add_notification("success", 10, "npm test").unwrap().new_window.unwrap();
+        aggregator.add_notification("success", 10, "make").unwrap();
+
+        // 其他窗口不刷新
+        assert!(aggregator.flush_window(window_start + 1).unwrap().is_none());
+
+        let result = aggregator.flush_window(window_start).unwrap().unwrap();
+        assert_eq!(result.total, 2);
+
+        // 只刷新一次
+        assert!(aggregator.flush_window(window_start).unwrap().is_none());
+
+        let _ = fs::remove_file(&state_file);
+    }
+
+    #[test]
+    fn test_window_keeps_target_of_first_notification() {
+        let (aggregator, state_file) = temp_aggregator("aggregate-target", 5);
+        let target = WindowTarget {
+            project_path: Some("/work/app".to_string()),
+            sinks: vec!["team".to_string()],
+        };
+
+        let window_start = aggregator
+            .with_target(target.clone())
+            .add_notification("success", 10, "npm test")
+            .unwrap()
+            .new_window
+            .unwrap();
+        // 加入已有窗口的通知不改变目标
+        let aggregator = NotificationAggregator::new(state_file.clone(), 5000, 5);
+        aggregator.add_notification("success", 10, "make").unwrap();
+
+        let result = aggregator.flush_window(window_start).unwrap().unwrap();
+        assert_eq!(result.total, 2);
+        assert_eq!(result.target, target);
+
+        let _ = fs::remove_file(&state_file);
+    }
+
+    #[test]
+    fn test_sub_second_window() {
+        let mut state = AggregationState::new(1500);
+
+        state.window_start = now_ms() - 1200;
+        assert!(!state.is_expired(1500));
+
+        state.window_start = now_ms() - 1600;
+        assert!(state.is_expired(1500));
+
+        state.window_start = now_ms() - 300;
+        assert!(state.is_expired(200));
+    }
+
+    #[test]
+    fn test_migrate_v1_state() {
+        let (aggregator, state_file) = temp_aggregator("aggregate-v1", 5);
+        fs::write(
+            &state_file,
+            r#"{"notifications":[{"status":"success","duration":10,"cmd":"npm test","timestamp":1700000001}],"window_start":1700000000}"#,
+        )
+        .unwrap();
+
+        let state = aggregator.load_state_or_reset().unwrap();
+        assert_eq!(state.version, STATE_VERSION);
+        assert_eq!(state.window_start, 1_700_000_000_000);
+        assert_eq!(state.notifications[0].timestamp, 1_700_000_001_000);
+
+        // 迁移后的通知保留在缓冲区中，并以新格式写回
+        let result = aggregator.add_notification("success", 10, "make").unwrap();
+        assert_eq!(result.ready.len(), 1);
+        assert_eq!(result.ready[0].notifications[0].cmd, "npm test");
+
+        let saved: Value = serde_json::from_str(&fs::read_to_string(&state_file).unwrap()).unwrap();
+        assert_eq!(saved["version"], STATE_VERSION);
+
+        assert!(AggregationState::parse(r#"{"version":99,"notifications":[],"window_start":0}"#).is_err());
+
+        let _ = fs::remove_file(&state_file);
+    }
+
+    #[test]
+    fn test_corrupt_state_is_backed_up() {
+        let (aggregator, state_file) = temp_aggregator("aggregate-corrupt", 5);
+        fs::write(&state_file, "{\"notifications\": [").unwrap();
+
+        let result = aggregator.add_notification("success", 10, "npm test").unwrap();
+        assert!(result.ready.is_empty());
+        assert!(result.new_window.is_some());
+
+        let backup = state_file.with_file_name(format!(
+            "{}.corrupt",
+            state_file.file_name().unwrap().to_string_lossy()
+        ));
+        assert!(backup.exists());
+        let window_start = result.new_window.unwrap();
+        assert_eq!(aggregator.flush_window(window_start).unwrap().unwrap().total, 1);
+
+        let _ = fs::remove_file(&backup);
+        let _ = fs::remove_file(&state_file);
+    }
+
+    /// 并发压力测试：多个通知者同时写入同一个状态文件，不能丢失任何事件
+    #[test]
+    fn test_concurrent_notifiers_lose_nothing() {
+        const NOTIFIERS: usize = 16;
+        const EVENTS_PER_NOTIFIER: usize = 25;
+
+        let (_, state_file) = temp_aggregator("aggregate-stress", 7);
+
+        let handles: Vec<_> = (0..NOTIFIERS)
+            .map(|notifier| {
+                let state_file = state_file.clone();
+                std::thread::spawn(move || {
+                    // 每个线程使用独立的聚合器和文件句柄，与多个 ccn 进程的情况一致
+                    let aggregator = NotificationAggregator::new(state_file, 60_000, 7);
+                    let mut sent = Vec::new();
+                    for event in 0..EVENTS_PER_NOTIFIER {
+                        let cmd = format!("notifier-{}-event-{}", notifier, event);
+                        let result = aggregator.add_notification("success", 1, &cmd).unwrap();
+                        sent.extend(result.ready.into_iter().flat_map(|r| r.notifications));
+                    }
+                    sent
+                })
+            })
+            .collect();
+
+        let mut delivered: Vec<String> = handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .map(|n| n.cmd)
+            .collect();
+
+        let aggregator = NotificationAggregator::new(state_file.clone(), 60_000, 7);
+        let window_start = aggregator.load_state_or_reset().unwrap().window_start;
+        if let Some(rest) = aggregator.flush_window(window_start).unwrap() {
+            delivered.extend(rest.notifications.into_iter().map(|n| n.cmd));
+        }
+
+        // 每个事件恰好出现一次
+        let total = delivered.len();
+        delivered.sort();
+        delivered.dedup();
+        assert_eq!(total, NOTIFIERS * EVENTS_PER_NOTIFIER);
+        assert_eq!(delivered.len(), NOTIFIERS * EVENTS_PER_NOTIFIER);
+
+        let _ = fs::remove_file(&state_file);
     }
 }
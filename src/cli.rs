@@ -2,16 +2,42 @@
 //!
 //! 处理命令行参数解析和子命令调度
 
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
 use anyhow::{Context, Result};
-
-use crate::config::{load_config, Config};
-use crate::notification::{get_notification_manager, NotificationStatus};
-use crate::policy::PolicyEngine;
+use chrono::{Local, NaiveDate, Utc};
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use crate::config::{
+    active_overrides, configured_language, ensure_config_file, load_config, load_layered_config, load_project_config,
+    migrate_config_file, read_config_layer, read_config_layers, set_cli_overrides, Config, ConfigOverride,
+    ConfigSource, LayeredConfig, QuietAction, RuleAction, TemplateConfig, CONFIG_VERSION,
+};
+use crate::config_edit::{lookup, validate_config_text, ConfigDocument};
+use crate::config_validate::{validate_layers, Severity};
+#[cfg(unix)]
+use crate::config::{find_project_config, get_config_path};
+use crate::hook::HookInput;
+use crate::duration::{format_millis, format_secs};
+use crate::i18n::{self, Localizer};
+use crate::history::{HistoryEntry, HistoryFilter, NotificationHistory, get_history_file_path};
+use crate::notification::{get_configured_notification_manager, NotificationContext, NotificationStatus};
+use crate::dispatcher::CompositeNotificationManager;
+use crate::policy::{PolicyEngine, RuleContext, RuleOutcome, RuleTrace};
 use crate::integration::IntegrationManager;
 use crate::wizard::ConfigWizard;
-use crate::aggregator::{NotificationAggregator, get_state_file_path};
-use crate::sound::{SoundPlayer, SystemSound};
+use crate::aggregator::{
+    AggregatedResult, DeferredQueue, FlushTask, NotificationAggregator, WindowTarget, get_deferred_file_path,
+    get_state_file_path,
+};
+use crate::sound::{Sound, SoundPlayer};
+use crate::session::{SessionTracker, get_session_file_path};
+use crate::template::{self, TemplateContext};
+use crate::runner::{exit_like, run_command};
+use crate::stats::{format_rate, StatusCounts, UsageReport};
+#[cfg(unix)]
+use crate::daemon::{self, get_socket_path, Daemon, DaemonEvent, DaemonRequest, NotifyRequest};
 #[cfg(windows)]
 use crate::path_manager::PathManager;
 
@@ -22,23 +48,136 @@ use crate::path_manager::PathManager;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// 临时覆盖配置项，可多次使用，如 --set sound_enabled=false --set threshold.min_duration=30
+    #[arg(long = "set", global = true, value_name = "KEY=VALUE")]
+    set: Vec<String>,
+
+    /// 界面语言，如 zh-CN、en-US；默认按配置项 language 和 LC_ALL/LANG 确定
+    #[arg(long, global = true, value_name = "LOCALE")]
+    lang: Option<String>,
 }
 
 #[derive(Subcommand, Debug)]
 enum Commands {
     /// 发送通知
     Notify {
-        /// 任务状态: success, error, pending
-        #[arg(short, long)]
-        status: String,
+        /// 任务状态（hook 模式下可省略）
+        #[arg(short, long, value_enum, ignore_case = true)]
+        status: Option<StatusArg>,
 
         /// 任务耗时（秒），默认为0
         #[arg(short, long, default_value = "0", value_name = "SECS")]
         duration: Option<u64>,
 
-        /// 执行的命令
+        /// 执行的命令（hook 模式下可省略）
         #[arg(short, long)]
-        cmd: String,
+        cmd: Option<String>,
+
+        /// 从 stdin 读取 Claude Code hook 的 JSON 数据
+        #[arg(long)]
+        from_hook: bool,
+    },
+
+    /// 执行命令，结束后根据耗时和退出码发送通知
+    Run {
+        /// 要执行的命令及其参数（放在 `--` 之后）
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true, value_name = "COMMAND")]
+        command: Vec<String>,
+    },
+
+    /// 查看通知历史
+    History {
+        /// 按状态过滤
+        #[arg(short, long, value_enum, ignore_case = true)]
+        status: Option<StatusArg>,
+
+        /// 起始日期（含），格式 YYYY-MM-DD
+        #[arg(long, value_name = "DATE")]
+        since: Option<String>,
+
+        /// 截止日期（含），格式 YYYY-MM-DD
+        #[arg(long, value_name = "DATE")]
+        until: Option<String>,
+
+        /// 按项目名过滤
+        #[arg(short, long)]
+        project: Option<String>,
+
+        /// 最多显示最近多少条，0 表示全部
+        #[arg(short = 'n', long, default_value = "20")]
+        limit: usize,
+
+        /// 输出格式
+        #[arg(short, long, value_enum, default_value = "table")]
+        format: OutputFormat,
+    },
+
+    /// 基于通知历史的使用统计
+    Stats {
+        /// 起始日期（含），格式 YYYY-MM-DD
+        #[arg(long, value_name = "DATE")]
+        since: Option<String>,
+
+        /// 截止日期（含），格式 YYYY-MM-DD
+        #[arg(long, value_name = "DATE")]
+        until: Option<String>,
+
+        /// 只统计指定项目
+        #[arg(short, long)]
+        project: Option<String>,
+
+        /// 显示最常见命令的数量
+        #[arg(long, default_value = "5")]
+        top: usize,
+
+        /// 输出格式
+        #[arg(short, long, value_enum, default_value = "table")]
+        format: OutputFormat,
+    },
+
+    /// 策略调试
+    Policy {
+        #[command(subcommand)]
+        action: PolicyCommands,
+    },
+
+    /// 消息模板预览
+    Template {
+        #[command(subcommand)]
+        action: TemplateCommands,
+    },
+
+    /// 通知音效
+    Sound {
+        #[command(subcommand)]
+        action: SoundCommands,
+    },
+
+    /// 常驻后台进程，复用配置和通知渠道处理 notify/run 转发的通知（仅 Unix）
+    Daemon {
+        #[command(subcommand)]
+        action: Option<DaemonCommands>,
+    },
+
+    /// 在聚合窗口结束时发送缓冲区中的通知（内部使用）
+    #[command(hide = true)]
+    FlushAggregation {
+        /// 要刷新的窗口开始时间（毫秒时间戳）
+        #[arg(long)]
+        window_start: u64,
+
+        /// 等待时间（毫秒）
+        #[arg(long)]
+        delay: u64,
+    },
+
+    /// 在 defer 时段结束时发送暂存通知的摘要（内部使用）
+    #[command(hide = true)]
+    FlushDeferred {
+        /// 等待时间（毫秒）
+        #[arg(long)]
+        delay: u64,
     },
 
     /// 启动交互式配置向导
@@ -53,20 +192,353 @@ enum Commands {
     /// 验证集成
     Verify,
 
-    /// 显示当前配置
-    Config,
+    /// 显示或修改配置，不带子命令时显示当前目录的生效配置摘要
+    Config {
+        #[command(subcommand)]
+        action: Option<ConfigCommands>,
+
+        /// 显示每个配置项的值来自哪个文件
+        #[arg(long)]
+        show_origin: bool,
+    },
 
     /// 发送测试通知
     Test,
 }
 
+#[derive(Subcommand, Debug)]
+enum TemplateCommands {
+    /// 用示例数据渲染模板的标题和内容
+    Render {
+        /// 模板名称，默认为命令命中的模板
+        name: Option<String>,
+
+        /// 直接渲染给定的格式字符串，不读取配置中的模板
+        #[arg(short, long, conflicts_with = "name")]
+        text: Option<String>,
+
+        /// 任务状态
+        #[arg(short, long, value_enum, ignore_case = true, default_value = "success")]
+        status: StatusArg,
+
+        /// 示例命令
+        #[arg(short, long, default_value = "npm test")]
+        cmd: String,
+
+        /// 示例耗时（秒）
+        #[arg(short, long, default_value = "75")]
+        duration: u64,
+
+        /// 项目路径，默认为当前目录
+        #[arg(short, long)]
+        project: Option<String>,
+
+        /// 示例退出码
+        #[arg(long)]
+        exit_code: Option<i32>,
+
+        /// 示例会话 ID
+        #[arg(long)]
+        session_id: Option<String>,
+
+        /// hook 事件名称（如 Stop）
+        #[arg(short, long)]
+        event: Option<String>,
+    },
+
+    /// 列出模板可用的变量和过滤器
+    Vars,
+}
+
+#[derive(Subcommand, Debug)]
+enum SoundCommands {
+    /// 播放某个状态的通知音效，不受声音设置和免打扰时段影响
+    Test {
+        /// 任务状态
+        #[arg(value_enum, ignore_case = true)]
+        status: StatusArg,
+
+        /// 按命令匹配模板
+        #[arg(short, long)]
+        cmd: Option<String>,
+
+        /// 模板名称，默认为命令命中的模板
+        #[arg(short, long, conflicts_with = "cmd")]
+        template: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum PolicyCommands {
+    /// 显示一条通知命中了哪些规则和模板，以及会如何处理
+    Explain {
+        /// 要检查的命令
+        #[arg(short, long)]
+        cmd: String,
+
+        /// 任务状态
+        #[arg(short, long, value_enum, ignore_case = true, default_value = "success")]
+        status: StatusArg,
+
+        /// 任务耗时（秒）
+        #[arg(short, long, default_value = "0")]
+        duration: u64,
+
+        /// 项目路径，默认为当前目录
+        #[arg(short, long)]
+        project: Option<String>,
+
+        /// hook 事件名称（如 Stop）
+        #[arg(short, long)]
+        event: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigCommands {
+    /// 显示配置项的生效值，如 ccn config get aggregation.window
+    Get {
+        /// 点分路径
+        key: String,
+    },
+
+    /// 修改全局配置文件中的配置项，如 ccn config set threshold.min_duration 30
+    Set {
+        /// 点分路径
+        key: String,
+
+        /// 新值，按 YAML 解析
+        #[arg(allow_hyphen_values = true)]
+        value: String,
+    },
+
+    /// 从全局配置文件中删除配置项（之后使用默认值），如 ccn config unset templates.build
+    Unset {
+        /// 点分路径
+        key: String,
+    },
+
+    /// 用 $VISUAL / $EDITOR 编辑全局配置文件，保存前校验
+    Edit,
+
+    /// 校验配置，列出所有错误和警告（文件、行号和配置项）
+    Validate {
+        /// 只校验这个配置文件；默认校验全局配置、当前目录的项目配置和临时覆盖
+        file: Option<PathBuf>,
+    },
+
+    /// 将全局配置文件升级到当前版本，原文件备份为 <文件名>.v<旧版本>.bak
+    Migrate,
+
+    /// 显示完整的生效配置
+    Show {
+        /// 输出格式
+        #[arg(short, long, value_enum, default_value = "yaml")]
+        format: ConfigFormat,
+    },
+}
+
+/// 配置输出格式
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum ConfigFormat {
+    Yaml,
+    Json,
+}
+
+#[derive(Subcommand, Debug)]
+enum DaemonCommands {
+    /// 在前台运行守护进程（默认）
+    Start,
+
+    /// 停止正在运行的守护进程
+    Stop,
+
+    /// 查看守护进程是否在运行
+    Status,
+}
+
+/// `--status` 参数的取值
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum StatusArg {
+    Success,
+    #[value(alias = "failed", alias = "failure")]
+    Error,
+    #[value(alias = "running")]
+    Pending,
+}
+
+impl From<StatusArg> for NotificationStatus {
+    fn from(status: StatusArg) -> Self {
+        match status {
+            StatusArg::Success => NotificationStatus::Success,
+            StatusArg::Error => NotificationStatus::Error,
+            StatusArg::Pending => NotificationStatus::Pending,
+        }
+    }
+}
+
+/// 输出格式
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum OutputFormat {
+    Table,
+    Json,
+}
+
+/// 确定界面语言
+///
+/// 在解析命令行之前调用，以便帮助文字也使用该语言，因此直接从参数中读取 `--lang` 和 `--set`
+fn init_locale() {
+    let args: Vec<String> = std::env::args().collect();
+    let flag = option_values(&args, "--lang").pop();
+    let overrides: Vec<ConfigOverride> = active_overrides()
+        .into_iter()
+        .chain(option_values(&args, "--set").iter().filter_map(|arg| ConfigOverride::parse_set(arg).ok()))
+        .collect();
+    let configured = configured_language(std::env::current_dir().ok().as_deref(), &overrides);
+
+    let catalog_dir = i18n::get_catalog_dir();
+    let available = i18n::available_locales(Some(&catalog_dir));
+    let locale = i18n::resolve_locale(flag.as_deref(), configured.as_deref(), &i18n::env_locales(), &available);
+    let localizer = Localizer::new(&locale, Some(&catalog_dir));
+    i18n::init(localizer);
+    log::debug!("{}", t!("config.log.locale", locale = i18n::current().locale()));
+}
+
+/// 命令行中 `--name value` 和 `--name=value` 形式的值（`--` 之后的参数不算）
+fn option_values(args: &[String], name: &str) -> Vec<String> {
+    let mut values = Vec::new();
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        if arg == "--" {
+            break;
+        }
+        if arg == name {
+            values.extend(iter.next().cloned());
+        } else if let Some(value) = arg.strip_prefix(name).and_then(|rest| rest.strip_prefix('=')) {
+            values.push(value.to_string());
+        }
+    }
+    values
+}
+
+/// 用消息目录中的 `<key>.about` 和 `<key>.args.<参数>` 替换命令和参数的说明，子命令的键为 `<key>.<子命令>`
+fn localize_command(mut command: clap::Command, key: &str) -> clap::Command {
+    let localizer = i18n::current();
+    if let Some(about) = localizer.lookup(&format!("{}.about", key)) {
+        command = command.about(about.to_string()).long_about(None);
+    }
+
+    let args: Vec<String> = command
+        .get_arguments()
+        .map(|arg| arg.get_id().to_string())
+        .filter(|id| id != "help" && id != "version")
+        .collect();
+    for id in args {
+        if let Some(help) = localizer.lookup(&format!("{}.args.{}", key, id)) {
+            command = command.mut_arg(id, |arg| arg.help(help.to_string()).long_help(None));
+        }
+    }
+
+    let subcommands: Vec<String> = command.get_subcommands().map(|sub| sub.get_name().to_string()).collect();
+    for name in subcommands {
+        let sub_key = format!("{}.{}", key, name);
+        command = command.mut_subcommand(&name, |sub| localize_command(sub, &sub_key));
+    }
+    command
+}
+
 /// 运行 CLI 命令
 pub fn run() -> Result<()> {
-    let cli = Cli::parse();
+    init_locale();
+    let matches = localize_command(Cli::command(), "help").get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+
+    let overrides = cli
+        .set
+        .iter()
+        .map(|arg| ConfigOverride::parse_set(arg))
+        .collect::<Result<Vec<_>>>()?;
+    set_cli_overrides(overrides);
 
     match cli.command {
-        Commands::Notify { status, duration, cmd } => {
-            handle_notify(status, duration.unwrap_or(0), cmd)
+        Commands::Notify { status, duration, cmd, from_hook } => {
+            handle_notify(status, duration.unwrap_or(0), cmd, from_hook)
+        }
+
+        Commands::Run { command } => {
+            handle_run(command)
+        }
+
+        Commands::History { status, since, until, project, limit, format } => {
+            handle_history(status, since, until, project, limit, format)
+        }
+
+        Commands::Stats { since, until, project, top, format } => {
+            handle_stats(since, until, project, top, format)
+        }
+
+        Commands::Policy { action } => match action {
+            PolicyCommands::Explain { cmd, status, duration, project, event } => {
+                let context = RuleContext {
+                    status: status.into(),
+                    duration,
+                    cmd: &cmd,
+                    project_path: None,
+                    event: event.as_deref(),
+                    now: Utc::now(),
+                };
+                let project_path = project.or_else(current_project_path);
+                handle_policy_explain(RuleContext {
+                    project_path: project_path.as_deref(),
+                    ..context
+                })
+            }
+        },
+
+        Commands::Template { action } => match action {
+            TemplateCommands::Render { name, text, status, cmd, duration, project, exit_code, session_id, event } => {
+                let status = status.into();
+                let project_path = project.or_else(current_project_path);
+                let project = project_path.as_deref()
+                    .and_then(|path| Path::new(path).file_name())
+                    .map(|name| name.to_string_lossy().to_string());
+                let title = build_title(status, &cmd);
+                let message = build_message(duration, &cmd);
+                let content = NotificationContent {
+                    cmd: &cmd,
+                    duration,
+                    project: project.as_deref(),
+                    project_path: project_path.as_deref(),
+                    event: event.as_deref(),
+                    session_id: session_id.as_deref(),
+                    exit_code,
+                    title: &title,
+                    message: &message,
+                };
+                handle_template_render(status, &content, name, text)
+            }
+            TemplateCommands::Vars => {
+                handle_template_vars();
+                Ok(())
+            }
+        },
+
+        Commands::Sound { action } => match action {
+            SoundCommands::Test { status, cmd, template } => {
+                handle_sound_test(status.into(), cmd, template)
+            }
+        },
+
+        Commands::Daemon { action } => {
+            handle_daemon(action)
+        }
+
+        Commands::FlushAggregation { window_start, delay } => {
+            handle_flush_aggregation(window_start, delay)
+        }
+
+        Commands::FlushDeferred { delay } => {
+            handle_flush_deferred(delay)
         }
 
         Commands::Init => {
@@ -85,8 +557,8 @@ pub fn run() -> Result<()> {
             handle_verify()
         }
 
-        Commands::Config => {
-            handle_config()
+        Commands::Config { action, show_origin } => {
+            handle_config(action, show_origin)
         }
 
         Commands::Test => {
@@ -96,221 +568,1371 @@ pub fn run() -> Result<()> {
 }
 
 /// 处理 notify 命令
-fn handle_notify(status: String, duration: u64, cmd: String) -> Result<()> {
-    log::info!("收到通知请求: status={}, duration={}, cmd={}", status, duration, cmd);
-
-    // 解析状态
-    let notification_status = match status.to_lowercase().as_str() {
-        "success" => NotificationStatus::Success,
-        "error" | "failed" | "failure" => NotificationStatus::Error,
-        "pending" | "running" => NotificationStatus::Pending,
-        _ => {
-            eprintln!("警告: 未知状态 '{}', 使用默认状态 'success'", status);
-            NotificationStatus::Success
+fn handle_notify(
+    status: Option<StatusArg>,
+    duration: u64,
+    cmd: Option<String>,
+    from_hook: bool,
+) -> Result<()> {
+    // 显式 --from-hook，或未提供任何参数且 stdin 非终端时，读取 hook 数据
+    let read_hook = from_hook || (status.is_none() && cmd.is_none() && !io::stdin().is_terminal());
+    let hook_input = if read_hook {
+        match HookInput::from_reader(io::stdin().lock()) {
+            Ok(input) => {
+                log::info!("{}", t!("hook.log.received", event = input.event_name(), cwd = format!("{:?}", input.cwd)));
+                Some(input)
+            }
+            Err(e) => {
+                log::warn!("{}", t!("hook.log.parse_failed", error = format!("{:#}", e)));
+                None
+            }
         }
+    } else {
+        None
     };
 
-    // 加载配置
-    let config = load_config()
-        .context("无法加载配置文件")?;
+    // 会话开始事件：仅记录开始时间，不发送通知
+    if let Some(input) = hook_input.as_ref().filter(|input| input.is_session_start()) {
+        if let Some(session_id) = &input.session_id {
+            let tracker = SessionTracker::new(get_session_file_path());
+            if let Err(e) = tracker.record_start(session_id) {
+                log::warn!("{}", t!("hook.log.session_failed", error = e));
+            }
+        }
+        return Ok(());
+    }
 
-    // 创建策略引擎
-    let policy_engine = PolicyEngine::new(config.clone());
+    // Stop / SubagentStop 未指定耗时时，从会话开始计算
+    let duration = match &hook_input {
+        Some(input) if input.is_stop() && duration == 0 => input
+            .session_id
+            .as_deref()
+            .and_then(|id| SessionTracker::new(get_session_file_path()).elapsed(id))
+            .unwrap_or(0),
+        _ => duration,
+    };
 
-    // 检查是否应该发送通知
-    if !policy_engine.should_notify(notification_status, duration, &cmd) {
-        log::info!("通知被策略过滤（时间阈值低于 {} 秒）", config.threshold.min_duration);
-        return Ok(());
+    // 命令行参数优先，缺失时由 hook 数据补全
+    let notification_status = match (status, &hook_input) {
+        (Some(status), _) => status.into(),
+        (None, Some(input)) => input.status(),
+        (None, None) => anyhow::bail!(t!("notify.missing_status")),
+    };
+    let cmd = match (cmd, &hook_input) {
+        (Some(cmd), _) => cmd,
+        (None, Some(input)) => input.command(),
+        (None, None) => anyhow::bail!(t!("notify.missing_cmd")),
+    };
+
+    log::info!(
+        "{}",
+        t!("notify.log.received", status = notification_status.as_str(), duration = duration, cmd = cmd)
+    );
+
+    // 构建通知内容
+    let title = match &hook_input {
+        Some(input) => input.title(),
+        None => build_title(notification_status, &cmd),
+    };
+    let message = match hook_input.as_ref().and_then(|input| input.message()) {
+        Some(message) if duration > 0 => build_message(duration, &message),
+        Some(message) => message,
+        None => build_message(duration, &cmd),
+    };
+
+    let project = hook_input
+        .as_ref()
+        .and_then(|input| input.project_name())
+        .or_else(current_project);
+    let project_path = hook_input
+        .as_ref()
+        .and_then(|input| input.cwd.clone())
+        .or_else(current_project_path);
+    let content = NotificationContent {
+        cmd: &cmd,
+        duration,
+        project: project.as_deref(),
+        project_path: project_path.as_deref(),
+        event: hook_input.as_ref().map(|input| input.event_name()),
+        session_id: hook_input.as_ref().and_then(|input| input.session_id.as_deref()),
+        exit_code: None,
+        title: &title,
+        message: &message,
+    };
+    dispatch_notification(notification_status, &content)
+}
+
+/// 经过策略过滤和聚合后发送通知
+///
+/// 守护进程在运行时转发给它处理，否则在当前进程内处理
+fn dispatch_notification(
+    notification_status: NotificationStatus,
+    content: &NotificationContent,
+) -> Result<()> {
+    #[cfg(unix)]
+    if let Some(result) = daemon::forward(&DaemonRequest::Notify(Box::new(content.to_request(notification_status)))) {
+        log::info!("{}", t!("notify.log.forwarded"));
+        return result;
+    }
+
+    NotifyRuntime::load(content.project_path.map(Path::new))?.dispatch(notification_status, content)
+}
+
+/// 处理通知所需的配置和通知渠道
+///
+/// 进程内处理时每次调用新建；`ccn daemon` 中常驻内存，配置文件变化时重新加载
+struct NotifyRuntime {
+    config: Config,
+    policy: PolicyEngine,
+    notifier: CompositeNotificationManager,
+    /// 安排聚合窗口或 defer 时段结束时的刷新，参数为刷新任务和等待毫秒数
+    schedule_flush: Rc<dyn Fn(FlushTask, u64)>,
+}
+
+impl NotifyRuntime {
+    /// 加载项目目录对应的配置，聚合窗口和 defer 时段结束时由后台进程发送通知
+    fn load(project_dir: Option<&Path>) -> Result<Self> {
+        let config = load_project_config(project_dir)
+            .context(t!("error.load_config"))?;
+        Ok(Self::new(config, Rc::new(schedule_flush)))
     }
 
-    // duration 为 0 或 error/pending 状态时直接发送（绕过聚合）
-    let should_bypass_aggregation = duration == 0
-        || matches!(notification_status, NotificationStatus::Error | NotificationStatus::Pending);
+    fn new(config: Config, schedule_flush: Rc<dyn Fn(FlushTask, u64)>) -> Self {
+        Self {
+            policy: PolicyEngine::new(config.clone()),
+            notifier: CompositeNotificationManager::from_config(&config),
+            config,
+            schedule_flush,
+        }
+    }
 
-    // 如果启用聚合且不需要绕过，使用聚合器
-    if config.aggregation.enabled && !should_bypass_aggregation {
-        return handle_aggregated_notification(&config, &status, duration, &cmd, notification_status);
+    /// 替换配置并重建通知渠道
+    #[cfg(unix)]
+    fn reload(&mut self, config: Config) {
+        self.policy = PolicyEngine::new(config.clone());
+        self.notifier = CompositeNotificationManager::from_config(&config);
+        self.config = config;
     }
 
-    // 直接发送通知
-    send_single_notification(notification_status, &cmd, duration, &config)
+    /// 按规则、免打扰时段和聚合设置处理一条通知
+    fn dispatch(
+        &self,
+        notification_status: NotificationStatus,
+        content: &NotificationContent,
+    ) -> Result<()> {
+        // 免打扰时段结束后，先发送暂存通知的摘要
+        let now = Utc::now();
+        if !self.policy.in_defer_period(now) {
+            self.flush_deferred();
+        }
+
+        // 按规则决定如何处理
+        let outcome = self.policy.evaluate(&RuleContext {
+            status: notification_status,
+            duration: content.duration,
+            cmd: content.cmd,
+            project_path: content.project_path,
+            event: content.event,
+            now,
+        });
+        let should_notify = outcome.should_notify();
+        let quiet_action = if should_notify {
+            self.policy.quiet_action(notification_status, now)
+        } else {
+            None
+        };
+
+        // 记录历史（包括被过滤的事件），失败不影响通知
+        let suppressed = !should_notify || quiet_action == Some(QuietAction::Suppress);
+        record_history(notification_status, content, suppressed);
+
+        if !should_notify {
+            log::info!("{}", t!("notify.log.dropped", rule = outcome.rule.as_deref().unwrap_or("default_action")));
+            return Ok(());
+        }
+
+        // 免打扰时段：丢弃或暂存，静音由 play_sound 处理
+        match quiet_action {
+            Some(QuietAction::Suppress) => {
+                log::info!("{}", t!("notify.log.quiet_dropped"));
+                return Ok(());
+            }
+            Some(QuietAction::Defer) => {
+                let queued = DeferredQueue::new(get_deferred_file_path())
+                    .push(notification_status.as_str(), content.duration, content.cmd)
+                    .context(t!("error.defer"))?;
+                log::info!("{}", t!("notify.log.quiet_deferred"));
+
+                // 队列中的第一条通知：安排在 defer 时段结束时发送摘要，不必等下一条通知
+                if queued == 1
+                    && let Some(end) = self.policy.defer_period_end(now)
+                {
+                    let delay = (end - now).num_milliseconds().max(0) as u64;
+                    log::info!("{}", t!("notify.log.digest_scheduled", delay = format_millis(delay)));
+                    (self.schedule_flush)(FlushTask::Deferred, delay);
+                }
+                return Ok(());
+            }
+            Some(QuietAction::Silence) | None => {}
+        }
+
+        // duration 为 0 或 error/pending 状态时直接发送（绕过聚合）
+        let should_bypass_aggregation = content.duration == 0
+            || matches!(notification_status, NotificationStatus::Error | NotificationStatus::Pending);
+
+        // 规则要求聚合，或启用聚合且不需要绕过时，使用聚合器
+        if outcome.action == RuleAction::Aggregate
+            || (self.config.aggregation.enabled && !should_bypass_aggregation)
+        {
+            return self.handle_aggregated(notification_status, content, &outcome);
+        }
+
+        // 直接发送通知
+        self.send_single(notification_status, content, &outcome)
+    }
+}
+/// 追加一条历史记录
+fn record_history(status: NotificationStatus, content: &NotificationContent, suppressed: bool) {
+    let entry = HistoryEntry {
+        timestamp: Local::now(),
+        status,
+        cmd: content.cmd.to_string(),
+        duration: content.duration,
+        project: content.project.map(str::to_string),
+        suppressed,
+    };
+
+    if let Err(e) = NotificationHistory::new(get_history_file_path()).append(&entry) {
+        log::warn!("{}", t!("notify.log.history_failed", error = format!("{:#}", e)));
+    }
+}
+
+/// 当前工作目录
+fn current_project_path() -> Option<String> {
+    std::env::current_dir()
+        .ok()
+        .map(|dir| dir.to_string_lossy().to_string())
+}
+
+/// 当前工作目录对应的项目名
+fn current_project() -> Option<String> {
+    std::env::current_dir()
+        .ok()?
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+}
+
+/// 处理 run 命令：执行子命令，结束后发送通知，并以子命令的退出状态退出
+fn handle_run(command: Vec<String>) -> Result<()> {
+    let cmd = command.join(" ");
+
+    let outcome = match run_command(&command) {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            // 与 shell 一致：命令无法执行时返回 127
+            eprintln!("ccn: {:#}", e);
+            std::process::exit(127);
+        }
+    };
+
+    let duration = outcome.elapsed.as_secs();
+    let notification_status = outcome.status();
+    log::info!("{}", t!("run.log.finished", cmd = cmd, outcome = outcome.describe(), duration = duration));
+
+    let title = build_title(notification_status, &cmd);
+    let message = if outcome.success() {
+        build_message(duration, &cmd)
+    } else {
+        t!("notify.message_failed", cmd = cmd, duration = format_secs(duration), outcome = outcome.describe())
+    };
+
+    // 通知失败不影响子命令的退出状态
+    let project = current_project();
+    let project_path = current_project_path();
+    let content = NotificationContent {
+        cmd: &cmd,
+        duration,
+        project: project.as_deref(),
+        project_path: project_path.as_deref(),
+        event: None,
+        session_id: None,
+        exit_code: outcome.exit_code,
+        title: &title,
+        message: &message,
+    };
+    if let Err(e) = dispatch_notification(notification_status, &content) {
+        log::warn!("{}", t!("notify.log.send_failed", error = format!("{:#}", e)));
+    }
+
+    exit_like(&outcome)
 }
 
-/// 处理聚合通知
-fn handle_aggregated_notification(
-    config: &Config,
-    status_str: &str,
+/// 单条通知的内容
+struct NotificationContent<'a> {
+    cmd: &'a str,
     duration: u64,
-    cmd: &str,
-    notification_status: NotificationStatus,
-) -> Result<()> {
-    let aggregator = NotificationAggregator::new(
-        get_state_file_path(),
-        config.aggregation.window,
-        config.aggregation.max_toasts,
-    );
+    project: Option<&'a str>,
+    project_path: Option<&'a str>,
+    /// 触发通知的 hook 事件名称
+    event: Option<&'a str>,
+    /// Claude Code 会话 ID
+    session_id: Option<&'a str>,
+    /// 命令的退出码
+    exit_code: Option<i32>,
+    title: &'a str,
+    message: &'a str,
+}
 
-    // 尝试添加到聚合器
-    match aggregator.add_notification(status_str, duration, cmd) {
-        Ok(Some(result)) => {
-            // 达到聚合条件，发送聚合通知
-            log::info!("发送聚合通知: {} 个任务", result.total);
-
-            let status = match result.status() {
-                "error" => NotificationStatus::Error,
-                _ => NotificationStatus::Success,
-            };
-
-            let notifier = get_notification_manager();
-            notifier.send_notification(
-                status,
-                &result.title(),
-                &result.message(),
-                config.templates.default.duration,
-            )?;
-        }
-        Ok(None) => {
-            // 添加到聚合缓冲区，暂不发送
-            log::info!("通知已添加到聚合缓冲区");
+impl NotificationContent<'_> {
+    /// 构造模板渲染上下文，`title` 和 `message` 为内置的默认文字
+    fn template_context(&self, status: NotificationStatus) -> TemplateContext {
+        TemplateContext {
+            status: Some(status),
+            cmd: self.cmd.to_string(),
+            duration: self.duration,
+            project: self.project.map(str::to_string),
+            cwd: self.project_path.map(str::to_string),
+            hostname: Some(gethostname::gethostname().to_string_lossy().into_owned()),
+            session_id: self.session_id.map(str::to_string),
+            exit_code: self.exit_code,
+            event: self.event.map(str::to_string),
+            title: self.title.to_string(),
+            message: self.message.to_string(),
         }
+    }
+}
+
+/// 按模板渲染通知标题和内容，模板未设置时回退到默认模板，再回退到内置文字
+fn render_content(
+    status: NotificationStatus,
+    content: &NotificationContent,
+    template: &TemplateConfig,
+    default: &TemplateConfig,
+) -> (String, String) {
+    let context = content.template_context(status);
+    let render = |source: Option<&String>, fallback: &str| match source {
+        Some(source) => template::render(source, &context).unwrap_or_else(|e| {
+            log::warn!("{}", t!("notify.log.render_failed", error = format!("{:#}", e)));
+            fallback.to_string()
+        }),
+        None => fallback.to_string(),
+    };
+    let title = render(template.title.as_ref().or(default.title.as_ref()), content.title);
+    let message = render(template.body.as_ref().or(default.body.as_ref()), content.message);
+    (title, message)
+}
+
+#[cfg(unix)]
+impl<'a> NotificationContent<'a> {
+    /// 转换为转发给守护进程的请求
+    fn to_request(&self, status: NotificationStatus) -> NotifyRequest {
+        NotifyRequest {
+            status,
+            cmd: self.cmd.to_string(),
+            duration: self.duration,
+            project: self.project.map(str::to_string),
+            project_path: self.project_path.map(str::to_string),
+            event: self.event.map(str::to_string),
+            session_id: self.session_id.map(str::to_string),
+            exit_code: self.exit_code,
+            title: self.title.to_string(),
+            message: self.message.to_string(),
+            overrides: active_overrides(),
+        }
+    }
+
+    /// 从守护进程收到的请求还原
+    fn from_request(request: &'a NotifyRequest) -> Self {
+        Self {
+            cmd: &request.cmd,
+            duration: request.duration,
+            project: request.project.as_deref(),
+            project_path: request.project_path.as_deref(),
+            event: request.event.as_deref(),
+            session_id: request.session_id.as_deref(),
+            exit_code: request.exit_code,
+            title: &request.title,
+            message: &request.message,
+        }
+    }
+}
+
+impl NotifyRuntime {
+    fn aggregator(&self) -> NotificationAggregator {
+        NotificationAggregator::new(
+            get_state_file_path(),
+            self.config.aggregation.window,
+            self.config.aggregation.max_toasts,
+        )
+    }
+
+    /// 处理聚合通知
+    fn handle_aggregated(
+        &self,
+        notification_status: NotificationStatus,
+        content: &NotificationContent,
+        outcome: &RuleOutcome,
+    ) -> Result<()> {
+        let window = self.config.aggregation.window;
+
+        // 尝试添加到聚合器，开启新窗口时记录项目和规则指定的渠道，供窗口结束时使用
+        let target = WindowTarget {
+            project_path: content.project_path.map(str::to_string),
+            sinks: outcome.sinks.clone(),
+        };
+        let added = self
+            .aggregator()
+            .with_target(target)
+            .add_notification(notification_status.as_str(), content.duration, content.cmd);
+        match added {
+            Ok(added) => {
+                // 上一个窗口遗留的通知或达到聚合条件的通知，立即发送
+                for result in &added.ready {
+                    log::info!("{}", t!("aggregate.log.send", total = result.total));
+                    self.send_window_result(result)?;
+                }
+
+                // 新窗口：安排在窗口结束时发送
+                match added.new_window {
+                    Some(window_start) => {
+                        log::info!("{}", t!("aggregate.log.added_scheduled", delay = format_millis(window)));
+                        (self.schedule_flush)(FlushTask::Aggregation(window_start), window);
+                    }
+                    None if added.ready.is_empty() => log::info!("{}", t!("aggregate.log.added")),
+                    None => {}
+                }
+            }
+            Err(e) => {
+                log::warn!("{}", t!("aggregate.log.failed", error = e));
+                self.send_single(notification_status, content, outcome)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 聚合窗口结束时发送缓冲区中的通知，窗口已发送或已开启新窗口时不做任何事
+    fn flush_window(&self, window_start: u64) -> Result<()> {
+        if let Some(result) = self.aggregator().flush_window(window_start)? {
+            log::info!("{}", t!("aggregate.log.window_ended", total = result.total));
+            self.send_window_result(&result)?;
+        }
+
+        Ok(())
+    }
+
+    /// 按聚合窗口记录的目标发送：项目中有 `.ccn.yaml` 时使用该项目的配置，并只发送到规则指定的渠道
+    fn send_window_result(&self, result: &AggregatedResult) -> Result<()> {
+        let target = &result.target;
+        let project_dir = target.project_path.as_deref().map(Path::new);
+        if project_dir.and_then(find_project_config).is_some() {
+            let config = load_project_config(project_dir)
+                .context(t!("error.load_project_config"))?;
+            return NotifyRuntime::new(config, Rc::clone(&self.schedule_flush))
+                .send_aggregated_result(&result.title(), result, &target.sinks);
+        }
+
+        self.send_aggregated_result(&result.title(), result, &target.sinks)
+    }
+
+    /// 发送聚合结果（聚合通知或免打扰摘要），`sinks` 为空表示发送到全部渠道
+    fn send_aggregated_result(
+        &self,
+        title: &str,
+        result: &AggregatedResult,
+        sinks: &[String],
+    ) -> Result<()> {
+        let status = match result.status() {
+            "error" => NotificationStatus::Error,
+            _ => NotificationStatus::Success,
+        };
+
+        let context = NotificationContext {
+            cmd: result.notifications.iter().map(|n| n.cmd.as_str()).collect::<Vec<_>>().join(", "),
+            duration_sec: result.notifications.iter().map(|n| n.duration).sum(),
+        };
+
+        self.notifier.send_routed(
+            sinks,
+            status,
+            title,
+            &result.message(),
+            self.config.templates.default.duration,
+            &context,
+        )
+    }
+
+    /// 发送免打扰期间暂存的通知摘要，失败只记录警告
+    fn flush_deferred(&self) {
+        let result = match DeferredQueue::new(get_deferred_file_path()).take() {
+            Ok(Some(result)) => result,
+            Ok(None) => return,
+            Err(e) => {
+                log::warn!("{}", t!("notify.log.deferred_read_failed", error = format!("{:#}", e)));
+                return;
+            }
+        };
+
+        log::info!("{}", t!("notify.log.digest_send", total = result.total));
+        let title = t!("notify.deferred_title", title = result.title());
+        if let Err(e) = self.send_aggregated_result(&title, &result, &[]) {
+            log::warn!("{}", t!("notify.log.digest_failed", error = format!("{:#}", e)));
+        }
+    }
+
+    /// 发送单个通知
+    fn send_single(
+        &self,
+        status: NotificationStatus,
+        content: &NotificationContent,
+        outcome: &RuleOutcome,
+    ) -> Result<()> {
+        let config = &self.config;
+
+        let template_name = self.policy.match_template(content.cmd)
+            .unwrap_or_else(|| "default".to_string());
+
+        // 播放音效
+        self.play_sound(status, &template_name, outcome.force_sound);
+
+        // 发送通知
+        let templates = &config.templates;
+        let template = templates.get(&template_name);
+        let duration_ms = template.duration;
+
+        let (title, message) = render_content(status, content, template, &templates.default);
+        let context = NotificationContext {
+            cmd: content.cmd.to_string(),
+            duration_sec: content.duration,
+        };
+        self.notifier
+            .send_routed(&outcome.sinks, status, &title, &message, duration_ms, &context)
+            .context(t!("error.send"))?;
+
+        log::info!("{}", t!("notify.log.sent"));
+        Ok(())
+    }
+
+    /// 播放模板在该状态下的音效，`force` 为 true 时忽略声音设置和免打扰时段
+    fn play_sound(&self, status: NotificationStatus, template_name: &str, force: bool) {
+        let config = &self.config;
+        if !force && !self.policy.should_play_sound(status, Utc::now()) {
+            return;
+        }
+
+        let sound = Sound::from_config(config.templates.sound(template_name, status), status);
+        log::debug!("{}", t!("sound.log.play", template = template_name, sound = format!("{:?}", sound)));
+        if let Err(e) = sound_player(config).play(&sound) {
+            log::warn!("{}: {:#}", t!("sound.failed"), e);
+        }
+    }
+}
+
+/// 按配置的声音主题和音量创建播放器
+fn sound_player(config: &Config) -> SoundPlayer {
+    SoundPlayer::new(true)
+        .with_theme(&config.sound_theme)
+        .with_volume(f32::from(config.sound_volume) / 100.0)
+}
+
+/// 启动后台进程，在聚合窗口或 defer 时段结束时发送通知
+fn schedule_flush(task: FlushTask, delay_ms: u64) {
+    let exe = match std::env::current_exe() {
+        Ok(exe) => exe,
         Err(e) => {
-            log::warn!("聚合失败，发送单个通知: {}", e);
-            send_single_notification(notification_status, cmd, duration, config)?;
+            log::warn!("{}", t!("aggregate.log.no_exe", error = e));
+            return;
+        }
+    };
+
+    let mut command = std::process::Command::new(exe);
+    match task {
+        FlushTask::Aggregation(window_start) => {
+            command.args(["flush-aggregation", "--window-start", &window_start.to_string()]);
         }
+        FlushTask::Deferred => {
+            command.arg("flush-deferred");
+        }
+    }
+    command
+        .args(["--delay", &delay_ms.to_string()])
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null());
+
+    // 脱离当前进程组/控制台，避免终端关闭或 Ctrl+C 时被一起结束
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const DETACHED_PROCESS: u32 = 0x0000_0008;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(DETACHED_PROCESS | CREATE_NO_WINDOW);
     }
 
+    if let Err(e) = command.spawn() {
+        log::warn!("{}", t!("aggregate.log.spawn_failed", error = e));
+    }
+}
+
+/// 处理 flush-aggregation 命令（由 schedule_flush 启动）
+fn handle_flush_aggregation(window_start: u64, delay: u64) -> Result<()> {
+    std::thread::sleep(std::time::Duration::from_millis(delay));
+    NotifyRuntime::load(None)?.flush_window(window_start)
+}
+
+/// 处理 flush-deferred 命令（由 schedule_flush 启动），时段被延长时留给之后的调用发送
+fn handle_flush_deferred(delay: u64) -> Result<()> {
+    std::thread::sleep(std::time::Duration::from_millis(delay));
+    let runtime = NotifyRuntime::load(None)?;
+    if !runtime.policy.in_defer_period(Utc::now()) {
+        runtime.flush_deferred();
+    }
     Ok(())
 }
 
-/// 发送单个通知
-fn send_single_notification(
-    status: NotificationStatus,
-    cmd: &str,
-    duration: u64,
-    config: &Config,
+/// 处理 daemon 命令
+#[cfg(unix)]
+fn handle_daemon(action: Option<DaemonCommands>) -> Result<()> {
+    match action.unwrap_or(DaemonCommands::Start) {
+        DaemonCommands::Start => run_daemon(),
+
+        DaemonCommands::Stop => {
+            match daemon::forward(&DaemonRequest::Shutdown) {
+                Some(result) => {
+                    result?;
+                    println!("{}", t!("daemon.stopped"));
+                }
+                None => println!("{}", t!("daemon.not_running")),
+            }
+            Ok(())
+        }
+
+        DaemonCommands::Status => {
+            match daemon::forward(&DaemonRequest::Ping) {
+                Some(Ok(())) => println!("{}", t!("daemon.running", socket = get_socket_path().display())),
+                Some(Err(e)) => println!("{}", t!("daemon.unresponsive", error = format!("{:#}", e))),
+                None => println!("{}", t!("daemon.not_running")),
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn handle_daemon(_action: Option<DaemonCommands>) -> Result<()> {
+    anyhow::bail!(t!("daemon.unsupported"))
+}
+
+/// 在前台运行守护进程，直到收到 `ccn daemon stop`
+#[cfg(unix)]
+fn run_daemon() -> Result<()> {
+    /// 没有请求时检查免打扰暂存队列和配置文件的间隔
+    const TICK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+    let socket_path = get_socket_path();
+    let daemon = Daemon::start(&socket_path)?;
+
+    let config = load_config()
+        .context(t!("error.load_config"))?;
+    let mut config_modified = config_modified_time();
+    let mut runtime = NotifyRuntime::new(config, Rc::new(daemon.flush_scheduler()));
+
+    println!("{}", t!("daemon.started", socket = socket_path.display()));
+    println!("{}", t!("daemon.stop_hint"));
+
+    loop {
+        let event = daemon.next_event(TICK_INTERVAL);
+
+        // 配置文件变化时重新加载，加载失败时沿用旧配置
+        let modified = config_modified_time();
+        if modified != config_modified {
+            config_modified = modified;
+            match load_config() {
+                Ok(config) => {
+                    log::info!("{}", t!("daemon.log.reload"));
+                    runtime.reload(config);
+                }
+                Err(e) => log::warn!("{}", t!("daemon.log.reload_failed", error = format!("{:#}", e))),
+            }
+        }
+
+        match event {
+            DaemonEvent::Notify(request, responder) => {
+                log::info!("{}", t!("daemon.log.received", status = request.status.as_str(), cmd = request.cmd));
+                let content = NotificationContent::from_request(&request);
+
+                // 项目中有 .ccn.yaml 或客户端有临时覆盖时按请求加载配置，否则复用常驻的配置和通知渠道
+                let project_dir = request.project_path.as_deref().map(Path::new);
+                let result = if project_dir.and_then(find_project_config).is_some() || !request.overrides.is_empty() {
+                    load_layered_config(project_dir, &request.overrides)
+                        .context(t!("error.load_project_config"))
+                        .and_then(|layered| {
+                            NotifyRuntime::new(layered.config, Rc::clone(&runtime.schedule_flush))
+                                .dispatch(request.status, &content)
+                        })
+                } else {
+                    runtime.dispatch(request.status, &content)
+                };
+                responder.reply(result);
+            }
+            DaemonEvent::Flush(FlushTask::Aggregation(window_start)) => {
+                if let Err(e) = runtime.flush_window(window_start) {
+                    log::warn!("{}", t!("aggregate.log.send_failed", error = format!("{:#}", e)));
+                }
+            }
+            DaemonEvent::Flush(FlushTask::Deferred) | DaemonEvent::Tick => {
+                // 免打扰时段结束后及时发送暂存的通知，不必等下一条通知
+                if !runtime.policy.in_defer_period(Utc::now()) {
+                    runtime.flush_deferred();
+                }
+            }
+            DaemonEvent::Shutdown(responder) => {
+                responder.reply(Ok(()));
+                break;
+            }
+        }
+    }
+
+    println!("{}", t!("daemon.stopped"));
+    Ok(())
+}
+
+/// 配置文件的修改时间，文件不存在时为 None
+#[cfg(unix)]
+fn config_modified_time() -> Option<std::time::SystemTime> {
+    std::fs::metadata(get_config_path())
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+/// 处理 history 命令
+fn handle_history(
+    status: Option<StatusArg>,
+    since: Option<String>,
+    until: Option<String>,
+    project: Option<String>,
+    limit: usize,
+    format: OutputFormat,
 ) -> Result<()> {
-    // 播放音效
-    play_notification_sound(status, config);
+    let filter = HistoryFilter {
+        status: status.map(NotificationStatus::from),
+        since: since.as_deref().map(parse_date).transpose()?,
+        until: until.as_deref().map(parse_date).transpose()?,
+        project,
+    };
 
-    let notifier = get_notification_manager();
+    let mut entries = NotificationHistory::new(get_history_file_path()).query(&filter)?;
 
-    // 构建通知内容
-    let title = build_title(status, cmd);
-    let message = build_message(duration, cmd);
-
-    // 发送通知
-    let template_name = PolicyEngine::new(config.clone()).match_template(cmd);
-    let duration_ms = if let Some(name) = &template_name {
-        if name == "default" {
-            config.templates.default.duration
-        } else {
-            config.templates.custom.get(name)
-                .map(|t| t.duration)
-                .unwrap_or(config.templates.default.duration)
+    // 只保留最近的 limit 条
+    if limit > 0 && entries.len() > limit {
+        entries.drain(..entries.len() - limit);
+    }
+
+    match format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&entries)?);
         }
-    } else {
-        config.templates.default.duration
+        OutputFormat::Table => {
+            if entries.is_empty() {
+                println!("{}", t!("history.empty"));
+                return Ok(());
+            }
+
+            println!(
+                "{:<19}  {:<7}  {:>8}  {:<16}  {:<4}  {}",
+                t!("history.time"),
+                t!("history.status"),
+                t!("history.duration"),
+                t!("history.project"),
+                t!("history.suppressed"),
+                t!("history.command"),
+            );
+            for entry in &entries {
+                println!(
+                    "{:<19}  {:<7}  {:>8}  {:<16}  {:<4}  {}",
+                    entry.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                    entry.status.as_str(),
+                    format_secs(entry.duration),
+                    entry.project.as_deref().unwrap_or("-"),
+                    if entry.suppressed { t!("history.yes") } else { String::new() },
+                    entry.cmd,
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// 处理 stats 命令
+fn handle_stats(
+    since: Option<String>,
+    until: Option<String>,
+    project: Option<String>,
+    top: usize,
+    format: OutputFormat,
+) -> Result<()> {
+    let filter = HistoryFilter {
+        status: None,
+        since: since.as_deref().map(parse_date).transpose()?,
+        until: until.as_deref().map(parse_date).transpose()?,
+        project,
     };
 
-    notifier.send_notification(status, &title, &message, duration_ms)
-        .context("发送通知失败")?;
+    let entries = NotificationHistory::new(get_history_file_path()).query(&filter)?;
+    let report = UsageReport::from_entries(&entries, top);
+
+    match format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+        OutputFormat::Table => print_stats(&report),
+    }
 
-    log::info!("通知已发送");
     Ok(())
 }
 
-/// 播放通知音效
-fn play_notification_sound(status: NotificationStatus, config: &Config) {
-    if !config.sound_enabled {
+/// 以文本形式输出统计报告
+fn print_stats(report: &UsageReport) {
+    let (Some(first), Some(last)) = (report.first_date, report.last_date) else {
+        println!("{}", t!("history.empty"));
         return;
+    };
+
+    println!("📊 {}", t!("stats.heading", first = first, last = last));
+    println!();
+
+    let overall = &report.overall;
+    println!("{}", t!(
+        "stats.overall",
+        total = overall.total,
+        success = overall.success,
+        error = overall.error,
+        pending = overall.pending,
+        suppressed = overall.suppressed,
+    ));
+    println!("{}", t!("stats.success_rate", rate = format_rate(overall.success_rate())));
+
+    if let Some(d) = &report.durations {
+        println!("{}", t!(
+            "stats.durations",
+            mean = format_secs(d.mean.round() as u64),
+            p50 = format_secs(d.p50),
+            p90 = format_secs(d.p90),
+            p99 = format_secs(d.p99),
+            min = format_secs(d.min),
+            max = format_secs(d.max),
+            count = d.count,
+        ));
     }
 
-    let sound_player = SoundPlayer::new(true);
+    println!();
+    println!("{}", t!("stats.by_day"));
+    print_counts_table(&t!("stats.date"), report.by_day.iter().map(|(day, counts)| (day.to_string(), counts)));
 
-    // 尝试播放自定义音效
-    let template_name = PolicyEngine::new(config.clone()).match_template("");
-    let sound_file = if let Some(name) = &template_name {
-        if name == "default" {
-            &config.templates.default.sound
-        } else {
-            config.templates.custom.get(name)
-                .map(|t| &t.sound)
-                .unwrap_or(&config.templates.default.sound)
+    println!();
+    println!("{}", t!("stats.by_project"));
+    print_counts_table(&t!("stats.project"), report.by_project.iter().map(|(project, counts)| (project.clone(), counts)));
+
+    if !report.top_commands.is_empty() {
+        println!();
+        println!("{}", t!("stats.top_commands"));
+        for command in &report.top_commands {
+            println!("  {:>5}  {}", command.count, command.cmd);
         }
+    }
+}
+
+fn print_counts_table<'a>(label: &str, rows: impl Iterator<Item = (String, &'a StatusCounts)>) {
+    println!(
+        "  {:<16}  {:>5}  {:>5}  {:>5}  {:>7}",
+        label,
+        t!("stats.total"),
+        t!("stats.success"),
+        t!("stats.error"),
+        t!("stats.rate"),
+    );
+    for (name, counts) in rows {
+        println!("  {:<16}  {:>5}  {:>5}  {:>5}  {:>7}",
+            name, counts.total, counts.success, counts.error, format_rate(counts.success_rate()));
+    }
+}
+
+/// 处理 policy explain 命令
+fn handle_policy_explain(context: RuleContext) -> Result<()> {
+    let config = load_project_config(context.project_path.map(Path::new))
+        .context(t!("error.load_config"))?;
+    let explanation = PolicyEngine::new(config.clone()).explain(&context);
+
+    println!("{}", t!("policy.command", cmd = context.cmd));
+    println!("{}", t!("policy.status", status = context.status.as_str(), duration = format_secs(context.duration)));
+    println!("{}", t!("policy.project", project = context.project_path.unwrap_or("-")));
+    println!("{}", t!("policy.event", event = context.event.unwrap_or("-")));
+
+    println!();
+    if config.rules.is_empty() {
+        println!("{}", t!("policy.rules_generated"));
     } else {
-        &config.templates.default.sound
+        println!("{}", t!("policy.rules"));
+    }
+    print_rule_traces(&explanation.rules);
+
+    println!();
+    println!("{}", t!("policy.templates"));
+    print_rule_traces(&explanation.templates);
+    println!("{}", t!("policy.template", name = explanation.template));
+
+    println!();
+    let outcome = &explanation.outcome;
+    let reason = match &outcome.rule {
+        Some(rule) => t!("policy.matched_rule", rule = rule),
+        None => t!("policy.no_rule"),
+    };
+    let verdict = match outcome.action {
+        RuleAction::Notify => format!("✅ {}", t!("policy.verdict.notify")),
+        RuleAction::Aggregate => format!("📦 {}", t!("policy.verdict.aggregate")),
+        RuleAction::Drop => format!("❌ {}", t!("policy.verdict.drop")),
     };
+    println!("{}", t!("policy.result", verdict = verdict, reason = reason));
+    if outcome.force_sound {
+        println!("{}", t!("policy.force_sound"));
+    }
+    if !outcome.sinks.is_empty() {
+        println!("{}", t!("policy.sinks", sinks = outcome.sinks.join(", ")));
+    }
 
-    // 如果配置了自定义音效文件
-    if sound_file != "default" && !sound_file.is_empty() {
-        if let Err(e) = sound_player.play_sound_file(sound_file) {
-            log::warn!("播放自定义音效失败: {}", e);
-        }
-        return;
+    if let Some(action) = explanation.quiet_action {
+        println!("{}", t!("policy.quiet", action = format!("{:?}", action)));
+    }
+
+    Ok(())
+}
+
+/// 处理 template render 命令
+fn handle_template_render(
+    status: NotificationStatus,
+    content: &NotificationContent,
+    name: Option<String>,
+    text: Option<String>,
+) -> Result<()> {
+    if let Some(text) = text {
+        let rendered = template::render(&text, &content.template_context(status))
+            .context(t!("template.syntax_error"))?;
+        println!("{}", rendered);
+        return Ok(());
+    }
+
+    let config = load_project_config(content.project_path.map(Path::new))
+        .context(t!("error.load_config"))?;
+    let templates = &config.templates;
+    let name = match name {
+        Some(name) => name,
+        None => PolicyEngine::new(config.clone())
+            .match_template(content.cmd)
+            .unwrap_or_else(|| "default".to_string()),
+    };
+    let template = if name == "default" {
+        &templates.default
+    } else {
+        templates.custom.get(&name)
+            .with_context(|| t!("template.not_found", name = name))?
+    };
+
+    let (title, message) = render_content(status, content, template, &templates.default);
+
+    println!("{}", t!("template.name", name = name));
+    println!("{}", t!("template.sample", status = status.as_str(), cmd = content.cmd, duration = format_secs(content.duration)));
+    println!();
+    println!("{}", t!("template.title", title = title));
+    println!("{}", t!("template.body", body = message));
+    Ok(())
+}
+
+/// 处理 template vars 命令
+fn handle_template_vars() {
+    println!("{}", t!("template.variables"));
+    for name in template::VARIABLES {
+        println!("  {:<16} {}", name, t!(&format!("template.var.{}", name)));
+    }
+    println!();
+    println!("{}", t!("template.filters"));
+    for name in template::FILTERS {
+        println!("  {:<16} {}", name, t!(&format!("template.filter.{}", name)));
     }
+}
 
-    // 否则播放系统提示音
-    let system_sound = match status {
-        NotificationStatus::Success => SystemSound::Success,
-        NotificationStatus::Error => SystemSound::Error,
-        NotificationStatus::Pending => SystemSound::Notification,
+/// 处理 sound test 命令
+fn handle_sound_test(status: NotificationStatus, cmd: Option<String>, template: Option<String>) -> Result<()> {
+    let config = load_project_config(std::env::current_dir().ok().as_deref())
+        .context(t!("error.load_config"))?;
+    let name = match (template, cmd) {
+        (Some(name), _) => {
+            if name != "default" && !config.templates.custom.contains_key(&name) {
+                anyhow::bail!(t!("template.not_found", name = name));
+            }
+            name
+        }
+        (None, Some(cmd)) => PolicyEngine::new(config.clone())
+            .match_template(&cmd)
+            .unwrap_or_else(|| "default".to_string()),
+        (None, None) => "default".to_string(),
     };
 
-    if let Err(e) = sound_player.play_system_sound(system_sound) {
-        log::warn!("播放系统音效失败: {}", e);
+    let sound = Sound::from_config(config.templates.sound(&name, status), status);
+    println!("{}", t!("template.name", name = name));
+    println!("{}", t!("sound.status", status = status.as_str()));
+    let description = match &sound {
+        Sound::Silent => t!("sound.silent"),
+        Sound::System(_) if cfg!(all(unix, not(target_os = "macos"))) => {
+            t!("sound.system_theme", theme = config.sound_theme)
+        }
+        Sound::System(_) => t!("sound.system"),
+        Sound::File(path) => path.clone(),
+    };
+    println!("{}", t!("sound.sound", sound = description));
+    println!("{}", t!("sound.volume", volume = config.sound_volume));
+
+    if !config.sound_enabled {
+        println!("{}", t!("sound.disabled"));
+    }
+    if !cfg!(feature = "sound") && !matches!(sound, Sound::Silent) {
+        println!("{}", t!("sound.no_feature"));
+    }
+
+    sound_player(&config).play(&sound).context(t!("sound.failed"))
+}
+
+fn print_rule_traces(traces: &[RuleTrace]) {
+    if traces.is_empty() {
+        println!("  {}", t!("policy.none"));
+        return;
+    }
+
+    let first_match = traces.iter().position(|trace| trace.matched);
+    for (index, trace) in traces.iter().enumerate() {
+        let mark = if trace.matched { "✓" } else { "✗" };
+        let note = match (&trace.error, first_match == Some(index)) {
+            (Some(error), _) => format!("  ⚠️ {}", t!("policy.invalid_rule", error = error)),
+            (None, true) => format!("  ← {}", t!("policy.effective")),
+            (None, false) => String::new(),
+        };
+        println!("  {} {} {}{}", mark, trace.name, trace.rule, note);
     }
 }
 
+/// 解析 YYYY-MM-DD 格式的日期
+fn parse_date(date: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .with_context(|| t!("error.date_format", date = date))
+}
+
 /// 处理 test 命令
 fn handle_test() -> Result<()> {
-    println!("发送测试通知...");
+    println!("{}", t!("test.sending"));
 
-    let notifier = get_notification_manager();
+    let config = load_config()
+        .context(t!("error.load_config"))?;
+    let notifier = get_configured_notification_manager(&config);
 
     if !notifier.is_available() {
-        println!("警告: 通知系统不可用");
+        println!("{}", t!("test.unavailable"));
         return Ok(());
     }
 
     // 发送成功测试通知
     notifier.send_notification(
         NotificationStatus::Success,
-        "CCN 测试通知",
-        "CCN 已成功集成！",
+        &t!("test.title"),
+        &t!("test.message"),
         5000,
     )?;
 
-    println!("测试通知已发送");
+    println!("{}", t!("test.sent"));
     Ok(())
 }
 
 /// 处理 config 命令
-fn handle_config() -> Result<()> {
-    let config = load_config()
-        .context("无法加载配置文件")?;
-
-    println!("当前配置：");
-    println!("版本: {}", config.version);
-    println!("声音: {}", if config.sound_enabled { "启用" } else { "禁用" });
-    println!("专注助手模式: {:?}", config.focus_assistant_mode);
-    println!("最小阈值: {} 秒", config.threshold.min_duration);
-    println!("白名单: {:?}", config.threshold.whitelist);
-    println!("聚合: {}", if config.aggregation.enabled { "启用" } else { "禁用" });
-    println!("聚合窗口: {} 毫秒", config.aggregation.window);
-    println!("日志级别: {}", config.logging.level);
+fn handle_config(action: Option<ConfigCommands>, show_origin: bool) -> Result<()> {
+    match action {
+        None => handle_config_summary(show_origin),
+        Some(ConfigCommands::Get { key }) => handle_config_get(&key),
+        Some(ConfigCommands::Set { key, value }) => handle_config_set(&key, &value),
+        Some(ConfigCommands::Unset { key }) => handle_config_unset(&key),
+        Some(ConfigCommands::Edit) => handle_config_edit(),
+        Some(ConfigCommands::Validate { file }) => handle_config_validate(file),
+        Some(ConfigCommands::Migrate) => handle_config_migrate(),
+        Some(ConfigCommands::Show { format }) => handle_config_show(format),
+    }
+}
+
+/// 当前目录的生效配置（全局配置 + 项目配置 + 临时覆盖）
+fn load_current_layered_config() -> Result<LayeredConfig> {
+    let project_dir = std::env::current_dir().ok();
+    load_layered_config(project_dir.as_deref(), &active_overrides())
+        .context(t!("error.load_config"))
+}
+
+/// 显示生效配置的摘要，`show_origin` 时列出每个配置项的来源
+fn handle_config_summary(show_origin: bool) -> Result<()> {
+    let layered = load_current_layered_config()?;
+
+    for (source, _) in &layered.layers {
+        match source {
+            ConfigSource::Global(path) => println!("{}", t!("config.global_file", path = path.display())),
+            ConfigSource::Project(path) => println!("{}", t!("config.project_file", path = path.display())),
+            ConfigSource::Env(_) | ConfigSource::Cli => println!("{}", t!("config.override", source = source)),
+            ConfigSource::Default => {}
+        }
+    }
+    println!();
+
+    if show_origin {
+        for (key, value, source) in layered.origins()? {
+            let value = serde_json::to_string(&value).unwrap_or_default();
+            println!("{} = {}    # {}", key, value, source);
+        }
+        return Ok(());
+    }
+
+    let config = layered.config;
+    let on_off = |enabled: bool| if enabled { t!("config.summary.on") } else { t!("config.summary.off") };
+    println!("{}", t!("config.summary.heading"));
+    println!("{}", t!("config.summary.version", version = config.version));
+    println!("{}", t!("config.summary.sound", value = on_off(config.sound_enabled)));
+    println!("{}", t!("config.summary.focus", value = format!("{:?}", config.focus_assistant_mode)));
+    println!("{}", t!("config.summary.threshold", value = format_secs(config.threshold.min_duration)));
+    let whitelist: Vec<String> = config.threshold.whitelist.iter().map(|rule| rule.to_string()).collect();
+    println!("{}", t!("config.summary.whitelist", value = whitelist.join(", ")));
+    println!("{}", t!("config.summary.aggregation", value = on_off(config.aggregation.enabled)));
+    println!("{}", t!("config.summary.window", value = format_millis(config.aggregation.window)));
+    println!("{}", t!("config.summary.log_level", value = config.logging.level));
+    let event_names: Vec<&str> = config.hooks.events.iter().map(|e| e.event_name()).collect();
+    println!("{}", t!("config.summary.hooks", value = event_names.join(", ")));
+    println!("{}", t!(
+        "config.summary.quiet_hours",
+        count = config.schedule.quiet_hours.len(),
+        timezone = config.schedule.timezone,
+    ));
+    println!("\n{}", t!("config.summary.show_hint"));
+
+    Ok(())
+}
+
+/// 处理 config get 命令
+fn handle_config_get(key: &str) -> Result<()> {
+    let layered = load_current_layered_config()?;
+    let effective = serde_yaml::to_value(&layered.config)?;
+    let value = lookup(&effective, key)
+        .with_context(|| t!("config.key_not_set", key = key))?;
+
+    match value {
+        serde_yaml::Value::String(text) => println!("{}", text),
+        other => print!("{}", serde_yaml::to_string(other)?),
+    }
+    Ok(())
+}
+
+/// 处理 config set 命令
+fn handle_config_set(key: &str, value: &str) -> Result<()> {
+    let path = ensure_config_file()?;
+    let mut document = ConfigDocument::load(&path)?;
+    document.set(key, value)?;
+    document.save()?;
 
+    println!("{}", t!("config.set", key = key, value = value));
+    warn_if_shadowed(key)
+}
+
+/// 处理 config unset 命令
+fn handle_config_unset(key: &str) -> Result<()> {
+    let path = ensure_config_file()?;
+    let mut document = ConfigDocument::load(&path)?;
+    document.unset(key)?;
+    document.save()?;
+
+    println!("{}", t!("config.unset", key = key));
+    warn_if_shadowed(key)
+}
+
+/// 修改全局配置后，提示当前生效值是否被项目配置或临时覆盖遮盖
+fn warn_if_shadowed(key: &str) -> Result<()> {
+    let layered = load_current_layered_config()?;
+    let shadowed_by = layered
+        .origins()?
+        .into_iter()
+        .find(|(origin_key, _, _)| origin_key == key || origin_key.starts_with(&format!("{}.", key)))
+        .map(|(_, _, source)| source)
+        .filter(|source| !matches!(source, ConfigSource::Global(_) | ConfigSource::Default));
+
+    if let Some(source) = shadowed_by {
+        println!("{}", t!("config.shadowed", source = source));
+    }
+    Ok(())
+}
+
+/// 处理 config edit 命令：编辑临时副本，校验通过后才写回配置文件
+fn handle_config_edit() -> Result<()> {
+    let path = ensure_config_file()?;
+    let original = std::fs::read_to_string(&path)
+        .with_context(|| t!("error.read_config", path = format!("{:?}", path)))?;
+
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| if cfg!(windows) { "notepad".to_string() } else { "vi".to_string() });
+    let mut editor_args = editor.split_whitespace();
+    let program = editor_args.next().context(t!("config.edit.empty_editor"))?;
+    let editor_args: Vec<&str> = editor_args.collect();
+
+    let temp_path = path.with_file_name(".config.edit.yaml");
+    std::fs::write(&temp_path, &original)
+        .with_context(|| t!("config.edit.create_temp", path = format!("{:?}", temp_path)))?;
+
+    let result = loop {
+        let status = std::process::Command::new(program)
+            .args(&editor_args)
+            .arg(&temp_path)
+            .status()
+            .with_context(|| t!("config.edit.launch", editor = editor));
+        match status {
+            Ok(status) if status.success() => {}
+            Ok(status) => break Err(anyhow::anyhow!(t!("config.edit.editor_failed", status = status))),
+            Err(e) => break Err(e),
+        }
+
+        // 出错时 break 而不是 `?` 返回，保证临时文件总会被删除
+        let edited = match std::fs::read_to_string(&temp_path)
+            .with_context(|| t!("config.edit.read_temp", path = format!("{:?}", temp_path)))
+        {
+            Ok(edited) => edited,
+            Err(e) => break Err(e),
+        };
+        if edited == original {
+            println!("{}", t!("config.edit.unchanged"));
+            break Ok(());
+        }
+
+        match validate_config_text(&path, &edited) {
+            Ok(_) => {
+                if let Err(e) = crate::state_file::write_atomic(&path, edited.as_bytes()) {
+                    break Err(e);
+                }
+                println!("{}", t!("config.edit.saved", path = path.display()));
+                break Ok(());
+            }
+            Err(e) => {
+                eprintln!("{}", t!("config.edit.invalid", error = format!("{:#}", e)));
+                let retry = if io::stdin().is_terminal() { confirm(&t!("config.edit.retry")) } else { Ok(false) };
+                match retry {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        println!("{}", t!("config.edit.discarded"));
+                        break Ok(());
+                    }
+                    Err(e) => break Err(e),
+                }
+            }
+        }
+    };
+
+    let _ = std::fs::remove_file(&temp_path);
+    result
+}
+
+/// 询问是/否，默认为是
+fn confirm(prompt: &str) -> Result<bool> {
+    use std::io::Write;
+
+    print!("{}", prompt);
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(!matches!(answer.trim().to_lowercase().as_str(), "n" | "no"))
+}
+
+/// 处理 config validate 命令
+fn handle_config_validate(file: Option<PathBuf>) -> Result<()> {
+    let (layers, overrides) = match file {
+        Some(path) => (vec![read_config_layer(ConfigSource::Global(path.clone()), &path)?], Vec::new()),
+        None => {
+            let project_dir = std::env::current_dir().ok();
+            (read_config_layers(project_dir.as_deref())?, active_overrides())
+        }
+    };
+
+    for layer in &layers {
+        println!("{}", t!("config.validate.checking", source = layer.source));
+    }
+    for item in &overrides {
+        println!("{}", t!("config.validate.checking", source = item.source()));
+    }
+
+    let report = validate_layers(&layers, &overrides)?;
+    let errors = report.errors().count();
+    let warnings = report.warnings().count();
+
+    if !report.issues.is_empty() {
+        println!();
+    }
+    for issue in &report.issues {
+        let label = match issue.severity {
+            Severity::Error => t!("config.validate.error"),
+            Severity::Warning => t!("config.validate.warning"),
+        };
+        println!("{}: {}", label, issue);
+    }
+
+    println!();
+    if errors > 0 {
+        anyhow::bail!(t!("config.validate.invalid", errors = errors, warnings = warnings));
+    }
+    if warnings > 0 {
+        println!("{}", t!("config.validate.valid_with_warnings", warnings = warnings));
+    } else {
+        println!("{}", t!("config.validate.valid"));
+    }
+    Ok(())
+}
+
+/// 处理 config migrate 命令
+fn handle_config_migrate() -> Result<()> {
+    let path = ensure_config_file()?;
+    let Some(outcome) = migrate_config_file(&path)? else {
+        println!("{}", t!("config.migrate.up_to_date", version = CONFIG_VERSION, path = path.display()));
+        return Ok(());
+    };
+
+    println!("{}", t!("config.migrate.migrated", from = outcome.from, to = CONFIG_VERSION, path = path.display()));
+    for step in &outcome.steps {
+        println!("  {} → {}: {}", step.from, step.to, t!(step.description));
+    }
+    println!("{}", t!("config.migrate.backup", path = outcome.backup.display()));
+    Ok(())
+}
+
+/// 处理 config show 命令
+fn handle_config_show(format: ConfigFormat) -> Result<()> {
+    let config = load_current_layered_config()?.config;
+    match format {
+        ConfigFormat::Yaml => print!("{}", serde_yaml::to_string(&config)?),
+        ConfigFormat::Json => println!("{}", serde_json::to_string_pretty(&config)?),
+    }
     Ok(())
 }
 
 /// 处理 init 命令
 fn handle_init() -> Result<()> {
-    println!("启动配置向导...\n");
+    println!("{}\n", t!("init.starting"));
 
     let wizard = ConfigWizard::new();
     match wizard.run() {
         Ok(_) => Ok(()),
         Err(e) => {
-            eprintln!("配置向导出错: {}", e);
+            eprintln!("{}", t!("init.failed", error = e));
             Err(e)
         }
     }
@@ -318,7 +1940,7 @@ fn handle_init() -> Result<()> {
 
 /// 处理 setup 命令
 fn handle_setup() -> Result<()> {
-    println!("正在设置 CCN 集成...");
+    println!("{}", t!("setup.starting"));
 
     let manager = IntegrationManager::new();
 
@@ -326,15 +1948,15 @@ fn handle_setup() -> Result<()> {
     let config_path = manager.detect_config_path();
     let config_path = match config_path {
         Some(path) => {
-            println!("✓ 找到 Claude Code 配置文件: {:?}", path);
+            println!("✓ {}", t!("setup.found_config", path = format!("{:?}", path)));
             path
         }
         None => {
-            println!("❌ 未找到 Claude Code 配置文件\n");
-            println!("请确认：");
-            println!("  1. Claude Code 已安装（CLI 或 VS Code 插件）");
-            println!("  2. 配置文件存在于: ~/.claude/settings.json");
-            println!("\n或者设置自定义路径：");
+            println!("❌ {}\n", t!("setup.config_not_found"));
+            println!("{}", t!("setup.check.heading"));
+            println!("  1. {}", t!("setup.check.installed"));
+            println!("  2. {}", t!("setup.check.settings"));
+            println!("\n{}", t!("setup.check.custom_path"));
             println!("  Windows: set CLAUDE_CONFIG_DIR=D:\\custom\\path");
             println!("  Linux/macOS: export CLAUDE_CONFIG_DIR=/custom/path");
             return Ok(());
@@ -343,56 +1965,59 @@ fn handle_setup() -> Result<()> {
 
     // 检查是否已集成
     if manager.is_integrated(&config_path).unwrap_or(false) {
-        println!("⚠ CCN 已经集成到 Claude Code");
-        println!("如需重新集成，请先运行 `ccn uninstall`");
+        println!("⚠ {}", t!("setup.already_integrated"));
+        println!("{}", t!("setup.reinstall_hint"));
         return Ok(());
     }
 
     // 添加到 PATH（仅 Windows）
     #[cfg(windows)]
     {
-        println!("正在配置 PATH 环境变量...");
+        println!("{}", t!("setup.path.configuring"));
         let ccn_dir = std::env::current_exe()
-            .context("无法获取可执行文件路径")?
+            .context(t!("error.current_exe"))?
             .parent()
-            .ok_or_else(|| anyhow::anyhow!("无法获取可执行文件目录"))?
+            .ok_or_else(|| anyhow::anyhow!(t!("error.current_exe_dir")))?
             .to_path_buf();
 
         match PathManager::add_to_path(&ccn_dir) {
             Ok(true) => {
-                println!("✓ 已将 CCN 添加到系统 PATH");
+                println!("✓ {}", t!("setup.path.added"));
             }
             Ok(false) => {
-                println!("ℹ PATH 已包含 CCN 目录，跳过");
+                println!("ℹ {}", t!("setup.path.present"));
             }
             Err(e) => {
-                println!("⚠ 无法修改 PATH: {}", e);
-                println!("请手动添加到 PATH: {:?}", ccn_dir);
+                println!("⚠ {}", t!("setup.path.failed", error = e));
+                println!("{}", t!("setup.path.manual", dir = format!("{:?}", ccn_dir)));
             }
         }
     }
 
     // 备份配置文件
-    println!("正在备份配置文件...");
+    println!("{}", t!("setup.backing_up"));
     let backup_path = manager.backup_config(&config_path)?;
-    println!("✓ 备份已创建: {:?}", backup_path);
+    println!("✓ {}", t!("setup.backup_created", path = format!("{:?}", backup_path)));
 
     // 注入 hooks
-    println!("正在注入 hooks...");
-    manager.inject_hooks(&config_path)?;
-    println!("✓ Hooks 已成功注入");
+    println!("{}", t!("setup.injecting"));
+    let config = load_config()
+        .context(t!("error.load_config"))?;
+    manager.inject_hooks(&config_path, &config.hooks.events)?;
+    let event_names: Vec<&str> = config.hooks.events.iter().map(|e| e.event_name()).collect();
+    println!("✓ {}", t!("setup.injected", events = event_names.join(", ")));
 
     // 发送测试通知
-    println!("正在发送测试通知...");
+    println!("{}", t!("setup.sending_test"));
     manager.send_test_notification()?;
 
-    println!("\n✅ CCN 集成成功！");
+    println!("\n✅ {}", t!("setup.done"));
 
     #[cfg(windows)]
     {
-        println!("\n⚠ 重要提示：");
-        println!("请重启您的终端或 VS Code，以使 PATH 环境变量生效。");
-        println!("重启后，hooks 将自动生效，您会收到任务完成通知。");
+        println!("\n⚠ {}", t!("setup.restart.heading"));
+        println!("{}", t!("setup.restart.path"));
+        println!("{}", t!("setup.restart.hooks"));
     }
 
     Ok(())
@@ -400,7 +2025,7 @@ fn handle_setup() -> Result<()> {
 
 /// 处理 uninstall 命令
 fn handle_uninstall() -> Result<()> {
-    println!("正在卸载 CCN 集成...");
+    println!("{}", t!("uninstall.starting"));
 
     let manager = IntegrationManager::new();
 
@@ -408,56 +2033,56 @@ fn handle_uninstall() -> Result<()> {
     let config_path = manager.detect_config_path();
     let config_path = match config_path {
         Some(path) => {
-            println!("✓ 找到 Claude Code 配置文件: {:?}", path);
+            println!("✓ {}", t!("setup.found_config", path = format!("{:?}", path)));
             path
         }
         None => {
-            println!("❌ 未找到 Claude Code 配置文件");
-            println!("CCN 可能未安装");
+            println!("❌ {}", t!("setup.config_not_found"));
+            println!("{}", t!("uninstall.not_installed"));
             return Ok(());
         }
     };
 
     // 检查是否已集成
     if !manager.is_integrated(&config_path).unwrap_or(false) {
-        println!("⚠ CCN 未集成到 Claude Code");
+        println!("⚠ {}", t!("uninstall.not_integrated"));
         return Ok(());
     }
 
     // 移除 hooks
-    println!("正在移除 hooks...");
+    println!("{}", t!("uninstall.removing"));
     manager.remove_hooks(&config_path)?;
-    println!("✓ Hooks 已移除");
+    println!("✓ {}", t!("uninstall.removed"));
 
     // 从 PATH 移除（仅 Windows）
     #[cfg(windows)]
     {
-        println!("正在清理 PATH 环境变量...");
+        println!("{}", t!("uninstall.path.cleaning"));
         let ccn_dir = std::env::current_exe()
-            .context("无法获取可执行文件路径")?
+            .context(t!("error.current_exe"))?
             .parent()
-            .ok_or_else(|| anyhow::anyhow!("无法获取可执行文件目录"))?
+            .ok_or_else(|| anyhow::anyhow!(t!("error.current_exe_dir")))?
             .to_path_buf();
 
         match PathManager::remove_from_path(&ccn_dir) {
             Ok(true) => {
-                println!("✓ 已从系统 PATH 移除 CCN");
+                println!("✓ {}", t!("uninstall.path.removed"));
             }
             Ok(false) => {
-                println!("ℹ PATH 中不包含 CCN 目录");
+                println!("ℹ {}", t!("uninstall.path.absent"));
             }
             Err(e) => {
-                println!("⚠ 无法清理 PATH: {}", e);
+                println!("⚠ {}", t!("uninstall.path.failed", error = e));
             }
         }
     }
 
-    println!("\n✅ CCN 集成已移除");
-    println!("配置文件的备份仍保留在原位置");
+    println!("\n✅ {}", t!("uninstall.done"));
+    println!("{}", t!("uninstall.backup_kept"));
 
     #[cfg(windows)]
     {
-        println!("\n⚠ 请重启终端以使 PATH 更新生效");
+        println!("\n⚠ {}", t!("uninstall.restart"));
     }
 
     Ok(())
@@ -465,7 +2090,7 @@ fn handle_uninstall() -> Result<()> {
 
 /// 处理 verify 命令
 fn handle_verify() -> Result<()> {
-    println!("正在验证 CCN 集成...\n");
+    println!("{}\n", t!("verify.starting"));
 
     let manager = IntegrationManager::new();
 
@@ -473,33 +2098,31 @@ fn handle_verify() -> Result<()> {
     let result = manager.verify_integration()?;
 
     // 显示结果
-    println!("集成验证结果：");
-    println!("  ccn 命令在 PATH 中: {}", if result.ccn_in_path { "✓" } else { "✗" });
-    println!("  测试通知发送: {}", if result.test_notification_sent { "✓" } else { "✗" });
+    println!("{}", t!("verify.heading"));
+    println!("  {}: {}", t!("verify.in_path"), if result.ccn_in_path { "✓" } else { "✗" });
+    println!("  {}: {}", t!("verify.test_sent"), if result.test_notification_sent { "✓" } else { "✗" });
 
     if let Some(error) = result.error {
-        println!("\n错误: {}", error);
-        return Err(anyhow::anyhow!("集成验证失败"));
+        println!("\n{}", t!("verify.error", error = error));
+        return Err(anyhow::anyhow!(t!("verify.failed")));
     }
 
-    println!("\n✅ 集成验证通过！CCN 已正确配置。");
+    println!("\n✅ {}", t!("verify.done"));
     Ok(())
 }
 
 /// 构建通知标题
 fn build_title(status: NotificationStatus, _cmd: &str) -> String {
-    let status_text = match status {
-        NotificationStatus::Success => "完成",
-        NotificationStatus::Error => "失败",
-        NotificationStatus::Pending => "进行中",
-    };
-
-    format!("任务{}", status_text)
+    match status {
+        NotificationStatus::Success => t!("notify.title.success"),
+        NotificationStatus::Error => t!("notify.title.error"),
+        NotificationStatus::Pending => t!("notify.title.pending"),
+    }
 }
 
 /// 构建通知消息
 fn build_message(duration: u64, cmd: &str) -> String {
-    format!("{} (耗时: {}秒)", cmd, duration)
+    t!("notify.message", cmd = cmd, duration = format_secs(duration))
 }
 
 #[cfg(test)]
@@ -507,6 +2130,31 @@ mod tests {
     use super::*;
     use crate::notification::NotificationStatus;
 
+    /// 收集命令树中每个命令和参数对应的帮助文本键
+    fn help_keys(command: &clap::Command, key: &str, keys: &mut Vec<String>) {
+        keys.push(format!("{}.about", key));
+        for arg in command.get_arguments() {
+            let id = arg.get_id().as_str();
+            if id != "help" && id != "version" {
+                keys.push(format!("{}.args.{}", key, id));
+            }
+        }
+        for sub in command.get_subcommands() {
+            help_keys(sub, &format!("{}.{}", key, sub.get_name()), keys);
+        }
+    }
+
+    #[test]
+    fn test_every_command_has_help_text() {
+        let mut keys = Vec::new();
+        help_keys(&Cli::command(), "help", &mut keys);
+        for locale in ["zh-CN", "en-US"] {
+            let catalog = i18n::Catalog::builtin(locale).unwrap();
+            let missing: Vec<&String> = keys.iter().filter(|key| catalog.get(key).is_none()).collect();
+            assert!(missing.is_empty(), "{} 缺少帮助文本: {:?}", locale, missing);
+        }
+    }
+
     #[test]
     fn test_build_title_success() {
         let title = build_title(NotificationStatus::Success, "npm test");
@@ -540,6 +2188,69 @@ mod tests {
     #[test]
     fn test_build_message_long_duration() {
         let message = build_message(3600, "long running task");
-        assert_eq!(message, "long running task (耗时: 3600秒)");
+        assert_eq!(message, "long running task (耗时: 1小时)");
+    }
+
+    #[test]
+    fn test_parse_status() {
+        let parse = |status: &str| StatusArg::from_str(status, true).map(NotificationStatus::from);
+        assert_eq!(parse("success"), Ok(NotificationStatus::Success));
+        assert_eq!(parse("FAILED"), Ok(NotificationStatus::Error));
+        assert_eq!(parse("failure"), Ok(NotificationStatus::Error));
+        assert_eq!(parse("running"), Ok(NotificationStatus::Pending));
+        assert!(parse("unknown").is_err());
+
+        // 所有接受 --status 的命令都拒绝未知状态，而不是回退为 success
+        for args in [
+            &["ccn", "notify", "--status", "unknown", "--cmd", "x"][..],
+            &["ccn", "history", "--status", "unknown"],
+            &["ccn", "policy", "explain", "--cmd", "x", "--status", "unknown"],
+            &["ccn", "template", "render", "--status", "unknown"],
+            &["ccn", "sound", "test", "unknown"],
+        ] {
+            let error = Cli::try_parse_from(args).unwrap_err();
+            assert_eq!(error.kind(), clap::error::ErrorKind::InvalidValue, "{:?}", args);
+        }
+        assert!(Cli::try_parse_from(["ccn", "history", "--status", "Failed"]).is_ok());
+    }
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(parse_date("2026-01-04").unwrap(), NaiveDate::from_ymd_opt(2026, 1, 4).unwrap());
+        assert!(parse_date("2026/01/04").is_err());
+    }
+
+    #[test]
+    fn test_render_content_falls_back() {
+        let content = NotificationContent {
+            cmd: "cargo build",
+            duration: 65,
+            project: Some("demo"),
+            project_path: None,
+            event: None,
+            session_id: None,
+            exit_code: Some(2),
+            title: "任务失败",
+            message: "cargo build (耗时: 65秒)",
+        };
+        let mut default = TemplateConfig::default();
+        let mut build = TemplateConfig::default();
+
+        // 都没有设置时使用内置文字
+        let (title, message) = render_content(NotificationStatus::Error, &content, &build, &default);
+        assert_eq!(title, "任务失败");
+        assert_eq!(message, "cargo build (耗时: 65秒)");
+
+        // 模板只设置标题时，内容回退到默认模板
+        default.body = Some("{cmd} 用时 {duration_human}".to_string());
+        build.title = Some("{project}: {status_text}{if exit_code} ({exit_code}){end}".to_string());
+        let (title, message) = render_content(NotificationStatus::Error, &content, &build, &default);
+        assert_eq!(title, "demo: 失败 (2)");
+        assert_eq!(message, "cargo build 用时 1分5秒");
+
+        // 语法错误时回退到内置文字
+        build.title = Some("{cmd".to_string());
+        let (title, _) = render_content(NotificationStatus::Error, &content, &build, &default);
+        assert_eq!(title, "任务失败");
     }
 }
@@ -4,19 +4,51 @@
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::duration;
+use crate::config_validate::{validate_layers, ConfigLayer};
+use crate::notification::NotificationStatus;
+use crate::sound::DEFAULT_SOUND_THEME;
 
 /// 配置文件结构
+///
+/// 省略的配置项使用默认值
 #[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
 pub struct Config {
     pub version: String,
+    /// 界面语言，如 zh-CN、en-US；省略时按 LC_ALL/LC_MESSAGES/LANG 确定
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
     pub sound_enabled: bool,
+    /// Linux 系统提示音使用的 freedesktop 声音主题（如 `/usr/share/sounds/<主题>`），找不到时使用内置提示音
+    pub sound_theme: String,
+    /// 音效音量（0-100）
+    pub sound_volume: u8,
     pub focus_assistant_mode: FocusAssistantMode,
     pub threshold: ThresholdConfig,
     pub templates: TemplatesConfig,
     pub aggregation: AggregationConfig,
     pub logging: LoggingConfig,
+    #[serde(default)]
+    pub hooks: HooksConfig,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<WebhookConfig>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notifiers: Vec<NotifierConfig>,
+    #[serde(default)]
+    pub schedule: ScheduleConfig,
+    /// 通知规则，按顺序匹配，首个命中的规则生效；为空时由 threshold 生成等价规则
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<RuleConfig>,
+    /// 没有规则命中时的动作
+    #[serde(default, skip_serializing_if = "RuleAction::is_notify")]
+    pub default_action: RuleAction,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
@@ -28,65 +60,587 @@ pub enum FocusAssistantMode {
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
 pub struct ThresholdConfig {
+    /// 最小耗时（秒），也可以写 `90s`、`5m` 等
+    #[serde(with = "duration::secs")]
     pub min_duration: u64,
-    pub whitelist: Vec<String>,
+    /// 按顺序匹配，首个命中的规则生效
+    pub whitelist: Vec<MatchRule>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+impl Default for ThresholdConfig {
+    fn default() -> Self {
+        Self {
+            min_duration: 10,
+            whitelist: vec![],
+        }
+    }
+}
+
+/// 命令匹配规则
+///
+/// 配置中可以直接写字符串（子串匹配），也可以写成 `{ pattern, mode, anchor }`
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(from = "MatchRuleRepr", into = "MatchRuleRepr")]
+pub struct MatchRule {
+    pub pattern: String,
+    pub mode: MatchMode,
+    /// 锚定方式，仅对 substring 和 regex 生效（glob 和 exact 总是匹配整条命令）
+    pub anchor: Anchor,
+}
+
+impl MatchRule {
+    /// 子串匹配规则（旧版配置的行为）
+    pub fn substring(pattern: &str) -> Self {
+        Self {
+            pattern: pattern.to_string(),
+            mode: MatchMode::Substring,
+            anchor: Anchor::None,
+        }
+    }
+}
+
+impl std::fmt::Display for MatchRule {
+    /// 纯子串规则显示为 `"deploy"`，其他显示为 `regex:"^npm" (anchor=start)` 形式
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mode = match self.mode {
+            MatchMode::Substring => "substring",
+            MatchMode::Glob => "glob",
+            MatchMode::Regex => "regex",
+            MatchMode::Exact => "exact",
+        };
+
+        if self.mode == MatchMode::Substring && self.anchor == Anchor::None {
+            return write!(f, "\"{}\"", self.pattern);
+        }
+
+        write!(f, "{}:\"{}\"", mode, self.pattern)?;
+        match self.anchor {
+            Anchor::None => Ok(()),
+            Anchor::Start => write!(f, " (anchor=start)"),
+            Anchor::End => write!(f, " (anchor=end)"),
+            Anchor::Both => write!(f, " (anchor=both)"),
+        }
+    }
+}
+
+impl From<&str> for MatchRule {
+    fn from(pattern: &str) -> Self {
+        Self::substring(pattern)
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchMode {
+    #[default]
+    Substring,
+    /// `*` 匹配任意字符序列，`?` 匹配单个字符
+    Glob,
+    Regex,
+    Exact,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Anchor {
+    #[default]
+    None,
+    /// 从命令开头匹配
+    Start,
+    /// 匹配到命令结尾
+    End,
+    /// 匹配整条命令
+    Both,
+}
+
+/// `MatchRule` 的序列化形式：纯子串规则写成字符串
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum MatchRuleRepr {
+    Plain(String),
+    Full {
+        pattern: String,
+        #[serde(default)]
+        mode: MatchMode,
+        #[serde(default)]
+        anchor: Anchor,
+    },
+}
+
+impl From<MatchRuleRepr> for MatchRule {
+    fn from(repr: MatchRuleRepr) -> Self {
+        match repr {
+            MatchRuleRepr::Plain(pattern) => Self::substring(&pattern),
+            MatchRuleRepr::Full { pattern, mode, anchor } => Self { pattern, mode, anchor },
+        }
+    }
+}
+
+impl From<MatchRule> for MatchRuleRepr {
+    fn from(rule: MatchRule) -> Self {
+        if rule.mode == MatchMode::Substring && rule.anchor == Anchor::None {
+            MatchRuleRepr::Plain(rule.pattern)
+        } else {
+            MatchRuleRepr::Full {
+                pattern: rule.pattern,
+                mode: rule.mode,
+                anchor: rule.anchor,
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[serde(default)]
 pub struct TemplatesConfig {
     pub default: TemplateConfig,
     #[serde(flatten)]
     pub custom: std::collections::HashMap<String, TemplateConfig>,
 }
 
+impl TemplatesConfig {
+    /// 按名称取模板，`default` 或不存在的名称返回默认模板
+    pub fn get(&self, name: &str) -> &TemplateConfig {
+        self.custom.get(name).filter(|_| name != "default").unwrap_or(&self.default)
+    }
+
+    /// 模板在某个状态下的音效
+    ///
+    /// 依次取模板的 `sounds.<状态>`、模板的 `sound`（为 `default` 时跳过）、默认模板的 `sounds.<状态>` 和 `sound`
+    pub fn sound(&self, name: &str, status: NotificationStatus) -> &str {
+        let template = self.get(name);
+        let default = &self.default;
+        template
+            .sounds
+            .get(status)
+            .or_else(|| (template.sound != "default").then_some(template.sound.as_str()))
+            .or_else(|| default.sounds.get(status))
+            .unwrap_or(&default.sound)
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
 pub struct TemplateConfig {
     pub icon: String,
+    /// 音效：`default` 为系统提示音，`none` 为不播放，其他值为音频文件路径
     pub sound: String,
+    /// 按状态覆盖 `sound`
+    #[serde(default, skip_serializing_if = "StatusSounds::is_empty")]
+    pub sounds: StatusSounds,
+    /// 通知显示时长（毫秒），也可以写 `5s` 等
+    #[serde(with = "duration::millis")]
     pub duration: u64,
+    /// 通知标题的格式字符串（见 `template` 模块），省略时使用默认模板的标题
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// 通知内容的格式字符串，省略时使用默认模板的内容
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    /// 匹配规则，省略时按模板名做子串匹配
+    #[serde(default, rename = "match", skip_serializing_if = "Option::is_none")]
+    pub matcher: Option<MatchRule>,
+    /// 优先级，数值大的先匹配；相同时按模板名排序
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub priority: i32,
+}
+
+fn is_zero(value: &i32) -> bool {
+    *value == 0
+}
+
+impl Default for TemplateConfig {
+    fn default() -> Self {
+        Self {
+            icon: "auto".to_string(),
+            sound: "default".to_string(),
+            sounds: StatusSounds::default(),
+            duration: 5000,
+            title: None,
+            body: None,
+            matcher: None,
+            priority: 0,
+        }
+    }
+}
+
+/// 按状态设置的音效，取值同模板的 `sound`
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
+pub struct StatusSounds {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub success: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending: Option<String>,
+}
+
+impl StatusSounds {
+    pub fn get(&self, status: NotificationStatus) -> Option<&str> {
+        match status {
+            NotificationStatus::Success => self.success.as_deref(),
+            NotificationStatus::Error => self.error.as_deref(),
+            NotificationStatus::Pending => self.pending.as_deref(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.success.is_none() && self.error.is_none() && self.pending.is_none()
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
 pub struct AggregationConfig {
     pub enabled: bool,
+    /// 聚合窗口（毫秒），也可以写 `5s` 等
+    #[serde(with = "duration::millis")]
     pub window: u64,
     pub max_toasts: usize,
 }
 
+impl Default for AggregationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window: 5000,
+            max_toasts: 3,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
 pub struct LoggingConfig {
     pub level: String,
     pub file: Option<String>,
 }
 
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+            file: None,
+        }
+    }
+}
+
+/// Webhook 通知配置（HTTP POST JSON）
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct WebhookConfig {
+    pub url: String,
+    /// 请求超时（毫秒）
+    #[serde(default = "default_webhook_timeout", with = "duration::millis")]
+    pub timeout: u64,
+    /// 失败后的重试次数
+    #[serde(default)]
+    pub retries: u32,
+    /// 首次重试前的等待时间（毫秒），之后每次翻倍
+    #[serde(default = "default_webhook_retry_delay", with = "duration::millis")]
+    pub retry_delay: u64,
+    /// 自定义请求头
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// 自定义请求体，字符串中的 {status} {title} {message} {cmd} {duration} {hostname} 会被替换
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<serde_json::Value>,
+}
+
+fn default_webhook_timeout() -> u64 {
+    5000
+}
+
+fn default_webhook_retry_delay() -> u64 {
+    500
+}
+
+/// 通知渠道配置
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct NotifierConfig {
+    /// 渠道名称，供规则的 `sinks` 引用；省略时使用类型名（desktop、webhook、log）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(flatten)]
+    pub kind: NotifierKind,
+    /// 只发送这些状态的通知，为空表示全部
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub statuses: Vec<NotificationStatus>,
+    /// 只发送耗时不低于该值（秒）的通知，耗时未知（0）时不过滤
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "duration::option_secs")]
+    pub min_duration: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum NotifierKind {
+    /// 平台原生桌面通知
+    Desktop,
+    /// HTTP webhook
+    Webhook(WebhookConfig),
+    /// 追加写入日志文件
+    Log { path: String },
+}
+
+/// `ccn setup` 安装到 Claude Code 的 hook 事件
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct HooksConfig {
+    pub events: Vec<HookEventKind>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum HookEventKind {
+    Notification,
+    PermissionRequest,
+    Stop,
+    SubagentStop,
+}
+
+impl HookEventKind {
+    /// Claude Code settings.json 中的事件名称
+    pub fn event_name(&self) -> &'static str {
+        match self {
+            HookEventKind::Notification => "Notification",
+            HookEventKind::PermissionRequest => "PermissionRequest",
+            HookEventKind::Stop => "Stop",
+            HookEventKind::SubagentStop => "SubagentStop",
+        }
+    }
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            events: vec![
+                HookEventKind::Notification,
+                HookEventKind::PermissionRequest,
+                HookEventKind::Stop,
+            ],
+        }
+    }
+}
+
+/// 通知规则
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RuleConfig {
+    /// 规则名称，用于日志和 `ccn policy explain`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// 匹配条件，全部满足才算命中；省略表示总是命中
+    #[serde(default)]
+    pub when: RuleConditions,
+    pub action: RuleAction,
+    /// 无论声音设置和免打扰时段如何都播放声音
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub force_sound: bool,
+    /// 只发送到这些通知渠道（`notifiers` 中的 name），为空表示全部
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sinks: Vec<String>,
+}
+
+/// 规则条件
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct RuleConditions {
+    /// 状态属于其中之一
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub status: Vec<NotificationStatus>,
+    /// 耗时范围（秒）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<DurationRange>,
+    /// 命令匹配
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cmd: Option<MatchRule>,
+    /// 项目路径（工作目录）匹配
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<MatchRule>,
+    /// hook 事件名称属于其中之一（如 Stop、Notification），非 hook 触发的通知不匹配
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub event: Vec<String>,
+    /// 时间段
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time: Option<TimeRange>,
+}
+
+/// 耗时范围（秒，两端均包含）
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DurationRange {
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "duration::option_secs")]
+    pub min: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "duration::option_secs")]
+    pub max: Option<u64>,
+}
+
+impl DurationRange {
+    pub fn contains(&self, duration: u64) -> bool {
+        self.min.is_none_or(|min| duration >= min) && self.max.is_none_or(|max| duration <= max)
+    }
+}
+
+/// 规则动作
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleAction {
+    /// 发送通知（是否聚合由 aggregation 配置决定）
+    #[default]
+    Notify,
+    /// 丢弃
+    Drop,
+    /// 总是经过聚合器
+    Aggregate,
+}
+
+impl RuleAction {
+    fn is_notify(&self) -> bool {
+        *self == RuleAction::Notify
+    }
+}
+
+impl Config {
+    /// 实际生效的规则
+    ///
+    /// 未配置 `rules` 时，将 threshold/whitelist 转换为等价规则：
+    /// 错误总是通知 → 耗时未知时通知 → 白名单命中时通知 → 达到阈值时通知 → 其余丢弃
+    pub fn effective_rules(&self) -> Vec<RuleConfig> {
+        if !self.rules.is_empty() {
+            return self.rules.clone();
+        }
+
+        let rule = |name: String, when: RuleConditions, action: RuleAction| RuleConfig {
+            name: Some(name),
+            when,
+            action,
+            force_sound: false,
+            sinks: vec![],
+        };
+
+        let mut rules = vec![
+            rule(
+                "error".to_string(),
+                RuleConditions {
+                    status: vec![NotificationStatus::Error],
+                    ..Default::default()
+                },
+                RuleAction::Notify,
+            ),
+            rule(
+                "unknown-duration".to_string(),
+                RuleConditions {
+                    duration: Some(DurationRange { min: None, max: Some(0) }),
+                    ..Default::default()
+                },
+                RuleAction::Notify,
+            ),
+        ];
+
+        for (index, matcher) in self.threshold.whitelist.iter().enumerate() {
+            rules.push(rule(
+                format!("whitelist #{}", index + 1),
+                RuleConditions {
+                    cmd: Some(matcher.clone()),
+                    ..Default::default()
+                },
+                RuleAction::Notify,
+            ));
+        }
+
+        rules.push(rule(
+            "threshold".to_string(),
+            RuleConditions {
+                duration: Some(DurationRange {
+                    min: Some(self.threshold.min_duration),
+                    max: None,
+                }),
+                ..Default::default()
+            },
+            RuleAction::Notify,
+        ));
+        rules.push(rule("below-threshold".to_string(), RuleConditions::default(), RuleAction::Drop));
+
+        rules
+    }
+}
+
+/// 免打扰时间表
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ScheduleConfig {
+    /// 时区：`local` 表示系统时区，否则为 IANA 名称（如 `Asia/Shanghai`）
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+    /// 免打扰时段
+    #[serde(default)]
+    pub quiet_hours: Vec<QuietHoursConfig>,
+}
+
+fn default_timezone() -> String {
+    "local".to_string()
+}
+
+impl Default for ScheduleConfig {
+    fn default() -> Self {
+        Self {
+            timezone: default_timezone(),
+            quiet_hours: vec![],
+        }
+    }
+}
+
+/// 单个免打扰时段
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct QuietHoursConfig {
+    #[serde(flatten)]
+    pub range: TimeRange,
+    #[serde(default)]
+    pub action: QuietAction,
+    /// 不受该时段影响的状态（如 error）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub except: Vec<NotificationStatus>,
+}
+
+/// 一周内的时间段（按 `schedule.timezone` 计算）
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct TimeRange {
+    /// 生效的星期（按时段开始的那天计算），为空表示每天
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub days: Vec<chrono::Weekday>,
+    /// 开始时间（HH:MM），晚于结束时间表示跨午夜
+    pub start: chrono::NaiveTime,
+    /// 结束时间（HH:MM，不含），与开始时间相同表示全天
+    pub end: chrono::NaiveTime,
+}
+
+/// 免打扰时段内的处理方式
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum QuietAction {
+    /// 丢弃通知
+    #[default]
+    Suppress,
+    /// 发送通知但不播放声音
+    Silence,
+    /// 暂存，时段结束后合并为一条摘要发送
+    Defer,
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
-            version: "1.0".to_string(),
+            version: CONFIG_VERSION.to_string(),
+            language: None,
             sound_enabled: true,
+            sound_theme: DEFAULT_SOUND_THEME.to_string(),
+            sound_volume: 100,
             focus_assistant_mode: FocusAssistantMode::Respect,
-            threshold: ThresholdConfig {
-                min_duration: 10,
-                whitelist: vec![],
-            },
-            templates: TemplatesConfig {
-                default: TemplateConfig {
-                    icon: "auto".to_string(),
-                    sound: "default".to_string(),
-                    duration: 5000,
-                },
-                custom: std::collections::HashMap::new(),
-            },
-            aggregation: AggregationConfig {
-                enabled: true,
-                window: 5000,
-                max_toasts: 3,
-            },
-            logging: LoggingConfig {
-                level: "info".to_string(),
-                file: None,
-            },
+            threshold: ThresholdConfig::default(),
+            templates: TemplatesConfig::default(),
+            aggregation: AggregationConfig::default(),
+            logging: LoggingConfig::default(),
+            hooks: HooksConfig::default(),
+            webhook: None,
+            notifiers: vec![],
+            schedule: ScheduleConfig::default(),
+            rules: vec![],
+            default_action: RuleAction::Notify,
         }
     }
 }
@@ -116,24 +670,479 @@ pub fn get_config_path() -> PathBuf {
     }
 }
 
-/// 加载配置文件
-pub fn load_config() -> Result<Config> {
+/// 项目配置文件名
+pub const PROJECT_CONFIG_FILE: &str = ".ccn.yaml";
+
+/// 配置值的来源
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSource {
+    /// 未在任何文件中设置，使用默认值
+    Default,
+    /// 全局配置文件
+    Global(PathBuf),
+    /// 项目目录中的 `.ccn.yaml`
+    Project(PathBuf),
+    /// `CCN_*` 环境变量
+    Env(String),
+    /// 命令行 `--set`
+    Cli,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigSource::Default => write!(f, "{}", t!("config.source.default")),
+            ConfigSource::Global(path) | ConfigSource::Project(path) => write!(f, "{}", path.display()),
+            ConfigSource::Env(name) => write!(f, "{}", t!("config.source.env", name = name)),
+            ConfigSource::Cli => write!(f, "{}", t!("config.source.cli")),
+        }
+    }
+}
+
+/// 按层合并后的配置
+#[derive(Debug)]
+pub struct LayeredConfig {
+    pub config: Config,
+    /// 参与合并的配置文件及其内容，后面的覆盖前面的
+    pub layers: Vec<(ConfigSource, serde_yaml::Value)>,
+}
+
+impl LayeredConfig {
+    /// 以默认配置为基础依次深度合并各层配置：映射逐键合并，其他值（包括列表）整体替换
+    pub fn from_layers(layers: Vec<(ConfigSource, serde_yaml::Value)>) -> Result<Self> {
+        let mut merged = serde_yaml::to_value(Config::default())?;
+        for (source, value) in &layers {
+            merge_yaml(&mut merged, value.clone());
+
+            // 逐层校验，便于指出是哪个文件导致格式错误
+            serde_yaml::from_value::<Config>(merged.clone())
+                .with_context(|| t!("config.invalid_format", source = source))?;
+        }
+
+        let config = serde_yaml::from_value(merged)?;
+        Ok(Self { config, layers })
+    }
+
+    /// 依次应用临时覆盖（环境变量、`--set`），每个覆盖作为单独的一层
+    pub fn with_overrides(mut self, overrides: &[ConfigOverride]) -> Result<Self> {
+        let mut merged = serde_yaml::to_value(&self.config)?;
+
+        for item in overrides {
+            let layer = item.to_layer(&merged);
+            merge_yaml(&mut merged, layer.clone());
+            self.config = serde_yaml::from_value(merged.clone())
+                .with_context(|| t!("config.invalid_override", source = item.source(), key = item.key, value = item.value))?;
+            self.layers.push((item.source(), layer));
+        }
+
+        Ok(self)
+    }
+
+    /// 列出每个生效配置项（点分路径）的值和来源
+    pub fn origins(&self) -> Result<Vec<(String, serde_yaml::Value, ConfigSource)>> {
+        let effective = serde_yaml::to_value(&self.config)?;
+        let mut leaves = Vec::new();
+        flatten_yaml(&effective, String::new(), &mut leaves);
+
+        let layer_keys: Vec<(&ConfigSource, Vec<String>)> = self
+            .layers
+            .iter()
+            .map(|(source, value)| {
+                let mut keys = Vec::new();
+                flatten_yaml(value, String::new(), &mut keys);
+                (source, keys.into_iter().map(|(key, _)| key).collect())
+            })
+            .collect();
+
+        Ok(leaves
+            .into_iter()
+            .map(|(key, value)| {
+                // 最后一个设置了该项（或其上级）的文件生效
+                let source = layer_keys
+                    .iter()
+                    .rev()
+                    .find(|(_, keys)| keys.iter().any(|k| *k == key || key.starts_with(&format!("{}.", k))))
+                    .map(|(source, _)| (*source).clone())
+                    .unwrap_or(ConfigSource::Default);
+                (key, value, source)
+            })
+            .collect())
+    }
+}
+
+/// 环境变量覆盖的前缀
+const ENV_PREFIX: &str = "CCN_";
+
+/// 临时覆盖的配置项：`CCN_*` 环境变量或命令行 `--set key=value`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigOverride {
+    /// 点分路径，如 `threshold.min_duration`
+    pub key: String,
+    pub value: String,
+    /// 来自环境变量时为变量名，来自 `--set` 时为 None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env: Option<String>,
+}
+
+impl ConfigOverride {
+    /// 解析 `--set key=value`
+    pub fn parse_set(arg: &str) -> Result<Self> {
+        let (key, value) = arg
+            .split_once('=')
+            .with_context(|| t!("config.set_format", arg = arg))?;
+        let key = key.trim();
+        if key.is_empty() || key.split('.').any(str::is_empty) {
+            anyhow::bail!("{}", t!("config.set_key", arg = arg));
+        }
+
+        Ok(Self {
+            key: key.to_string(),
+            value: value.to_string(),
+            env: None,
+        })
+    }
+
+    /// 解析环境变量：去掉 `CCN_` 前缀，`__` 表示下一级，名称转为小写
+    ///
+    /// 例如 `CCN_THRESHOLD__MIN_DURATION=30` 对应 `threshold.min_duration`
+    pub fn from_env_var(name: &str, value: &str) -> Option<Self> {
+        let path = name.strip_prefix(ENV_PREFIX)?;
+        if path.is_empty() || path.split("__").any(str::is_empty) {
+            return None;
+        }
+
+        Some(Self {
+            key: path.split("__").map(str::to_lowercase).collect::<Vec<_>>().join("."),
+            value: value.to_string(),
+            env: Some(name.to_string()),
+        })
+    }
+
+    pub fn source(&self) -> ConfigSource {
+        match &self.env {
+            Some(name) => ConfigSource::Env(name.clone()),
+            None => ConfigSource::Cli,
+        }
+    }
+
+    /// 生成只包含该配置项的 YAML 层
+    ///
+    /// 当前值是字符串时原样使用（避免 `version=2.0` 被解析成数字），否则按 YAML 解析
+    pub(crate) fn to_layer(&self, current: &serde_yaml::Value) -> serde_yaml::Value {
+        let segments: Vec<&str> = self.key.split('.').collect();
+        let existing = segments
+            .iter()
+            .try_fold(current, |value, segment| value.get(*segment));
+
+        let mut value = match existing {
+            Some(serde_yaml::Value::String(_)) => serde_yaml::Value::String(self.value.clone()),
+            _ => serde_yaml::from_str(&self.value)
+                .ok()
+                .filter(|value: &serde_yaml::Value| !value.is_null())
+                .unwrap_or_else(|| serde_yaml::Value::String(self.value.clone())),
+        };
+
+        for segment in segments.iter().rev() {
+            let mut map = serde_yaml::Mapping::new();
+            map.insert(serde_yaml::Value::String(segment.to_string()), value);
+            value = serde_yaml::Value::Mapping(map);
+        }
+        value
+    }
+}
+
+/// 命令行 `--set` 指定的覆盖，由 CLI 在启动时设置
+static CLI_OVERRIDES: OnceLock<Vec<ConfigOverride>> = OnceLock::new();
+
+/// 设置命令行 `--set` 覆盖（只在启动时调用一次）
+pub fn set_cli_overrides(overrides: Vec<ConfigOverride>) {
+    let _ = CLI_OVERRIDES.set(overrides);
+}
+
+/// 当前生效的临时覆盖：先环境变量（按变量名排序），后命令行 `--set`
+pub fn active_overrides() -> Vec<ConfigOverride> {
+    let mut env: Vec<(String, String)> = std::env::vars()
+        .filter(|(name, _)| name.starts_with(ENV_PREFIX))
+        .collect();
+    env.sort();
+
+    env.iter()
+        .filter_map(|(name, value)| ConfigOverride::from_env_var(name, value))
+        .chain(CLI_OVERRIDES.get().into_iter().flatten().cloned())
+        .collect()
+}
+
+/// 将 `overlay` 深度合并到 `base`
+pub(crate) fn merge_yaml(base: &mut serde_yaml::Value, overlay: serde_yaml::Value) {
+    use serde_yaml::Value;
+
+    match (base, overlay) {
+        (Value::Mapping(base), Value::Mapping(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_yaml(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        // 空文件不覆盖任何值
+        (_, Value::Null) => {}
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// 将 YAML 展开为 `(点分路径, 值)` 列表，列表和标量视为叶子
+fn flatten_yaml(value: &serde_yaml::Value, prefix: String, out: &mut Vec<(String, serde_yaml::Value)>) {
+    match value {
+        serde_yaml::Value::Mapping(map) if !map.is_empty() => {
+            for (key, child) in map {
+                let key = match key {
+                    serde_yaml::Value::String(key) => key.clone(),
+                    other => serde_yaml::to_string(other).unwrap_or_default().trim().to_string(),
+                };
+                let path = if prefix.is_empty() { key } else { format!("{}.{}", prefix, key) };
+                flatten_yaml(child, path, out);
+            }
+        }
+        serde_yaml::Value::Null if prefix.is_empty() => {}
+        other => out.push((prefix, other.clone())),
+    }
+}
+
+/// 从 `start` 向上查找最近的项目配置文件
+pub fn find_project_config(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(PROJECT_CONFIG_FILE))
+        .find(|path| path.is_file())
+}
+
+/// 读取全局配置和 `project_dir` 所在项目树中的 `.ccn.yaml`（如有），按优先级从低到高排列
+pub fn read_config_layers(project_dir: Option<&Path>) -> Result<Vec<ConfigLayer>> {
+    let config_path = ensure_config_file()?;
+
+    let mut layers = vec![read_config_layer(ConfigSource::Global(config_path.clone()), &config_path)?];
+    if let Some(project_path) = project_dir.and_then(find_project_config) {
+        log::debug!("{}", t!("config.log.project", path = format!("{:?}", project_path)));
+        layers.push(read_config_layer(ConfigSource::Project(project_path.clone()), &project_path)?);
+    }
+    Ok(layers)
+}
+
+/// 读取配置文件，旧版本的文件在内存中升级到当前版本
+pub fn read_config_layer(source: ConfigSource, path: &Path) -> Result<ConfigLayer> {
+    let mut layer = ConfigLayer::read(source, path)?;
+    migrate_layer(&mut layer)?;
+    Ok(layer)
+}
+
+/// 将一层配置升级到当前版本（不修改文件）
+pub fn migrate_layer(layer: &mut ConfigLayer) -> Result<()> {
+    let from = config_version(&layer.value);
+    let steps = migrate_config(&mut layer.value).with_context(|| t!("config.migrate.invalid_version", source = layer.source))?;
+    if !steps.is_empty() {
+        log::info!(
+            "{}",
+            t!(
+                "config.migrate.in_memory",
+                source = layer.source,
+                from = from.unwrap_or_default(),
+                to = CONFIG_VERSION
+            )
+        );
+    }
+    Ok(())
+}
+
+/// 按优先级从低到高合并配置：默认值、全局配置、`project_dir` 所在项目树中的 `.ccn.yaml`（如有）、
+/// `overrides`（环境变量和 `--set`）
+///
+/// 合并前校验各层，有错误时列出全部错误，警告（如未知的配置项）写入日志
+pub fn load_layered_config(project_dir: Option<&Path>, overrides: &[ConfigOverride]) -> Result<LayeredConfig> {
+    let layers = read_config_layers(project_dir)?;
+
+    let report = validate_layers(&layers, overrides)?;
+    report.log_warnings();
+    report.into_result()?;
+
+    let layers = layers.into_iter().map(|layer| (layer.source, layer.value)).collect();
+    LayeredConfig::from_layers(layers)?.with_overrides(overrides)
+}
+
+/// 读取配置的界面语言（全局配置、项目配置和 `overrides` 中最后设置的 `language`）
+///
+/// 在解析命令行之前调用，因此不创建也不校验配置文件，无法读取的文件直接跳过
+pub fn configured_language(project_dir: Option<&Path>, overrides: &[ConfigOverride]) -> Option<String> {
+    let global = Some(get_config_path())
+        .filter(|path| path.is_file())
+        .map(|path| (ConfigSource::Global(path.clone()), path));
+    let project = project_dir
+        .and_then(find_project_config)
+        .map(|path| (ConfigSource::Project(path.clone()), path));
+    let from_files = [global, project]
+        .into_iter()
+        .flatten()
+        .filter_map(|(source, path)| ConfigLayer::read(source, &path).ok())
+        .filter_map(|layer| layer.value.get("language").and_then(|v| v.as_str()).map(str::to_string))
+        .next_back();
+
+    overrides
+        .iter()
+        .rev()
+        .find(|o| o.key == "language")
+        .map(|o| o.value.clone())
+        .or(from_files)
+}
+
+/// 返回全局配置文件路径，文件不存在时写入默认配置
+pub fn ensure_config_file() -> Result<PathBuf> {
     let config_path = get_config_path();
 
     if !config_path.exists() {
-        log::info!("配置文件不存在，创建默认配置");
-        let config = Config::default();
-        save_config(&config)?;
-        return Ok(config);
+        log::info!("{}", t!("config.log.create_default"));
+        save_config(&Config::default())?;
+    }
+
+    Ok(config_path)
+}
+
+/// 当前的配置文件结构版本
+pub const CONFIG_VERSION: &str = "1.1";
+
+/// 配置文件结构的升级步骤：把 `from` 版本的配置改写为 `to` 版本
+#[derive(Debug)]
+pub struct Migration {
+    pub from: &'static str,
+    pub to: &'static str,
+    /// 说明文字的消息目录键
+    pub description: &'static str,
+    apply: fn(&mut serde_yaml::Mapping),
+}
+
+/// 按版本顺序排列的升级步骤，修改配置结构时在末尾追加一步并更新 `CONFIG_VERSION`
+pub const MIGRATIONS: &[Migration] = &[Migration {
+    from: "1.0",
+    to: "1.1",
+    description: "config.migrate.webhook_to_notifiers",
+    apply: migrate_webhook_to_notifiers,
+}];
+
+/// 1.0 → 1.1：未配置 `notifiers` 时，`webhook` 等价于只有一个 webhook 渠道
+fn migrate_webhook_to_notifiers(config: &mut serde_yaml::Mapping) {
+    use serde_yaml::Value;
+
+    if config.get("notifiers").is_some_and(|notifiers| !notifiers.is_null()) {
+        return;
+    }
+    let Some(Value::Mapping(webhook)) = config.remove("webhook") else {
+        return;
+    };
+
+    let mut notifier = serde_yaml::Mapping::new();
+    notifier.insert(Value::String("type".to_string()), Value::String("webhook".to_string()));
+    notifier.extend(webhook);
+    config.insert(
+        Value::String("notifiers".to_string()),
+        Value::Sequence(vec![Value::Mapping(notifier)]),
+    );
+}
+
+/// 配置中的版本号，未填写时为 None
+pub fn config_version(value: &serde_yaml::Value) -> Option<String> {
+    match value.get("version")? {
+        serde_yaml::Value::String(version) => Some(version.clone()),
+        // 未加引号的 `version: 1.0`
+        other => Some(serde_yaml::to_string(other).ok()?.trim().to_string()),
+    }
+}
+
+fn parse_version(version: &str) -> Option<(u32, u32)> {
+    let (major, minor) = version.split_once('.').unwrap_or((version, "0"));
+    Some((major.trim().parse().ok()?, minor.trim().parse().ok()?))
+}
+
+/// 将配置升级到当前版本，返回执行的升级步骤
+///
+/// 未填写版本号的配置（如项目配置）视为当前版本；版本高于当前支持的版本时返回错误
+pub fn migrate_config(value: &mut serde_yaml::Value) -> Result<Vec<&'static Migration>> {
+    let Some(version) = config_version(value) else {
+        return Ok(Vec::new());
+    };
+    let parsed = parse_version(&version).with_context(|| t!("config.migrate.unknown_version", version = format!("{:?}", version)))?;
+    let current = parse_version(CONFIG_VERSION).expect("CONFIG_VERSION 格式正确");
+    if parsed > current {
+        anyhow::bail!(
+            "{}",
+            t!(
+                "config.migrate.too_new",
+                version = version,
+                supported = CONFIG_VERSION,
+                ccn = env!("CARGO_PKG_VERSION")
+            )
+        );
+    }
+    if version == CONFIG_VERSION {
+        return Ok(Vec::new());
+    }
+
+    let serde_yaml::Value::Mapping(config) = value else {
+        return Ok(Vec::new());
+    };
+    let steps: Vec<&'static Migration> = MIGRATIONS
+        .iter()
+        .filter(|step| parse_version(step.to).is_some_and(|to| to > parsed))
+        .collect();
+    for step in &steps {
+        (step.apply)(config);
+    }
+    config.insert(
+        serde_yaml::Value::String("version".to_string()),
+        serde_yaml::Value::String(CONFIG_VERSION.to_string()),
+    );
+    Ok(steps)
+}
+
+/// 配置文件升级结果
+pub struct MigrationOutcome {
+    pub from: String,
+    pub backup: PathBuf,
+    pub steps: Vec<&'static Migration>,
+}
+
+/// 将配置文件升级到当前版本并写回，原文件备份为 `<文件名>.v<旧版本>.bak`
+///
+/// 已是当前版本时返回 None。升级后的文件由程序重新生成，注释不会保留（备份中仍有）
+pub fn migrate_config_file(path: &Path) -> Result<Option<MigrationOutcome>> {
+    let layer = ConfigLayer::read(ConfigSource::Global(path.to_path_buf()), path)?;
+    let mut value = layer.value;
+    let from = config_version(&value).unwrap_or_default();
+    let steps = migrate_config(&mut value)?;
+    if steps.is_empty() && from == config_version(&value).unwrap_or_default() {
+        return Ok(None);
     }
 
-    let content = fs::read_to_string(&config_path)
-        .with_context(|| format!("无法读取配置文件: {:?}", config_path))?;
+    let content = serde_yaml::to_string(&value).with_context(|| t!("config.serialize"))?;
+    let upgraded = ConfigLayer::parse(ConfigSource::Global(path.to_path_buf()), content.clone())?;
+    validate_layers(&[upgraded], &[])?.into_result()?;
+
+    let file_name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    let backup = path.with_file_name(format!("{}.v{}.bak", file_name, from));
+    fs::copy(path, &backup).with_context(|| t!("config.backup_failed", path = format!("{:?}", backup)))?;
+    crate::state_file::write_atomic(path, content.as_bytes())?;
+
+    Ok(Some(MigrationOutcome { from, backup, steps }))
+}
 
-    let config: Config = serde_yaml::from_str(&content)
-        .with_context(|| format!("配置文件格式错误: {:?}", config_path))?;
+/// 加载全局配置（应用环境变量和 `--set` 覆盖）
+pub fn load_config() -> Result<Config> {
+    Ok(load_layered_config(None, &active_overrides())?.config)
+}
 
-    Ok(config)
+/// 加载指定目录所在项目的生效配置（全局配置 + `.ccn.yaml` + 环境变量和 `--set` 覆盖）
+pub fn load_project_config(project_dir: Option<&Path>) -> Result<Config> {
+    Ok(load_layered_config(project_dir, &active_overrides())?.config)
 }
 
 /// 保存配置文件
@@ -143,14 +1152,14 @@ pub fn save_config(config: &Config) -> Result<()> {
     // 确保目录存在
     if let Some(parent) = config_path.parent() {
         fs::create_dir_all(parent)
-            .with_context(|| format!("无法创建配置目录: {:?}", parent))?;
+            .with_context(|| t!("config.create_dir", path = format!("{:?}", parent)))?;
     }
 
     let content = serde_yaml::to_string(config)
-        .with_context(|| "序列化配置失败")?;
+        .with_context(|| t!("config.serialize"))?;
 
     fs::write(&config_path, content)
-        .with_context(|| format!("无法写入配置文件: {:?}", config_path))?;
+        .with_context(|| t!("config.write_failed", path = format!("{:?}", config_path)))?;
 
     Ok(())
 }
@@ -159,14 +1168,151 @@ pub fn save_config(config: &Config) -> Result<()> {
 mod tests {
     use super::*;
 
+    fn yaml(content: &str) -> serde_yaml::Value {
+        serde_yaml::from_str(content).unwrap()
+    }
+
+    #[test]
+    fn test_project_overlay_deep_merge() {
+        let global = PathBuf::from("/home/user/.config/claude-code-notify/config.yaml");
+        let project = PathBuf::from("/work/monorepo/.ccn.yaml");
+        let layered = LayeredConfig::from_layers(vec![
+            (
+                ConfigSource::Global(global.clone()),
+                yaml("threshold:\n  min_duration: 10\n  whitelist: [deploy]\nsound_enabled: false\n"),
+            ),
+            (
+                ConfigSource::Project(project.clone()),
+                yaml("threshold:\n  min_duration: 120\ntemplates:\n  build:\n    match: cargo\n    icon: build\n    sound: default\n    duration: 8000\n"),
+            ),
+        ])
+        .unwrap();
+
+        // 映射逐键合并：阈值被覆盖，白名单保留
+        assert_eq!(layered.config.threshold.min_duration, 120);
+        assert_eq!(layered.config.threshold.whitelist, vec![MatchRule::substring("deploy")]);
+        assert!(!layered.config.sound_enabled);
+        assert!(layered.config.templates.custom.contains_key("build"));
+
+        let origins = layered.origins().unwrap();
+        let origin = |key: &str| origins.iter().find(|(k, _, _)| k == key).unwrap().2.clone();
+        assert_eq!(origin("threshold.min_duration"), ConfigSource::Project(project.clone()));
+        assert_eq!(origin("threshold.whitelist"), ConfigSource::Global(global.clone()));
+        assert_eq!(origin("sound_enabled"), ConfigSource::Global(global));
+        assert_eq!(origin("templates.build.duration"), ConfigSource::Project(project));
+        assert_eq!(origin("aggregation.window"), ConfigSource::Default);
+    }
+
+    #[test]
+    fn test_project_overlay_errors_name_file() {
+        let project = PathBuf::from("/work/app/.ccn.yaml");
+        let err = LayeredConfig::from_layers(vec![
+            (ConfigSource::Global(PathBuf::from("global.yaml")), yaml("version: \"1.0\"")),
+            (ConfigSource::Project(project), yaml("threshold:\n  min_duration: lots\n")),
+        ])
+        .unwrap_err();
+        assert!(format!("{:#}", err).contains("/work/app/.ccn.yaml"));
+
+        // 空的项目配置不覆盖任何值
+        let layered = LayeredConfig::from_layers(vec![
+            (ConfigSource::Global(PathBuf::from("global.yaml")), yaml("sound_enabled: false")),
+            (ConfigSource::Project(PathBuf::from(".ccn.yaml")), serde_yaml::Value::Null),
+        ])
+        .unwrap();
+        assert!(!layered.config.sound_enabled);
+    }
+
+    #[test]
+    fn test_override_parsing() {
+        let item = ConfigOverride::from_env_var("CCN_THRESHOLD__MIN_DURATION", "30").unwrap();
+        assert_eq!(item.key, "threshold.min_duration");
+        assert_eq!(item.source(), ConfigSource::Env("CCN_THRESHOLD__MIN_DURATION".to_string()));
+        assert_eq!(ConfigOverride::from_env_var("CCN_SOUND_ENABLED", "0").unwrap().key, "sound_enabled");
+        assert!(ConfigOverride::from_env_var("CCN_", "x").is_none());
+        assert!(ConfigOverride::from_env_var("CCN_A____B", "x").is_none());
+        assert!(ConfigOverride::from_env_var("HOME", "/root").is_none());
+
+        let item = ConfigOverride::parse_set("aggregation.window=1500").unwrap();
+        assert_eq!((item.key.as_str(), item.value.as_str()), ("aggregation.window", "1500"));
+        assert_eq!(item.source(), ConfigSource::Cli);
+        assert_eq!(ConfigOverride::parse_set("logging.file=a=b").unwrap().value, "a=b");
+        assert!(ConfigOverride::parse_set("sound_enabled").is_err());
+        assert!(ConfigOverride::parse_set("threshold..min_duration=1").is_err());
+    }
+
+    #[test]
+    fn test_override_precedence() {
+        let global = PathBuf::from("global.yaml");
+        let project = PathBuf::from("/work/app/.ccn.yaml");
+        let overrides = vec![
+            ConfigOverride::from_env_var("CCN_THRESHOLD__MIN_DURATION", "30").unwrap(),
+            ConfigOverride::from_env_var("CCN_SOUND_ENABLED", "false").unwrap(),
+            ConfigOverride::from_env_var("CCN_VERSION", "2.0").unwrap(),
+            ConfigOverride::parse_set("threshold.min_duration=45").unwrap(),
+            ConfigOverride::parse_set("threshold.whitelist=[deploy, release]").unwrap(),
+        ];
+
+        let layered = LayeredConfig::from_layers(vec![
+            (ConfigSource::Global(global.clone()), yaml("threshold:\n  min_duration: 10\naggregation:\n  window: 800\n")),
+            (ConfigSource::Project(project), yaml("threshold:\n  min_duration: 120\n")),
+        ])
+        .unwrap()
+        .with_overrides(&overrides)
+        .unwrap();
+
+        // 默认值 < 全局 < 项目 < 环境变量 < --set
+        assert_eq!(layered.config.threshold.min_duration, 45);
+        assert!(!layered.config.sound_enabled);
+        assert_eq!(layered.config.version, "2.0");
+        assert_eq!(layered.config.aggregation.window, 800);
+        assert_eq!(
+            layered.config.threshold.whitelist,
+            vec![MatchRule::substring("deploy"), MatchRule::substring("release")]
+        );
+
+        let origins = layered.origins().unwrap();
+        let origin = |key: &str| origins.iter().find(|(k, _, _)| k == key).unwrap().2.clone();
+        assert_eq!(origin("threshold.min_duration"), ConfigSource::Cli);
+        assert_eq!(origin("sound_enabled"), ConfigSource::Env("CCN_SOUND_ENABLED".to_string()));
+        assert_eq!(origin("aggregation.window"), ConfigSource::Global(global));
+        assert_eq!(origin("aggregation.max_toasts"), ConfigSource::Default);
+    }
+
+    #[test]
+    fn test_invalid_override_names_source() {
+        let err = LayeredConfig::from_layers(Vec::new())
+            .unwrap()
+            .with_overrides(&[ConfigOverride::from_env_var("CCN_AGGREGATION__WINDOW", "soon").unwrap()])
+            .unwrap_err();
+        assert!(format!("{}", err).contains("CCN_AGGREGATION__WINDOW"));
+    }
+
+    #[test]
+    fn test_find_project_config_walks_up() {
+        let root = std::env::temp_dir().join(format!("ccn-test-project-{}", std::process::id()));
+        let nested = root.join("crates").join("core").join("src");
+        fs::create_dir_all(&nested).unwrap();
+
+        assert_ne!(find_project_config(&nested).as_deref(), Some(root.join(PROJECT_CONFIG_FILE).as_path()));
+
+        fs::write(root.join(PROJECT_CONFIG_FILE), "threshold:\n  min_duration: 60\n").unwrap();
+        assert_eq!(find_project_config(&nested), Some(root.join(PROJECT_CONFIG_FILE)));
+
+        // 更近的配置文件优先
+        fs::write(root.join("crates").join(PROJECT_CONFIG_FILE), "").unwrap();
+        assert_eq!(find_project_config(&nested), Some(root.join("crates").join(PROJECT_CONFIG_FILE)));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
     #[test]
     fn test_config_default() {
         let config = Config::default();
-        assert_eq!(config.version, "1.0");
-        assert_eq!(config.sound_enabled, true);
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert!(config.sound_enabled);
         assert_eq!(config.threshold.min_duration, 10);
         assert!(config.threshold.whitelist.is_empty());
-        assert_eq!(config.aggregation.enabled, true);
+        assert!(config.aggregation.enabled);
         assert_eq!(config.aggregation.window, 5000);
         assert_eq!(config.aggregation.max_toasts, 3);
         assert_eq!(config.logging.level, "info");
@@ -211,15 +1357,89 @@ logging:
 "#;
 
         let config: Config = serde_yaml::from_str(yaml).unwrap();
-        assert_eq!(config.sound_enabled, false);
+        assert!(!config.sound_enabled);
         assert_eq!(config.threshold.min_duration, 15);
         assert_eq!(config.threshold.whitelist.len(), 2);
-        assert_eq!(config.threshold.whitelist[0], "deploy");
-        assert_eq!(config.aggregation.enabled, false);
+        assert_eq!(config.threshold.whitelist[0], MatchRule::substring("deploy"));
+        assert!(!config.aggregation.enabled);
         assert_eq!(config.aggregation.window, 3000);
         assert_eq!(config.aggregation.max_toasts, 5);
         assert_eq!(config.logging.level, "debug");
         assert!(config.logging.file.is_some());
+
+        // 未配置 hooks 时使用默认事件
+        assert_eq!(config.hooks.events, HooksConfig::default().events);
+    }
+
+    #[test]
+    fn test_webhook_config() {
+        let yaml = r#"
+url: "https://hooks.example.com/ccn"
+retries: 2
+headers:
+  Authorization: "Bearer token"
+body:
+  text: "{title}: {message}"
+"#;
+        let webhook: WebhookConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(webhook.url, "https://hooks.example.com/ccn");
+        assert_eq!(webhook.timeout, 5000);
+        assert_eq!(webhook.retries, 2);
+        assert_eq!(webhook.retry_delay, 500);
+        assert_eq!(webhook.headers["Authorization"], "Bearer token");
+        assert_eq!(webhook.body.unwrap()["text"], "{title}: {message}");
+
+        // 默认不配置 webhook，也不写入配置文件
+        let config = Config::default();
+        assert!(config.webhook.is_none());
+        assert!(!serde_yaml::to_string(&config).unwrap().contains("webhook"));
+    }
+
+    #[test]
+    fn test_notifiers_config() {
+        let yaml = r#"
+- type: desktop
+- type: webhook
+  url: "https://hooks.example.com/ccn"
+  retries: 1
+  statuses: [error]
+- type: log
+  path: "~/ccn.log"
+  min_duration: 30
+"#;
+        let notifiers: Vec<NotifierConfig> = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(notifiers.len(), 3);
+
+        assert!(matches!(notifiers[0].kind, NotifierKind::Desktop));
+        assert!(notifiers[0].statuses.is_empty());
+
+        match &notifiers[1].kind {
+            NotifierKind::Webhook(webhook) => {
+                assert_eq!(webhook.url, "https://hooks.example.com/ccn");
+                assert_eq!(webhook.retries, 1);
+            }
+            other => panic!("unexpected notifier: {:?}", other),
+        }
+        assert_eq!(notifiers[1].statuses, vec![NotificationStatus::Error]);
+
+        assert!(matches!(&notifiers[2].kind, NotifierKind::Log { path } if path == "~/ccn.log"));
+        assert_eq!(notifiers[2].min_duration, Some(30));
+    }
+
+    #[test]
+    fn test_hooks_config_events() {
+        let yaml = r#"
+events:
+  - Stop
+  - SubagentStop
+"#;
+        let hooks: HooksConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(hooks.events, vec![HookEventKind::Stop, HookEventKind::SubagentStop]);
+        assert_eq!(hooks.events[1].event_name(), "SubagentStop");
+
+        let default = HooksConfig::default();
+        assert!(default.events.contains(&HookEventKind::Stop));
+        assert!(!default.events.contains(&HookEventKind::SubagentStop));
     }
 
     #[test]
@@ -227,7 +1447,12 @@ logging:
         let template = TemplateConfig {
             icon: "test.png".to_string(),
             sound: "test.wav".to_string(),
+            sounds: StatusSounds::default(),
             duration: 8000,
+            title: None,
+            body: None,
+            matcher: None,
+            priority: 0,
         };
 
         assert_eq!(template.icon, "test.png");
@@ -241,14 +1466,24 @@ logging:
         custom.insert("build".to_string(), TemplateConfig {
             icon: "build.png".to_string(),
             sound: "build.wav".to_string(),
+            sounds: StatusSounds::default(),
             duration: 6000,
+            title: None,
+            body: None,
+            matcher: None,
+            priority: 0,
         });
 
         let templates = TemplatesConfig {
             default: TemplateConfig {
                 icon: "default.png".to_string(),
                 sound: "default.wav".to_string(),
+                sounds: StatusSounds::default(),
                 duration: 5000,
+                title: None,
+                body: None,
+                matcher: None,
+                priority: 0,
             },
             custom,
         };
@@ -258,6 +1493,41 @@ logging:
         assert_eq!(templates.custom["build"].duration, 6000);
     }
 
+    #[test]
+    fn test_template_sound_selection() {
+        let config: Config = serde_yaml::from_str(
+            "\
+templates:
+  default:
+    sound: sounds/done.wav
+    sounds:
+      error: default
+  build:
+    sounds:
+      success: sounds/build.wav
+      pending: none
+  lint:
+    sound: sounds/lint.wav
+",
+        )
+        .unwrap();
+        let sound = |name: &str, status| config.templates.sound(name, status);
+
+        assert_eq!(sound("default", NotificationStatus::Success), "sounds/done.wav");
+        assert_eq!(sound("default", NotificationStatus::Error), "default");
+        assert_eq!(sound("build", NotificationStatus::Success), "sounds/build.wav");
+        assert_eq!(sound("build", NotificationStatus::Pending), "none");
+        // 模板没有设置时沿用默认模板
+        assert_eq!(sound("build", NotificationStatus::Error), "default");
+        assert_eq!(sound("lint", NotificationStatus::Error), "sounds/lint.wav");
+        assert_eq!(sound("missing", NotificationStatus::Success), "sounds/done.wav");
+
+        use crate::sound::{Sound, SystemSound};
+        assert_eq!(Sound::from_config("default", NotificationStatus::Pending), Sound::System(SystemSound::Notification));
+        assert_eq!(Sound::from_config("none", NotificationStatus::Error), Sound::Silent);
+        assert_eq!(Sound::from_config("a.wav", NotificationStatus::Error), Sound::File("a.wav".to_string()));
+    }
+
     #[test]
     fn test_focus_assistant_mode() {
         let modes = vec![
@@ -272,4 +1542,184 @@ logging:
             assert_eq!(mode, deserialized);
         }
     }
+
+    #[test]
+    fn test_match_rule_formats() {
+        let yaml = r#"
+- deploy
+- pattern: "npm run *"
+  mode: glob
+- pattern: "^cargo"
+  mode: regex
+  anchor: start
+"#;
+        let rules: Vec<MatchRule> = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(rules[0], MatchRule::substring("deploy"));
+        assert_eq!(rules[1].mode, MatchMode::Glob);
+        assert_eq!(rules[1].anchor, Anchor::None);
+        assert_eq!(rules[2].anchor, Anchor::Start);
+
+        // 纯子串规则序列化为字符串
+        let yaml = serde_yaml::to_string(&rules).unwrap();
+        assert!(yaml.starts_with("- deploy\n"));
+        let round_trip: Vec<MatchRule> = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(round_trip, rules);
+
+        assert_eq!(rules[2].to_string(), "regex:\"^cargo\" (anchor=start)");
+    }
+
+    #[test]
+    fn test_rules_config() {
+        let yaml = r#"
+- name: slow-builds
+  when:
+    status: [success]
+    duration: {min: 60}
+    cmd: {pattern: "cargo build*", mode: glob}
+    project: my-app
+    event: [Stop]
+    time: {start: "09:00", end: "18:00", days: [Mon, Fri]}
+  action: notify
+  force_sound: true
+  sinks: [team-webhook]
+- action: drop
+"#;
+        let rules: Vec<RuleConfig> = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].when.duration, Some(DurationRange { min: Some(60), max: None }));
+        assert_eq!(rules[0].when.time.as_ref().unwrap().days, vec![chrono::Weekday::Mon, chrono::Weekday::Fri]);
+        assert!(rules[0].force_sound);
+        assert_eq!(rules[0].sinks, vec!["team-webhook"]);
+        assert_eq!(rules[1].action, RuleAction::Drop);
+        assert!(rules[1].when.status.is_empty());
+    }
+
+    #[test]
+    fn test_effective_rules_from_threshold() {
+        let mut config = Config::default();
+        config.threshold.whitelist = vec!["deploy".into()];
+
+        let names: Vec<_> = config.effective_rules().into_iter().map(|r| r.name.unwrap()).collect();
+        assert_eq!(names, vec!["error", "unknown-duration", "whitelist #1", "threshold", "below-threshold"]);
+
+        // 配置了 rules 时原样使用
+        config.rules = vec![RuleConfig {
+            name: None,
+            when: RuleConditions::default(),
+            action: RuleAction::Drop,
+            force_sound: false,
+            sinks: vec![],
+        }];
+        assert_eq!(config.effective_rules().len(), 1);
+    }
+
+    /// 按加载配置的流程处理历史版本的配置文件：升级、校验、合并默认值
+    fn load_fixture(name: &str, content: &str) -> (Config, Vec<&'static str>) {
+        let source = ConfigSource::Global(PathBuf::from(name));
+        let mut layer = ConfigLayer::parse(source.clone(), content.to_string()).unwrap();
+        let steps = migrate_config(&mut layer.value).unwrap();
+
+        let report = validate_layers(std::slice::from_ref(&layer), &[]).unwrap();
+        assert!(report.issues.is_empty(), "{}: {:?}", name, report.issues);
+        let config = LayeredConfig::from_layers(vec![(source, layer.value)]).unwrap().config;
+        assert_eq!(config.version, CONFIG_VERSION);
+        (config, steps.iter().map(|step| step.to).collect())
+    }
+
+    #[test]
+    fn test_migrate_v1_0_baseline() {
+        let (config, steps) = load_fixture("v1.0-baseline", include_str!("../tests/fixtures/config/v1.0-baseline.yaml"));
+        assert_eq!(steps, vec!["1.1"]);
+        assert_eq!(config.threshold.min_duration, 10);
+        assert!(config.webhook.is_none());
+        assert!(config.notifiers.is_empty());
+        assert_eq!(config.hooks.events.len(), 3);
+    }
+
+    #[test]
+    fn test_migrate_v1_0_templates() {
+        let (config, steps) = load_fixture("v1.0-templates", include_str!("../tests/fixtures/config/v1.0-templates.yaml"));
+        assert_eq!(steps, vec!["1.1"]);
+        assert!(!config.sound_enabled);
+        assert_eq!(config.focus_assistant_mode, FocusAssistantMode::Always);
+        assert_eq!(config.threshold.whitelist, vec![MatchRule::substring("deploy"), MatchRule::substring("npm publish")]);
+        assert_eq!(config.templates.custom["build"].duration, 8000);
+        assert_eq!(config.logging.level, "debug");
+    }
+
+    #[test]
+    fn test_migrate_v1_0_webhook_to_notifiers() {
+        let (config, steps) = load_fixture("v1.0-webhook", include_str!("../tests/fixtures/config/v1.0-webhook.yaml"));
+        assert_eq!(steps, vec!["1.1"]);
+        assert!(config.webhook.is_none());
+        assert_eq!(config.notifiers.len(), 1);
+
+        let notifier = &config.notifiers[0];
+        assert!(notifier.name.is_none());
+        assert!(notifier.statuses.is_empty());
+        let NotifierKind::Webhook(webhook) = &notifier.kind else {
+            panic!("应迁移为 webhook 渠道: {:?}", notifier.kind);
+        };
+        assert_eq!(webhook.url, "https://hooks.example.com/ccn");
+        assert_eq!(webhook.retries, 2);
+        assert_eq!(webhook.timeout, 5000);
+        assert_eq!(webhook.headers["Authorization"], "Bearer token");
+        assert!(webhook.body.is_some());
+        assert_eq!(config.hooks.events, vec![HookEventKind::Notification, HookEventKind::Stop, HookEventKind::SubagentStop]);
+    }
+
+    #[test]
+    fn test_migrate_v1_0_notifiers_keeps_webhook() {
+        let (config, steps) = load_fixture("v1.0-notifiers", include_str!("../tests/fixtures/config/v1.0-notifiers.yaml"));
+        assert_eq!(steps, vec!["1.1"]);
+        // 已有 notifiers 时 webhook 本来就不生效，不做改动
+        assert_eq!(config.webhook.as_ref().unwrap().url, "https://old.example.com/ccn");
+        assert_eq!(config.notifiers.len(), 2);
+        assert_eq!(config.templates.custom["deploy"].matcher.as_ref().unwrap().mode, MatchMode::Glob);
+        assert_eq!(config.schedule.quiet_hours[0].action, QuietAction::Defer);
+        assert_eq!(config.rules[0].sinks, vec!["team"]);
+        assert_eq!(config.default_action, RuleAction::Drop);
+    }
+
+    #[test]
+    fn test_migrate_current_and_unversioned() {
+        let mut current = serde_yaml::to_value(Config::default()).unwrap();
+        assert!(migrate_config(&mut current).unwrap().is_empty());
+
+        // 项目配置通常不写版本号，视为当前版本，不做迁移
+        let mut project = yaml("webhook:\n  url: https://hooks.example.com/ccn\n");
+        assert!(migrate_config(&mut project).unwrap().is_empty());
+        assert!(project.get("webhook").is_some());
+    }
+
+    #[test]
+    fn test_migrate_rejects_newer_version() {
+        let error = migrate_config(&mut yaml("version: '9.0'\n")).unwrap_err().to_string();
+        let expected = t!("config.migrate.too_new", version = "9.0", supported = CONFIG_VERSION, ccn = env!("CARGO_PKG_VERSION"));
+        assert_eq!(error, expected);
+        assert!(migrate_config(&mut yaml("version: 1.2\n")).is_err());
+        let error = migrate_config(&mut yaml("version: latest\n")).unwrap_err().to_string();
+        assert_eq!(error, t!("config.migrate.unknown_version", version = "\"latest\""));
+    }
+
+    #[test]
+    fn test_migrate_config_file_writes_backup() {
+        let dir = std::env::temp_dir().join(format!("ccn-test-migrate-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.yaml");
+        let original = include_str!("../tests/fixtures/config/v1.0-webhook.yaml");
+        fs::write(&path, original).unwrap();
+
+        let outcome = migrate_config_file(&path).unwrap().unwrap();
+        assert_eq!(outcome.from, "1.0");
+        assert_eq!(outcome.backup, dir.join("config.yaml.v1.0.bak"));
+        assert_eq!(fs::read_to_string(&outcome.backup).unwrap(), original);
+
+        let upgraded = yaml(&fs::read_to_string(&path).unwrap());
+        assert_eq!(config_version(&upgraded).as_deref(), Some(CONFIG_VERSION));
+        assert!(upgraded.get("webhook").is_none());
+        assert!(migrate_config_file(&path).unwrap().is_none());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
 }
@@ -0,0 +1,443 @@
+//! 配置文件编辑模块
+//!
+//! 供 `ccn config set/unset/edit` 修改全局配置文件：能就地修改时只改动对应的行，保留注释和格式，
+//! 否则重新序列化整个文件。写入前按 `Config` 校验类型
+
+use anyhow::{bail, Context, Result};
+use serde_yaml::{Mapping, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::{merge_yaml, migrate_config, Config, ConfigOverride, ConfigSource, LayeredConfig};
+use crate::config_validate::{is_known_key, validate_layers, ConfigLayer};
+use crate::state_file;
+
+/// 按点分路径查找值，列表元素用下标表示（如 `notifiers.0.url`）
+pub fn lookup<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+    key.split('.').try_fold(value, |value, segment| match value {
+        Value::Sequence(items) => items.get(segment.parse::<usize>().ok()?),
+        _ => value.get(segment),
+    })
+}
+
+/// 校验配置文件内容（类型和语义约束），返回合并默认值后的配置
+pub fn validate_config_text(path: &Path, content: &str) -> Result<Config> {
+    let mut layer = ConfigLayer::parse(ConfigSource::Global(path.to_path_buf()), content.to_string())?;
+    migrate_config(&mut layer.value)?;
+    let report = validate_layers(std::slice::from_ref(&layer), &[])?;
+    report.log_warnings();
+    report.into_result()?;
+    merge_defaults(path, &layer.value)
+}
+
+/// 校验配置文件的值（旧版本先在内存中升级）
+fn validate_value(path: &Path, value: &Value) -> Result<Config> {
+    let mut layer = ConfigLayer {
+        source: ConfigSource::Global(path.to_path_buf()),
+        value: value.clone(),
+        text: None,
+    };
+    migrate_config(&mut layer.value)?;
+    validate_layers(std::slice::from_ref(&layer), &[])?.into_result()?;
+    merge_defaults(path, &layer.value)
+}
+
+fn merge_defaults(path: &Path, value: &Value) -> Result<Config> {
+    Ok(LayeredConfig::from_layers(vec![(ConfigSource::Global(path.to_path_buf()), value.clone())])?.config)
+}
+
+/// 可编辑的配置文件
+pub struct ConfigDocument {
+    path: PathBuf,
+    content: String,
+    value: Value,
+}
+
+impl ConfigDocument {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| t!("error.read_config", path = format!("{:?}", path)))?;
+        Self::from_content(path, content)
+    }
+
+    fn from_content(path: &Path, content: String) -> Result<Self> {
+        let value = match serde_yaml::from_str(&content)
+            .with_context(|| t!("config.invalid_format", source = format!("{:?}", path)))?
+        {
+            Value::Null => Value::Mapping(Mapping::new()),
+            value => value,
+        };
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            content,
+            value,
+        })
+    }
+
+    #[cfg(test)]
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    /// 设置配置项，值按 YAML 解析（原值为字符串时保留原文）
+    ///
+    /// 只校验修改后的配置，因此可以用来修复无效的值；配置项不存在或值的类型不符时返回错误，文件内容不变
+    pub fn set(&mut self, key: &str, raw: &str) -> Result<()> {
+        let effective = self.effective_value(key)?;
+        let item = ConfigOverride {
+            key: key.to_string(),
+            value: raw.to_string(),
+            env: None,
+        };
+
+        let mut value = self.value.clone();
+        merge_yaml(&mut value, item.to_layer(&effective));
+        let config = validate_value(&self.path, &value)
+            .with_context(|| t!("config.invalid_value", key = key, value = raw))?;
+
+        if lookup(&serde_yaml::to_value(&config)?, key).is_none() && !is_known_key(&serde_yaml::to_value(&config)?, key) {
+            bail!("{}", t!("config.unknown_key", key = key));
+        }
+
+        let new_value = lookup(&value, key).cloned().unwrap_or(Value::Null);
+        self.apply(value, |content| set_in_place(content, key, &new_value))
+    }
+
+    /// 合并默认值后的配置，用于判断 `key` 原来的类型
+    ///
+    /// 配置无效时不做校验，直接把文件内容合并到默认值上；`key` 的原值可能正是无效的值，按默认值的类型处理
+    fn effective_value(&self, key: &str) -> Result<Value> {
+        if let Ok(config) = validate_value(&self.path, &self.value) {
+            return Ok(serde_yaml::to_value(config)?);
+        }
+
+        let mut value = self.value.clone();
+        let _ = migrate_config(&mut value);
+        remove_path(&mut value, key);
+        let mut effective = serde_yaml::to_value(Config::default())?;
+        merge_yaml(&mut effective, value);
+        Ok(effective)
+    }
+
+    /// 删除配置项，之后使用默认值
+    pub fn unset(&mut self, key: &str) -> Result<()> {
+        let mut value = self.value.clone();
+        if !remove_path(&mut value, key) {
+            bail!("{}", t!("config.key_not_in_file", key = key));
+        }
+        validate_value(&self.path, &value)
+            .with_context(|| t!("config.invalid_after_unset", key = key))?;
+
+        self.apply(value, |content| remove_in_place(content, key))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        state_file::write_atomic(&self.path, self.content.as_bytes())
+    }
+
+    /// 应用修改：就地修改的结果与预期一致时使用它，否则重新序列化（注释不会保留）
+    fn apply(&mut self, value: Value, edit: impl FnOnce(&str) -> Option<String>) -> Result<()> {
+        let in_place = edit(&self.content)
+            .filter(|content| serde_yaml::from_str::<Value>(content).ok().as_ref() == Some(&value));
+
+        self.content = match in_place {
+            Some(content) => content,
+            None => {
+                log::info!("{}", t!("config.log.regenerate"));
+                serde_yaml::to_string(&value)?
+            }
+        };
+        self.value = value;
+        Ok(())
+    }
+}
+
+/// 生成只包含 `key` 的嵌套映射
+pub(crate) fn nest(key: &str, value: Value) -> Value {
+    key.rsplit('.').fold(value, |value, segment| {
+        let mut map = Mapping::new();
+        map.insert(Value::String(segment.to_string()), value);
+        Value::Mapping(map)
+    })
+}
+
+pub(crate) fn remove_path(value: &mut Value, key: &str) -> bool {
+    let (parent, last) = match key.rsplit_once('.') {
+        Some((parent, last)) => match key_path_mut(value, parent) {
+            Some(parent) => (parent, last),
+            None => return false,
+        },
+        None => (value, key),
+    };
+
+    parent
+        .as_mapping_mut()
+        .is_some_and(|map| map.remove(last).is_some())
+}
+
+fn key_path_mut<'a>(value: &'a mut Value, key: &str) -> Option<&'a mut Value> {
+    key.split('.').try_fold(value, |value, segment| value.get_mut(segment))
+}
+
+/// 行的缩进，空行和注释行返回 None
+pub(crate) fn indent_of(line: &str) -> Option<usize> {
+    let trimmed = line.trim_start();
+    if trimmed.is_empty() || trimmed.starts_with('#') {
+        None
+    } else {
+        Some(line.len() - trimmed.len())
+    }
+}
+
+/// `key: ...` 形式的行的键名（不支持列表项）
+pub(crate) fn line_key(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with('-') {
+        return None;
+    }
+
+    let (key, rest) = trimmed.split_once(':')?;
+    if !rest.is_empty() && !rest.starts_with(' ') {
+        return None;
+    }
+    Some(key.trim().trim_matches('"').trim_matches('\''))
+}
+
+/// 查找点分路径对应的行，返回 (行号, 缩进, 所属块的结束行号)
+fn find_key(lines: &[&str], key: &str) -> Option<(usize, usize, usize)> {
+    let (mut start, mut end) = (0, lines.len());
+    let mut found = None;
+
+    for segment in key.split('.') {
+        // 只在直接子项（缩进与块中第一项相同）中查找
+        let child_indent = (start..end).find_map(|i| indent_of(lines[i]))?;
+        let line = (start..end)
+            .find(|&i| indent_of(lines[i]) == Some(child_indent) && line_key(lines[i]) == Some(segment))?;
+
+        let mut block_end = (line + 1..end)
+            .find(|&i| indent_of(lines[i]).is_some_and(|indent| indent <= child_indent))
+            .unwrap_or(end);
+        // 块末尾的空行和缩进不深于该键的注释属于后面的内容
+        while block_end > line + 1 {
+            let last = lines[block_end - 1];
+            let trimmed = last.trim_start();
+            let is_outer_comment = trimmed.starts_with('#') && last.len() - trimmed.len() <= child_indent;
+            if trimmed.is_empty() || is_outer_comment {
+                block_end -= 1;
+            } else {
+                break;
+            }
+        }
+
+        found = Some((line, child_indent, block_end));
+        (start, end) = (line + 1, block_end);
+    }
+
+    found
+}
+
+/// 行内注释的起始位置（引号外、前面有空白的 `#`）
+fn comment_start(text: &str) -> Option<usize> {
+    let mut quote = None;
+    let mut previous = ' ';
+
+    for (index, ch) in text.char_indices() {
+        match (quote, ch) {
+            (None, '"' | '\'') => quote = Some(ch),
+            (Some(q), c) if c == q => quote = None,
+            (None, '#') if previous.is_whitespace() => return Some(index),
+            _ => {}
+        }
+        previous = ch;
+    }
+    None
+}
+
+fn join_lines(lines: &[String], original: &str) -> String {
+    let mut content = lines.join("\n");
+    if original.ends_with('\n') {
+        content.push('\n');
+    }
+    content
+}
+
+/// 就地替换标量值，保留行内注释；目标不是单行标量时返回 None
+fn set_in_place(content: &str, key: &str, value: &Value) -> Option<String> {
+    let literal = serde_yaml::to_string(value).ok()?;
+    let literal = literal.trim_end();
+    if literal.contains('\n') {
+        return None;
+    }
+
+    let lines: Vec<&str> = content.lines().collect();
+    let (index, _, block_end) = find_key(&lines, key)?;
+    if (index + 1..block_end).any(|i| indent_of(lines[i]).is_some()) {
+        return None;
+    }
+
+    let line = lines[index];
+    let colon = line.find(':')?;
+    let rest = &line[colon + 1..];
+    let comment = comment_start(rest).map(|start| match rest[..start].trim_end().len() {
+        0 => format!(" {}", &rest[start..]),
+        value_end => rest[value_end..].to_string(),
+    });
+
+    let mut updated: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+    updated[index] = format!("{}: {}{}", &line[..colon], literal, comment.unwrap_or_default());
+    Some(join_lines(&updated, content))
+}
+
+/// 就地删除键及其子块
+fn remove_in_place(content: &str, key: &str) -> Option<String> {
+    let lines: Vec<&str> = content.lines().collect();
+    let (index, _, block_end) = find_key(&lines, key)?;
+
+    let updated: Vec<String> = lines
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !(index..block_end).contains(i))
+        .map(|(_, line)| line.to_string())
+        .collect();
+    Some(join_lines(&updated, content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = "\
+# CCN 配置
+version: '1.0'
+sound_enabled: true   # 是否播放声音
+focus_assistant_mode: respect
+threshold:
+  min_duration: 10 # 秒
+  whitelist:
+  - deploy
+templates:
+  default:
+    icon: default
+    sound: default
+    duration: 5000
+  build:
+    icon: build
+    sound: default
+    duration: 8000
+
+# 聚合设置
+aggregation:
+  enabled: true
+  window: 5000
+  max_toasts: 3
+logging:
+  level: info
+";
+
+    fn document() -> ConfigDocument {
+        ConfigDocument::from_content(Path::new("config.yaml"), CONFIG.to_string()).unwrap()
+    }
+
+    fn parsed(document: &ConfigDocument) -> Config {
+        validate_config_text(Path::new("config.yaml"), document.content()).unwrap()
+    }
+
+    #[test]
+    fn test_set_scalar_keeps_comments() {
+        let mut document = document();
+        document.set("threshold.min_duration", "30").unwrap();
+        document.set("sound_enabled", "false").unwrap();
+        document.set("aggregation.window", "1500").unwrap();
+
+        let content = document.content();
+        assert!(content.contains("  min_duration: 30 # 秒\n"));
+        assert!(content.contains("sound_enabled: false   # 是否播放声音\n"));
+        assert!(content.contains("# 聚合设置\n"));
+        assert!(content.contains("  window: 1500\n"));
+        // 其他同名的键不受影响
+        assert!(content.contains("    duration: 8000\n"));
+
+        let config = parsed(&document);
+        assert_eq!(config.threshold.min_duration, 30);
+        assert!(!config.sound_enabled);
+        assert_eq!(config.aggregation.window, 1500);
+    }
+
+    #[test]
+    fn test_set_validates_types_and_keys() {
+        let mut document = document();
+
+        assert!(document.set("aggregation.window", "soon").is_err());
+        assert!(document.set("threshold.min_duration", "-5").is_err());
+        let error = document.set("aggregation.windw", "10").unwrap_err().to_string();
+        assert_eq!(error, t!("config.unknown_key", key = "aggregation.windw"));
+        assert_eq!(document.content(), CONFIG);
+
+        // 字符串类型的配置项保留原文；不支持的版本号被拒绝
+        document.set("version", "1.1").unwrap();
+        assert_eq!(parsed(&document).version, "1.1");
+        assert!(document.set("version", "9.0").is_err());
+
+        // 不写出默认值的配置项同样可以设置
+        document.set("default_action", "drop").unwrap();
+        assert!(document.content().contains("default_action: drop"));
+        document.set("templates.build.priority", "5").unwrap();
+        assert_eq!(parsed(&document).templates.custom["build"].priority, 5);
+    }
+
+    #[test]
+    fn test_set_repairs_invalid_value() {
+        let content = CONFIG.replace("  window: 5000\n", "  window: soon\n");
+        let mut document = ConfigDocument::from_content(Path::new("config.yaml"), content).unwrap();
+
+        // 修改其他配置项后仍然无效，文件内容不变
+        assert!(document.set("aggregation.max_toasts", "5").is_err());
+        assert!(document.content().contains("  window: soon\n"));
+
+        document.set("aggregation.window", "30s").unwrap();
+        assert!(document.content().contains("  window: 30s\n"));
+        assert_eq!(parsed(&document).aggregation.window, 30_000);
+    }
+
+    #[test]
+    fn test_set_list_regenerates_file() {
+        let mut document = document();
+        document.set("threshold.whitelist", "[deploy, release]").unwrap();
+
+        let whitelist: Vec<String> = parsed(&document)
+            .threshold
+            .whitelist
+            .iter()
+            .map(|rule| rule.pattern.clone())
+            .collect();
+        assert_eq!(whitelist, vec!["deploy", "release"]);
+    }
+
+    #[test]
+    fn test_unset_removes_block() {
+        let mut document = document();
+        document.unset("templates.build").unwrap();
+        document.unset("sound_enabled").unwrap();
+
+        let content = document.content();
+        assert!(!content.contains("build"));
+        assert!(!content.contains("sound_enabled"));
+        assert!(content.contains("# 聚合设置\naggregation:"));
+
+        let config = parsed(&document);
+        assert!(config.templates.custom.is_empty());
+        assert!(config.sound_enabled);
+
+        assert!(document.unset("templates.build").is_err());
+    }
+
+    #[test]
+    fn test_find_key_nested() {
+        let lines: Vec<&str> = CONFIG.lines().collect();
+        let (line, indent, _) = find_key(&lines, "templates.build.duration").unwrap();
+        assert_eq!((lines[line], indent), ("    duration: 8000", 4));
+        assert!(find_key(&lines, "threshold.window").is_none());
+        assert!(find_key(&lines, "logging.level").is_some());
+    }
+}
@@ -0,0 +1,690 @@
+//! 配置校验模块
+//!
+//! 逐层检查配置文件、环境变量和 `--set`：类型错误、语义约束（如 `max_toasts` 为 0、未知的日志级别）
+//! 和未知的配置项，每个问题都标明来源、行号和配置项路径
+
+use anyhow::{bail, Context, Result};
+use serde_yaml::{Mapping, Value};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use crate::config::{merge_yaml, Config, ConfigOverride, ConfigSource, MatchRule, NotifierKind};
+use crate::config_edit::{indent_of, line_key, lookup, nest, remove_path};
+use crate::i18n;
+use crate::notification::NotificationStatus;
+use crate::policy::compile_rule;
+use crate::template::Template;
+
+/// 问题的严重程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// 配置无法使用
+    Error,
+    /// 配置可以使用，但可能与预期不符
+    Warning,
+}
+
+/// 校验发现的问题
+#[derive(Debug, Clone)]
+pub struct ConfigIssue {
+    pub severity: Severity,
+    pub source: ConfigSource,
+    /// 行号（从 1 开始），无法定位时为 None
+    pub line: Option<usize>,
+    /// 点分路径，列表元素用下标表示（如 `notifiers.0.url`）；整个文件无效时为空
+    pub key: String,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)?;
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+        }
+        if !self.key.is_empty() {
+            write!(f, ": {}", self.key)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// 参与校验的一层配置
+pub struct ConfigLayer {
+    pub source: ConfigSource,
+    pub value: Value,
+    /// 文件内容，用于定位行号
+    pub text: Option<String>,
+}
+
+impl ConfigLayer {
+    /// 读取配置文件，YAML 语法错误的信息中带有行号
+    pub fn read(source: ConfigSource, path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| t!("error.read_config", path = format!("{:?}", path)))?;
+        Self::parse(source, text)
+    }
+
+    pub fn parse(source: ConfigSource, text: String) -> Result<Self> {
+        let value = serde_yaml::from_str(&text)
+            .with_context(|| t!("config.invalid_format", source = source))?;
+        Ok(Self {
+            source,
+            value,
+            text: Some(text),
+        })
+    }
+}
+
+/// 校验结果
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    pub issues: Vec<ConfigIssue>,
+}
+
+impl ValidationReport {
+    pub fn errors(&self) -> impl Iterator<Item = &ConfigIssue> {
+        self.issues.iter().filter(|issue| issue.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &ConfigIssue> {
+        self.issues.iter().filter(|issue| issue.severity == Severity::Warning)
+    }
+
+    pub fn log_warnings(&self) {
+        for issue in self.warnings() {
+            log::warn!("{}", t!("config.validate.warning_log", issue = issue));
+        }
+    }
+
+    /// 有错误时返回列出全部错误的 Err
+    pub fn into_result(self) -> Result<()> {
+        let errors: Vec<String> = self.errors().map(|issue| format!("  {}", issue)).collect();
+        if errors.is_empty() {
+            return Ok(());
+        }
+        bail!("{}\n{}", t!("config.validate.errors", count = errors.len()), errors.join("\n"))
+    }
+}
+
+/// 按优先级从低到高校验各层配置，之后依次应用 `overrides`
+///
+/// 类型错误的配置项会被跳过，以便继续检查其余配置项和语义约束
+pub fn validate_layers(layers: &[ConfigLayer], overrides: &[ConfigOverride]) -> Result<ValidationReport> {
+    let mut validator = Validator {
+        merged: serde_yaml::to_value(Config::default())?,
+        checked: Vec::new(),
+        issues: Vec::new(),
+    };
+
+    for layer in layers {
+        validator.check_layer(&layer.source, &layer.value, layer.text.as_deref());
+    }
+    for item in overrides {
+        let layer = item.to_layer(&validator.merged);
+        validator.check_layer(&item.source(), &layer, None);
+    }
+
+    validator.check_semantics()?;
+    Ok(ValidationReport {
+        issues: validator.issues,
+    })
+}
+
+/// 判断 `key` 是否为 `Config` 的配置项
+///
+/// 未知字段会被反序列化忽略：填入任何类型的值都能通过校验的配置项视为不存在
+pub(crate) fn is_known_key(value: &Value, key: &str) -> bool {
+    let mut probe_map = Mapping::new();
+    probe_map.insert(Value::String("__ccn_probe__".to_string()), Value::Null);
+    let probes = [
+        Value::Mapping(probe_map),
+        Value::String("__ccn_probe__".to_string()),
+        Value::Sequence(Vec::new()),
+    ];
+
+    probes.into_iter().any(|probe| {
+        let mut value = value.clone();
+        set_path(&mut value, key, probe).is_some() && parse(&value).is_err()
+    })
+}
+
+struct Validator<'a> {
+    /// 已通过检查的各层合并后的值
+    merged: Value,
+    /// 去掉无效配置项后的各层，用于定位语义问题的来源
+    checked: Vec<(ConfigSource, Value, Option<&'a str>)>,
+    issues: Vec<ConfigIssue>,
+}
+
+impl<'a> Validator<'a> {
+    fn check_layer(&mut self, source: &ConfigSource, layer: &Value, text: Option<&'a str>) {
+        let (layer, type_errors) = self.type_errors(layer);
+        for (key, message) in type_errors {
+            self.push(Severity::Error, source, text, key, message);
+        }
+
+        for key in self.unknown_keys(&layer) {
+            self.push(Severity::Warning, source, text, key, t!("config.validate.unknown_key"));
+        }
+
+        merge_yaml(&mut self.merged, layer.clone());
+        self.checked.push((source.clone(), layer, text));
+    }
+
+    fn push(&mut self, severity: Severity, source: &ConfigSource, text: Option<&str>, key: String, message: String) {
+        self.issues.push(ConfigIssue {
+            severity,
+            source: source.clone(),
+            line: text.and_then(|text| locate(text, &key)),
+            key,
+            message,
+        });
+    }
+
+    /// 找出类型不符的配置项，返回去掉这些配置项后的层
+    fn type_errors(&self, layer: &Value) -> (Value, Vec<(String, String)>) {
+        if parse(&merged(&self.merged, layer)).is_ok() {
+            return (layer.clone(), Vec::new());
+        }
+        if !matches!(layer, Value::Mapping(_)) {
+            return (Value::Null, vec![(String::new(), t!("config.validate.not_mapping"))]);
+        }
+
+        let mut leaves = Vec::new();
+        collect_leaves(layer, String::new(), &mut leaves);
+
+        // 单独合并到默认值上仍然出错的配置项；有些配置项依赖同层的其他配置项（如 webhook.url），
+        // 所以再放回去掉这些配置项的层中确认一次
+        let suspects: Vec<&(String, Value)> = leaves
+            .iter()
+            .filter(|(key, value)| parse(&merged(&self.merged, &nest(key, value.clone()))).is_err())
+            .collect();
+        let mut context = layer.clone();
+        for (key, _) in &suspects {
+            remove_path(&mut context, key);
+        }
+        let context = merged(&self.merged, &context);
+
+        let mut errors = Vec::new();
+        let mut cleaned = layer.clone();
+        for (key, value) in suspects {
+            if let Err(e) = parse(&merged(&context, &nest(key, value.clone()))) {
+                errors.extend(describe_type_error(&context, key, value, e));
+                remove_path(&mut cleaned, key);
+            }
+        }
+
+        if let Err(e) = parse(&merged(&self.merged, &cleaned)) {
+            errors.push((String::new(), e.to_string()));
+            cleaned = Value::Null;
+        }
+        (cleaned, errors)
+    }
+
+    /// 找出反序列化时被忽略的配置项（只报告最上层的未知键）
+    fn unknown_keys(&self, layer: &Value) -> Vec<String> {
+        let merged = merged(&self.merged, layer);
+        let Some(roundtrip) = parse(&merged).ok().and_then(|config| serde_yaml::to_value(config).ok()) else {
+            return Vec::new();
+        };
+
+        let mut unknown = Vec::new();
+        find_unknown(layer, &roundtrip, &merged, String::new(), &mut unknown);
+        unknown
+    }
+
+    fn check_semantics(&mut self) -> Result<()> {
+        let config = parse(&self.merged)?;
+
+        for (severity, key, message) in semantic_issues(&config) {
+            let (source, text) = self
+                .checked
+                .iter()
+                .rev()
+                .find(|(_, layer, _)| defines(layer, &key))
+                .map(|(source, _, text)| (source.clone(), *text))
+                .unwrap_or((ConfigSource::Default, None));
+            self.push(severity, &source, text, key, message);
+        }
+        Ok(())
+    }
+}
+
+fn parse(value: &Value) -> std::result::Result<Config, serde_yaml::Error> {
+    serde_yaml::from_value(value.clone())
+}
+
+fn merged(base: &Value, layer: &Value) -> Value {
+    let mut value = base.clone();
+    merge_yaml(&mut value, layer.clone());
+    value
+}
+
+fn join(prefix: &str, segment: &str) -> String {
+    if prefix.is_empty() {
+        segment.to_string()
+    } else {
+        format!("{}.{}", prefix, segment)
+    }
+}
+
+/// 展开映射，列表和标量视为叶子
+fn collect_leaves(value: &Value, prefix: String, out: &mut Vec<(String, Value)>) {
+    match value {
+        Value::Mapping(map) if !map.is_empty() => {
+            for (key, child) in map {
+                if let Some(key) = key.as_str() {
+                    collect_leaves(child, join(&prefix, key), out);
+                }
+            }
+        }
+        other => out.push((prefix, other.clone())),
+    }
+}
+
+/// 列表中有无效元素时指出是第几个
+fn describe_type_error(context: &Value, key: &str, value: &Value, error: serde_yaml::Error) -> Vec<(String, String)> {
+    if let Value::Sequence(items) = value {
+        let item_errors: Vec<(String, String)> = items
+            .iter()
+            .enumerate()
+            .filter_map(|(index, item)| {
+                let single = nest(key, Value::Sequence(vec![item.clone()]));
+                parse(&merged(context, &single))
+                    .err()
+                    .map(|e| (join(key, &index.to_string()), e.to_string()))
+            })
+            .collect();
+        if !item_errors.is_empty() {
+            return item_errors;
+        }
+    }
+    vec![(key.to_string(), error.to_string())]
+}
+
+fn find_unknown(layer: &Value, roundtrip: &Value, merged: &Value, path: String, out: &mut Vec<String>) {
+    match (layer, roundtrip) {
+        (Value::Mapping(layer), Value::Mapping(roundtrip)) => {
+            for (key, child) in layer {
+                let Some(name) = key.as_str() else { continue };
+                let child_path = join(&path, name);
+                match roundtrip.get(key) {
+                    Some(roundtrip_child) => find_unknown(child, roundtrip_child, merged, child_path, out),
+                    // 值为默认值时序列化会省略，需要进一步确认
+                    None if !is_known_key(merged, &child_path) => out.push(child_path),
+                    None => {}
+                }
+            }
+        }
+        (Value::Sequence(layer), Value::Sequence(roundtrip)) if layer.len() == roundtrip.len() => {
+            for (index, (child, roundtrip_child)) in layer.iter().zip(roundtrip).enumerate() {
+                find_unknown(child, roundtrip_child, merged, join(&path, &index.to_string()), out);
+            }
+        }
+        // 其他情况（如匹配规则的简写形式）序列化后结构不同，不再深入
+        _ => {}
+    }
+}
+
+/// 设置点分路径对应的值，缺少的上级映射会自动创建
+fn set_path(value: &mut Value, key: &str, new_value: Value) -> Option<()> {
+    let mut current = value;
+    for segment in key.split('.') {
+        current = match current {
+            Value::Sequence(items) => items.get_mut(segment.parse::<usize>().ok()?)?,
+            Value::Mapping(map) => map
+                .entry(Value::String(segment.to_string()))
+                .or_insert(Value::Mapping(Mapping::new())),
+            _ => return None,
+        };
+    }
+    *current = new_value;
+    Some(())
+}
+
+/// 该层是否设置了 `key` 或它的上级
+fn defines(layer: &Value, key: &str) -> bool {
+    let segments: Vec<&str> = key.split('.').collect();
+    (1..=segments.len()).any(|len| lookup(layer, &segments[..len].join(".")).is_some_and(|value| !value.is_null()))
+}
+
+/// 查找点分路径对应的行号（从 1 开始）；找不到时返回最近的上级所在的行
+fn locate(text: &str, key: &str) -> Option<usize> {
+    if key.is_empty() {
+        return None;
+    }
+
+    let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+    let (mut start, mut end) = (0, lines.len());
+    let mut found = None;
+
+    for segment in key.split('.') {
+        let Some(child_indent) = (start..end).find_map(|i| indent_of(&lines[i])) else {
+            break;
+        };
+        let at_child_indent = |line: &str| indent_of(line) == Some(child_indent);
+
+        let is_list = (start..end).any(|i| at_child_indent(&lines[i]) && lines[i].trim_start().starts_with('-'));
+        let line = match segment.parse::<usize>() {
+            Ok(index) if is_list => {
+                let item = (start..end)
+                    .filter(|&i| at_child_indent(&lines[i]) && lines[i].trim_start().starts_with('-'))
+                    .nth(index);
+                // 把 `- ` 换成空格，列表项的第一个键就和其余键对齐了
+                if let Some(i) = item {
+                    lines[i] = lines[i].replacen('-', " ", 1);
+                }
+                item
+            }
+            _ => (start..end).find(|&i| at_child_indent(&lines[i]) && line_key(&lines[i]) == Some(segment)),
+        };
+        let Some(line) = line else { break };
+        found = Some(line + 1);
+
+        // 列表项的内容从同一行开始；映射的值从下一行开始，列表可以与键同一缩进
+        let is_item = lines[line].trim_start().len() < lines[line].len() - child_indent;
+        let block_end = (line + 1..end)
+            .find(|&i| {
+                indent_of(&lines[i]).is_some_and(|indent| {
+                    indent < child_indent
+                        || (indent == child_indent && (is_item || !lines[i].trim_start().starts_with('-')))
+                })
+            })
+            .unwrap_or(end);
+        (start, end) = (if is_item { line } else { line + 1 }, block_end);
+    }
+
+    found
+}
+
+/// 检查语义约束，返回 (严重程度, 配置项, 说明)
+fn semantic_issues(config: &Config) -> Vec<(Severity, String, String)> {
+    let mut issues = Vec::new();
+    let mut error = |key: String, message: String| issues.push((Severity::Error, key, message));
+
+    if config.aggregation.max_toasts == 0 {
+        error("aggregation.max_toasts".to_string(), t!("config.validate.positive"));
+    }
+    if config.aggregation.enabled && config.aggregation.window == 0 {
+        error("aggregation.window".to_string(), t!("config.validate.window_positive"));
+    }
+    if config.sound_volume > 100 {
+        error("sound_volume".to_string(), t!("config.validate.volume"));
+    }
+    if config.logging.level.parse::<log::LevelFilter>().is_err() {
+        error(
+            "logging.level".to_string(),
+            t!("config.validate.log_level", level = format!("{:?}", config.logging.level)),
+        );
+    }
+    let timezone = &config.schedule.timezone;
+    if timezone != "local" && timezone.parse::<chrono_tz::Tz>().is_err() {
+        error(
+            "schedule.timezone".to_string(),
+            t!("config.validate.timezone", timezone = format!("{:?}", timezone)),
+        );
+    }
+    if let Some(language) = &config.language {
+        let available = i18n::available_locales(Some(&i18n::get_catalog_dir()));
+        if i18n::match_locale(language, &available).is_none() {
+            issues.push((
+                Severity::Warning,
+                "language".to_string(),
+                t!("config.validate.language", language = format!("{:?}", language), available = available.join(", ")),
+            ));
+        }
+    }
+
+    let mut check_rule = |key: String, rule: &MatchRule| {
+        if let Err(e) = compile_rule(rule) {
+            let reason = e.to_string();
+            let reason = reason.lines().rev().find(|line| !line.trim().is_empty()).unwrap_or("").trim();
+            issues.push((Severity::Error, key, t!("config.validate.rule", reason = reason)));
+        }
+    };
+    for (index, rule) in config.threshold.whitelist.iter().enumerate() {
+        check_rule(format!("threshold.whitelist.{}", index), rule);
+    }
+    let mut custom: Vec<_> = config.templates.custom.iter().collect();
+    custom.sort_by_key(|(name, _)| *name);
+    for (name, template) in &custom {
+        if let Some(rule) = &template.matcher {
+            check_rule(format!("templates.{}.match", name), rule);
+        }
+    }
+    for (index, rule) in config.rules.iter().enumerate() {
+        if let Some(cmd) = &rule.when.cmd {
+            check_rule(format!("rules.{}.when.cmd", index), cmd);
+        }
+        if let Some(project) = &rule.when.project {
+            check_rule(format!("rules.{}.when.project", index), project);
+        }
+    }
+
+    let templates = std::iter::once(("default", &config.templates.default))
+        .chain(custom.iter().map(|(name, template)| (name.as_str(), *template)));
+    for (name, template) in templates {
+        for (field, source) in [("title", &template.title), ("body", &template.body)] {
+            if let Some(Err(e)) = source.as_deref().map(Template::parse) {
+                issues.push((
+                    Severity::Error,
+                    format!("templates.{}.{}", name, field),
+                    t!("config.validate.template", error = format!("{:#}", e)),
+                ));
+            }
+        }
+
+        let sounds = [("sound".to_string(), Some(template.sound.as_str()))].into_iter().chain(
+            [NotificationStatus::Success, NotificationStatus::Error, NotificationStatus::Pending]
+                .map(|status| (format!("sounds.{}", status.as_str()), template.sounds.get(status))),
+        );
+        for (field, sound) in sounds {
+            if let Some(sound) = sound
+                && !matches!(sound, "" | "default" | "none")
+                && !Path::new(sound).exists()
+            {
+                issues.push((
+                    Severity::Warning,
+                    format!("templates.{}.{}", name, field),
+                    t!("sound.file_missing", path = sound),
+                ));
+            }
+        }
+    }
+
+    let url_issue = |key: String, url: &str| {
+        (!url.starts_with("http://") && !url.starts_with("https://"))
+            .then(|| (Severity::Error, key, t!("config.validate.url", url = format!("{:?}", url))))
+    };
+    if let Some(webhook) = &config.webhook {
+        issues.extend(url_issue("webhook.url".to_string(), &webhook.url));
+    }
+    for (index, notifier) in config.notifiers.iter().enumerate() {
+        match &notifier.kind {
+            NotifierKind::Webhook(webhook) => issues.extend(url_issue(format!("notifiers.{}.url", index), &webhook.url)),
+            NotifierKind::Log { path } if path.trim().is_empty() => issues.push((
+                Severity::Error,
+                format!("notifiers.{}.path", index),
+                t!("config.validate.log_path"),
+            )),
+            _ => {}
+        }
+    }
+
+    // 与 CompositeNotificationManager::from_config 的命名一致
+    let sink_names: BTreeSet<&str> = if config.notifiers.is_empty() {
+        BTreeSet::from([if config.webhook.is_some() { "webhook" } else { "desktop" }])
+    } else {
+        config
+            .notifiers
+            .iter()
+            .map(|notifier| {
+                notifier.name.as_deref().unwrap_or(match notifier.kind {
+                    NotifierKind::Desktop => "desktop",
+                    NotifierKind::Webhook(_) => "webhook",
+                    NotifierKind::Log { .. } => "log",
+                })
+            })
+            .collect()
+    };
+    for (index, rule) in config.rules.iter().enumerate() {
+        if let Some(range) = rule.when.duration
+            && let (Some(min), Some(max)) = (range.min, range.max)
+            && min > max
+        {
+            issues.push((
+                Severity::Warning,
+                format!("rules.{}.when.duration", index),
+                t!("config.validate.duration_range", min = min, max = max),
+            ));
+        }
+        for sink in rule.sinks.iter().filter(|sink| !sink_names.contains(sink.as_str())) {
+            issues.push((
+                Severity::Warning,
+                format!("rules.{}.sinks", index),
+                t!("config.validate.sink", sink = sink),
+            ));
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn global(text: &str) -> ConfigLayer {
+        ConfigLayer::parse(ConfigSource::Global(PathBuf::from("/etc/ccn.yaml")), text.to_string()).unwrap()
+    }
+
+    fn summary(report: &ValidationReport) -> Vec<(Severity, Option<usize>, String)> {
+        report
+            .issues
+            .iter()
+            .map(|issue| (issue.severity, issue.line, issue.key.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_partial_config_is_valid() {
+        let report = validate_layers(&[global("aggregation:\n  window: 3000\n")], &[]).unwrap();
+        assert!(report.issues.is_empty(), "{:?}", report.issues);
+    }
+
+    #[test]
+    fn test_reports_every_problem_with_line() {
+        let text = "\
+sound_enabled: true
+aggregation:
+  window: soon
+  max_toasts: 0
+logging:
+  level: verbose
+templates:
+  build:
+    match:
+      pattern: '(cargo'
+      mode: regex
+    colour: red
+    title: '{cmd|shout}'
+threshold:
+  min_duration: 30
+  typo: 1
+";
+        let report = validate_layers(&[global(text)], &[]).unwrap();
+        assert_eq!(
+            summary(&report),
+            vec![
+                (Severity::Error, Some(3), "aggregation.window".to_string()),
+                (Severity::Warning, Some(12), "templates.build.colour".to_string()),
+                (Severity::Warning, Some(16), "threshold.typo".to_string()),
+                (Severity::Error, Some(4), "aggregation.max_toasts".to_string()),
+                (Severity::Error, Some(6), "logging.level".to_string()),
+                (Severity::Error, Some(9), "templates.build.match".to_string()),
+                (Severity::Error, Some(13), "templates.build.title".to_string()),
+            ]
+        );
+        assert!(report.issues[0].message.contains("soon"));
+
+        let message = report.into_result().unwrap_err().to_string();
+        assert!(message.starts_with(&t!("config.validate.errors", count = 5)));
+        assert!(message.contains("/etc/ccn.yaml:6: logging.level"));
+    }
+
+    #[test]
+    fn test_list_items_are_located() {
+        let text = "\
+notifiers:
+- type: desktop
+- type: webhook
+  url: example.com
+  retries: many
+rules:
+  - action: notify
+    sinks: [team]
+";
+        let report = validate_layers(&[global(text)], &[]).unwrap();
+        assert_eq!(
+            summary(&report),
+            vec![
+                (Severity::Error, Some(3), "notifiers.1".to_string()),
+                (Severity::Warning, Some(8), "rules.0.sinks".to_string()),
+            ]
+        );
+
+        let text = text.replace("retries: many", "retries: 2");
+        let report = validate_layers(&[global(&text)], &[]).unwrap();
+        assert_eq!(summary(&report)[0], (Severity::Error, Some(4), "notifiers.1.url".to_string()));
+    }
+
+    #[test]
+    fn test_issue_source_is_the_defining_layer() {
+        let project = ConfigLayer::parse(
+            ConfigSource::Project(PathBuf::from("/work/.ccn.yaml")),
+            "schedule:\n  timezone: Mars/Olympus\n".to_string(),
+        )
+        .unwrap();
+        let overrides = vec![ConfigOverride::from_env_var("CCN_AGGREGATION__MAX_TOASTS", "0").unwrap()];
+        let report = validate_layers(&[global("sound_enabled: true\n"), project], &overrides).unwrap();
+
+        let sources: Vec<String> = report.issues.iter().map(|issue| issue.to_string()).collect();
+        assert_eq!(sources.len(), 2);
+        assert!(sources[0].starts_with("环境变量 CCN_AGGREGATION__MAX_TOASTS: aggregation.max_toasts"));
+        assert!(sources[1].starts_with("/work/.ccn.yaml:2: schedule.timezone"));
+    }
+
+    #[test]
+    fn test_sound_settings() {
+        let text = "\
+sound_volume: 120
+templates:
+  default:
+    sound: none
+    sounds:
+      error: /nonexistent/error.wav
+";
+        let report = validate_layers(&[global(text)], &[]).unwrap();
+        assert_eq!(
+            summary(&report),
+            vec![
+                (Severity::Error, Some(1), "sound_volume".to_string()),
+                (Severity::Warning, Some(6), "templates.default.sounds.error".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_known_keys() {
+        let value = serde_yaml::to_value(Config::default()).unwrap();
+        assert!(is_known_key(&value, "webhook"));
+        assert!(is_known_key(&value, "templates.default.match"));
+        assert!(is_known_key(&value, "default_action"));
+        assert!(!is_known_key(&value, "aggregation.colour"));
+        assert!(!is_known_key(&value, "colour"));
+    }
+}
@@ -0,0 +1,367 @@
+//! 守护进程模块
+//!
+//! `ccn daemon` 常驻内存，通过 Unix domain socket 接收 `ccn notify` / `ccn run` 转发的通知，
+//! 复用已加载的配置和通知渠道，并用定时器在聚合窗口和 defer 时段结束时发送通知。
+//!
+//! 协议：每个连接发送一行 JSON 请求，守护进程处理后返回一行 JSON 响应
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::aggregator::{get_state_file_path, FlushTask};
+use crate::config::ConfigOverride;
+use crate::notification::NotificationStatus;
+
+/// 转发给守护进程的通知
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotifyRequest {
+    pub status: NotificationStatus,
+    pub cmd: String,
+    /// 耗时（秒）
+    pub duration: u64,
+    #[serde(default)]
+    pub project: Option<String>,
+    #[serde(default)]
+    pub project_path: Option<String>,
+    #[serde(default)]
+    pub event: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    pub title: String,
+    pub message: String,
+    /// 客户端的临时覆盖（环境变量和 `--set`），守护进程按请求应用
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub overrides: Vec<ConfigOverride>,
+}
+
+/// 守护进程请求
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DaemonRequest {
+    Notify(Box<NotifyRequest>),
+    /// 检查守护进程是否在运行
+    Ping,
+    /// 停止守护进程
+    Shutdown,
+}
+
+/// 守护进程响应
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DaemonResponse {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl DaemonResponse {
+    fn from_result(result: &Result<()>) -> Self {
+        match result {
+            Ok(()) => Self { ok: true, error: None },
+            Err(e) => Self {
+                ok: false,
+                error: Some(format!("{:#}", e)),
+            },
+        }
+    }
+}
+
+/// 获取守护进程 socket 路径（与聚合状态文件位于同一目录）
+pub fn get_socket_path() -> PathBuf {
+    get_state_file_path().with_file_name("ccn.sock")
+}
+
+#[cfg(unix)]
+pub use unix::*;
+
+#[cfg(unix)]
+mod unix {
+    use super::*;
+    use anyhow::{bail, Context};
+    use std::fs;
+    use std::io::{BufRead, BufReader, ErrorKind, Write};
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::Path;
+    use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+    use std::time::Duration;
+
+    /// 等待守护进程处理一条请求的最长时间（包含 webhook 重试）
+    const RESPONSE_TIMEOUT: Duration = Duration::from_secs(60);
+
+    /// 守护进程读取请求的最长时间，避免异常客户端占用连接
+    const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// 将请求转发给守护进程
+    ///
+    /// 守护进程未运行时返回 None，由调用方在进程内处理
+    pub fn forward(request: &DaemonRequest) -> Option<Result<()>> {
+        forward_to(&get_socket_path(), request)
+    }
+
+    pub(crate) fn forward_to(socket_path: &Path, request: &DaemonRequest) -> Option<Result<()>> {
+        let stream = match UnixStream::connect(socket_path) {
+            Ok(stream) => stream,
+            Err(e) => {
+                if e.kind() != ErrorKind::NotFound {
+                    log::debug!("{}", t!("daemon.connect_failed", socket = format!("{:?}", socket_path), error = e));
+                }
+                return None;
+            }
+        };
+
+        // 已连接后出错不再回退，避免守护进程已处理的通知被重复发送
+        Some(exchange(stream, request).and_then(|response| match response.ok {
+            true => Ok(()),
+            false => bail!("{}", t!("daemon.request_failed", error = response.error.unwrap_or_default())),
+        }))
+    }
+
+    fn exchange(mut stream: UnixStream, request: &DaemonRequest) -> Result<DaemonResponse> {
+        stream.set_read_timeout(Some(RESPONSE_TIMEOUT))?;
+
+        let mut line = serde_json::to_string(request)?;
+        line.push('\n');
+        stream.write_all(line.as_bytes()).with_context(|| t!("daemon.send_request"))?;
+
+        let mut response = String::new();
+        BufReader::new(stream)
+            .read_line(&mut response)
+            .with_context(|| t!("daemon.wait_response"))?;
+        serde_json::from_str(&response).with_context(|| t!("daemon.parse_response"))
+    }
+
+    /// 守护进程收到的事件
+    pub enum DaemonEvent {
+        /// 需要处理的通知，处理结果通过 `Responder` 返回给客户端
+        Notify(Box<NotifyRequest>, Responder),
+        /// 聚合窗口或 defer 时段结束，需要执行刷新任务
+        Flush(FlushTask),
+        /// 客户端请求停止
+        Shutdown(Responder),
+        /// 一段时间内没有其他事件，用于执行周期性任务
+        Tick,
+    }
+
+    /// 向客户端返回处理结果
+    pub struct Responder(Sender<DaemonResponse>);
+
+    impl Responder {
+        pub fn reply(self, result: Result<()>) {
+            let _ = self.0.send(DaemonResponse::from_result(&result));
+        }
+    }
+
+    /// 守护进程：监听 socket，将请求转换为事件交给主线程依次处理
+    ///
+    /// 通知渠道不要求 `Send`，所以配置和通知渠道都只在主线程使用
+    pub struct Daemon {
+        socket_path: PathBuf,
+        sender: Sender<DaemonEvent>,
+        receiver: Receiver<DaemonEvent>,
+    }
+
+    impl Daemon {
+        /// 绑定 socket 并开始接受连接
+        ///
+        /// 已有守护进程在运行时返回错误；上次异常退出遗留的 socket 文件会被清理
+        pub fn start(socket_path: &Path) -> Result<Self> {
+            if socket_path.exists() {
+                if UnixStream::connect(socket_path).is_ok() {
+                    bail!("{}", t!("daemon.already_running", socket = format!("{:?}", socket_path)));
+                }
+                fs::remove_file(socket_path)
+                    .with_context(|| t!("daemon.remove_socket", socket = format!("{:?}", socket_path)))?;
+            }
+            if let Some(parent) = socket_path.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| t!("state.create_dir", path = format!("{:?}", parent)))?;
+            }
+
+            let listener = UnixListener::bind(socket_path)
+                .with_context(|| t!("daemon.bind", socket = format!("{:?}", socket_path)))?;
+            let (sender, receiver) = mpsc::channel();
+
+            let accept_sender = sender.clone();
+            std::thread::spawn(move || {
+                for stream in listener.incoming() {
+                    match stream {
+                        Ok(stream) => {
+                            let sender = accept_sender.clone();
+                            std::thread::spawn(move || handle_connection(stream, sender));
+                        }
+                        Err(e) => log::warn!("{}", t!("daemon.accept_failed", error = e)),
+                    }
+                }
+            });
+
+            Ok(Self {
+                socket_path: socket_path.to_path_buf(),
+                sender,
+                receiver,
+            })
+        }
+
+        /// 等待下一个事件，`timeout` 内没有事件时返回 `Tick`
+        pub fn next_event(&self, timeout: Duration) -> DaemonEvent {
+            match self.receiver.recv_timeout(timeout) {
+                Ok(event) => event,
+                // 自身持有 sender，不会断开
+                Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => DaemonEvent::Tick,
+            }
+        }
+
+        /// 返回刷新定时器：调用 `(task, delay_ms)` 后在 `delay_ms` 毫秒后产生 `Flush(task)` 事件
+        pub fn flush_scheduler(&self) -> impl Fn(FlushTask, u64) + 'static {
+            let sender = self.sender.clone();
+            move |task, delay_ms| {
+                let sender = sender.clone();
+                std::thread::spawn(move || {
+                    std::thread::sleep(Duration::from_millis(delay_ms));
+                    let _ = sender.send(DaemonEvent::Flush(task));
+                });
+            }
+        }
+    }
+
+    impl Drop for Daemon {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.socket_path);
+        }
+    }
+
+    /// 读取一条请求并等待主线程的处理结果
+    fn handle_connection(stream: UnixStream, sender: Sender<DaemonEvent>) {
+        let _ = stream.set_read_timeout(Some(REQUEST_TIMEOUT));
+        let mut writer = match stream.try_clone() {
+            Ok(writer) => writer,
+            Err(e) => {
+                log::warn!("{}", t!("daemon.connection_failed", error = e));
+                return;
+            }
+        };
+
+        let mut line = String::new();
+        let response = match BufReader::new(stream).read_line(&mut line) {
+            Ok(_) => match serde_json::from_str::<DaemonRequest>(&line) {
+                Ok(DaemonRequest::Ping) => DaemonResponse { ok: true, error: None },
+                Ok(request) => {
+                    let (reply_sender, reply_receiver) = mpsc::channel();
+                    let responder = Responder(reply_sender);
+                    let event = match request {
+                        DaemonRequest::Notify(notify) => DaemonEvent::Notify(notify, responder),
+                        _ => DaemonEvent::Shutdown(responder),
+                    };
+                    let _ = sender.send(event);
+                    reply_receiver.recv().unwrap_or_else(|_| DaemonResponse {
+                        ok: false,
+                        error: Some(t!("daemon.no_reply")),
+                    })
+                }
+                Err(e) => DaemonResponse {
+                    ok: false,
+                    error: Some(t!("daemon.parse_request", error = e)),
+                },
+            },
+            Err(e) => DaemonResponse {
+                ok: false,
+                error: Some(t!("daemon.read_request", error = e)),
+            },
+        };
+
+        if let Ok(mut line) = serde_json::to_string(&response) {
+            line.push('\n');
+            let _ = writer.write_all(line.as_bytes());
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn temp_socket(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ccn-test-daemon-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir.join("ccn.sock")
+    }
+
+    fn request() -> NotifyRequest {
+        NotifyRequest {
+            status: NotificationStatus::Error,
+            cmd: "cargo build".to_string(),
+            duration: 42,
+            project: Some("demo".to_string()),
+            project_path: Some("/work/demo".to_string()),
+            event: None,
+            session_id: None,
+            exit_code: Some(1),
+            title: "任务失败".to_string(),
+            message: "cargo build (耗时: 42秒)".to_string(),
+            overrides: vec![ConfigOverride::parse_set("sound_enabled=false").unwrap()],
+        }
+    }
+
+    #[test]
+    fn test_forward_without_daemon() {
+        let socket = temp_socket("absent");
+        assert!(forward_to(&socket, &DaemonRequest::Ping).is_none());
+    }
+
+    #[test]
+    fn test_forward_to_daemon() {
+        let socket = temp_socket("forward");
+        let daemon = Daemon::start(&socket).unwrap();
+
+        // 同一路径不能启动第二个守护进程
+        assert!(Daemon::start(&socket).is_err());
+        assert!(forward_to(&socket, &DaemonRequest::Ping).unwrap().is_ok());
+
+        let client_socket = socket.clone();
+        let client = std::thread::spawn(move || {
+            let ok = forward_to(&client_socket, &DaemonRequest::Notify(Box::new(request()))).unwrap();
+            let failed = forward_to(&client_socket, &DaemonRequest::Notify(Box::new(request()))).unwrap();
+            (ok, failed)
+        });
+
+        for reply_ok in [true, false] {
+            match daemon.next_event(Duration::from_secs(5)) {
+                DaemonEvent::Notify(received, responder) => {
+                    assert_eq!(*received, request());
+                    responder.reply(if reply_ok { Ok(()) } else { Err(anyhow::anyhow!("渠道不可用")) });
+                }
+                _ => panic!("应收到通知事件"),
+            }
+        }
+
+        let (ok, failed) = client.join().unwrap();
+        assert!(ok.is_ok());
+        assert!(format!("{:#}", failed.unwrap_err()).contains("渠道不可用"));
+
+        daemon.flush_scheduler()(FlushTask::Aggregation(1234), 10);
+        assert!(matches!(daemon.next_event(Duration::from_secs(5)), DaemonEvent::Flush(FlushTask::Aggregation(1234))));
+        assert!(matches!(daemon.next_event(Duration::from_millis(10)), DaemonEvent::Tick));
+
+        // 退出时清理 socket，之后的请求回退到进程内处理
+        drop(daemon);
+        assert!(!socket.exists());
+        assert!(forward_to(&socket, &DaemonRequest::Ping).is_none());
+
+        let _ = std::fs::remove_dir_all(socket.parent().unwrap());
+    }
+
+    #[test]
+    fn test_stale_socket_is_replaced() {
+        let socket = temp_socket("stale");
+        std::fs::create_dir_all(socket.parent().unwrap()).unwrap();
+        drop(std::os::unix::net::UnixListener::bind(&socket).unwrap());
+        assert!(socket.exists());
+
+        let _daemon = Daemon::start(&socket).unwrap();
+        assert!(forward_to(&socket, &DaemonRequest::Ping).unwrap().is_ok());
+
+        let _ = std::fs::remove_dir_all(socket.parent().unwrap());
+    }
+}
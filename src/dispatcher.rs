@@ -0,0 +1,459 @@
+//! 通知分发模块
+//!
+//! 将一条通知按 `notifiers` 配置同时发送到多个通知渠道（桌面、webhook、日志文件），
+//! 每个渠道有独立的过滤条件，单个渠道失败不影响其他渠道
+
+use anyhow::{Context, Result};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::config::{Config, NotifierConfig, NotifierKind};
+use crate::notification::{
+    get_notification_manager, NotificationContext, NotificationManager, NotificationStatus,
+};
+use crate::webhook::WebhookNotificationManager;
+
+/// 渠道过滤条件
+#[derive(Debug, Clone, Default)]
+pub struct NotifierFilter {
+    /// 允许的状态，为空表示全部
+    pub statuses: Vec<NotificationStatus>,
+    /// 最小耗时（秒）
+    pub min_duration: Option<u64>,
+}
+
+impl NotifierFilter {
+    /// 检查通知是否应发送到该渠道
+    pub fn accepts(&self, status: NotificationStatus, duration_sec: u64) -> bool {
+        if !self.statuses.is_empty() && !self.statuses.contains(&status) {
+            return false;
+        }
+
+        // 耗时为 0 表示未知（如 hooks 场景），不参与过滤
+        match self.min_duration {
+            Some(min) if duration_sec > 0 => duration_sec >= min,
+            _ => true,
+        }
+    }
+}
+
+/// 单个通知渠道
+struct Sink {
+    name: String,
+    manager: Box<dyn NotificationManager>,
+    filter: NotifierFilter,
+}
+
+/// 组合通知管理器
+pub struct CompositeNotificationManager {
+    sinks: Vec<Sink>,
+}
+
+impl CompositeNotificationManager {
+    pub fn new() -> Self {
+        Self { sinks: Vec::new() }
+    }
+
+    /// 添加通知渠道
+    pub fn add_sink(
+        &mut self,
+        name: &str,
+        manager: Box<dyn NotificationManager>,
+        filter: NotifierFilter,
+    ) {
+        self.sinks.push(Sink {
+            name: name.to_string(),
+            manager,
+            filter,
+        });
+    }
+
+    /// 根据配置创建
+    ///
+    /// 未配置 `notifiers` 时沿用旧行为：配置了 `webhook` 则只用 webhook，否则使用桌面通知
+    pub fn from_config(config: &Config) -> Self {
+        let mut composite = Self::new();
+
+        if config.notifiers.is_empty() {
+            match &config.webhook {
+                Some(webhook) => composite.add_sink(
+                    "webhook",
+                    Box::new(WebhookNotificationManager::new(webhook.clone())),
+                    NotifierFilter::default(),
+                ),
+                None => composite.add_sink("desktop", get_notification_manager(), NotifierFilter::default()),
+            }
+            return composite;
+        }
+
+        for notifier in &config.notifiers {
+            let (kind_name, manager) = create_notifier(notifier);
+            let filter = NotifierFilter {
+                statuses: notifier.statuses.clone(),
+                min_duration: notifier.min_duration,
+            };
+            composite.add_sink(notifier.name.as_deref().unwrap_or(kind_name), manager, filter);
+        }
+
+        composite
+    }
+
+    /// 只发送到指定名称的通知渠道，`sinks` 为空表示全部渠道
+    ///
+    /// 不消耗自身，适合常驻内存的通知渠道（`ccn daemon`）
+    pub fn send_routed(
+        &self,
+        sinks: &[String],
+        status: NotificationStatus,
+        title: &str,
+        message: &str,
+        duration_ms: u64,
+        context: &NotificationContext,
+    ) -> Result<()> {
+        let selected: Vec<&Sink> = self
+            .sinks
+            .iter()
+            .filter(|sink| sinks.is_empty() || sinks.contains(&sink.name))
+            .collect();
+        if selected.is_empty() && !sinks.is_empty() {
+            log::warn!("{}", t!("notification.sink.none_found", sinks = format!("{:?}", sinks)));
+        }
+
+        let mut attempted = 0;
+        let mut errors = Vec::new();
+
+        for sink in selected {
+            if !sink.filter.accepts(status, context.duration_sec) {
+                log::debug!("{}", t!("notification.sink.filtered", sink = sink.name));
+                continue;
+            }
+
+            attempted += 1;
+            if let Err(e) = sink
+                .manager
+                .send_with_context(status, title, message, duration_ms, context)
+            {
+                // 单个渠道失败不影响其他渠道
+                log::warn!("{}", t!("notification.sink.failed", sink = sink.name, error = format!("{:#}", e)));
+                errors.push(format!("{}: {:#}", sink.name, e));
+            }
+        }
+
+        if attempted > 0 && errors.len() == attempted {
+            anyhow::bail!("{}", t!("notification.sink.all_failed", errors = errors.join("; ")));
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for CompositeNotificationManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 根据渠道配置创建通知管理器
+fn create_notifier(notifier: &NotifierConfig) -> (&'static str, Box<dyn NotificationManager>) {
+    match &notifier.kind {
+        NotifierKind::Desktop => ("desktop", get_notification_manager()),
+        NotifierKind::Webhook(webhook) => (
+            "webhook",
+            Box::new(WebhookNotificationManager::new(webhook.clone())),
+        ),
+        NotifierKind::Log { path } => (
+            "log",
+            Box::new(LogFileNotificationManager::new(expand_home(path))),
+        ),
+    }
+}
+
+impl NotificationManager for CompositeNotificationManager {
+    fn send_notification(
+        &self,
+        status: NotificationStatus,
+        title: &str,
+        message: &str,
+        duration_ms: u64,
+    ) -> Result<()> {
+        self.send_with_context(status, title, message, duration_ms, &NotificationContext::default())
+    }
+
+    fn send_with_context(
+        &self,
+        status: NotificationStatus,
+        title: &str,
+        message: &str,
+        duration_ms: u64,
+        context: &NotificationContext,
+    ) -> Result<()> {
+        self.send_routed(&[], status, title, message, duration_ms, context)
+    }
+
+    fn is_available(&self) -> bool {
+        self.sinks.iter().any(|sink| sink.manager.is_available())
+    }
+}
+
+/// 日志文件通知管理器（每条通知追加一行）
+pub struct LogFileNotificationManager {
+    path: PathBuf,
+}
+
+impl LogFileNotificationManager {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+impl NotificationManager for LogFileNotificationManager {
+    fn send_notification(
+        &self,
+        status: NotificationStatus,
+        title: &str,
+        message: &str,
+        duration_ms: u64,
+    ) -> Result<()> {
+        self.send_with_context(status, title, message, duration_ms, &NotificationContext::default())
+    }
+
+    fn send_with_context(
+        &self,
+        status: NotificationStatus,
+        title: &str,
+        message: &str,
+        _duration_ms: u64,
+        context: &NotificationContext,
+    ) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| t!("notification.log_file.create_dir", path = format!("{:?}", parent)))?;
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| t!("notification.log_file.open", path = format!("{:?}", self.path)))?;
+
+        let line = format!(
+            "{} [{}] {} - {} (cmd={}, duration={}s)\n",
+            chrono::Local::now().to_rfc3339(),
+            status.as_str(),
+            title,
+            message.replace('\n', " "),
+            context.cmd,
+            context.duration_sec,
+        );
+
+        file.write_all(line.as_bytes())
+            .with_context(|| t!("notification.log_file.write", path = format!("{:?}", self.path)))?;
+        Ok(())
+    }
+
+    fn is_available(&self) -> bool {
+        true
+    }
+}
+
+/// 展开路径开头的 `~/`
+fn expand_home(path: &str) -> PathBuf {
+    #[cfg(windows)]
+    let home = std::env::var("USERPROFILE").ok();
+
+    #[cfg(not(windows))]
+    let home = std::env::var("HOME").ok();
+
+    match (path.strip_prefix("~/"), home) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// 记录收到的通知，可配置为总是失败
+    struct RecordingNotifier {
+        received: Arc<Mutex<Vec<String>>>,
+        fail: bool,
+    }
+
+    impl NotificationManager for RecordingNotifier {
+        fn send_notification(
+            &self,
+            _status: NotificationStatus,
+            title: &str,
+            _message: &str,
+            _duration_ms: u64,
+        ) -> Result<()> {
+            if self.fail {
+                anyhow::bail!("模拟失败");
+            }
+            self.received.lock().unwrap().push(title.to_string());
+            Ok(())
+        }
+
+        fn is_available(&self) -> bool {
+            true
+        }
+    }
+
+    fn recording(fail: bool) -> (Box<dyn NotificationManager>, Arc<Mutex<Vec<String>>>) {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let notifier = RecordingNotifier {
+            received: Arc::clone(&received),
+            fail,
+        };
+        (Box::new(notifier), received)
+    }
+
+    fn context(duration_sec: u64) -> NotificationContext {
+        NotificationContext {
+            cmd: "cargo build".to_string(),
+            duration_sec,
+        }
+    }
+
+    #[test]
+    fn test_filter_accepts() {
+        let filter = NotifierFilter {
+            statuses: vec![NotificationStatus::Error],
+            min_duration: Some(30),
+        };
+
+        assert!(filter.accepts(NotificationStatus::Error, 60));
+        assert!(filter.accepts(NotificationStatus::Error, 0));
+        assert!(!filter.accepts(NotificationStatus::Error, 10));
+        assert!(!filter.accepts(NotificationStatus::Success, 60));
+        assert!(NotifierFilter::default().accepts(NotificationStatus::Pending, 1));
+    }
+
+    #[test]
+    fn test_routes_by_status() {
+        let (desktop, desktop_received) = recording(false);
+        let (webhook, webhook_received) = recording(false);
+
+        let mut composite = CompositeNotificationManager::new();
+        composite.add_sink("desktop", desktop, NotifierFilter::default());
+        composite.add_sink(
+            "webhook",
+            webhook,
+            NotifierFilter {
+                statuses: vec![NotificationStatus::Error],
+                min_duration: None,
+            },
+        );
+
+        composite
+            .send_with_context(NotificationStatus::Success, "任务完成", "", 5000, &context(20))
+            .unwrap();
+        composite
+            .send_with_context(NotificationStatus::Error, "任务失败", "", 5000, &context(20))
+            .unwrap();
+
+        assert_eq!(*desktop_received.lock().unwrap(), vec!["任务完成", "任务失败"]);
+        assert_eq!(*webhook_received.lock().unwrap(), vec!["任务失败"]);
+    }
+
+    #[test]
+    fn test_failing_sink_does_not_block_others() {
+        let (failing, _) = recording(true);
+        let (working, received) = recording(false);
+
+        let mut composite = CompositeNotificationManager::new();
+        composite.add_sink("failing", failing, NotifierFilter::default());
+        composite.add_sink("working", working, NotifierFilter::default());
+
+        assert!(composite
+            .send_with_context(NotificationStatus::Error, "任务失败", "", 5000, &context(1))
+            .is_ok());
+        assert_eq!(received.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_all_sinks_failing_is_error() {
+        let (failing, _) = recording(true);
+
+        let mut composite = CompositeNotificationManager::new();
+        composite.add_sink("failing", failing, NotifierFilter::default());
+
+        assert!(composite
+            .send_notification(NotificationStatus::Error, "任务失败", "", 5000)
+            .is_err());
+
+        // 没有匹配的渠道不算失败
+        let empty = CompositeNotificationManager::new();
+        assert!(empty
+            .send_notification(NotificationStatus::Error, "任务失败", "", 5000)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_route_to_named_sinks() {
+        let (desktop, desktop_received) = recording(false);
+        let (webhook, webhook_received) = recording(false);
+
+        let mut composite = CompositeNotificationManager::new();
+        composite.add_sink("desktop", desktop, NotifierFilter::default());
+        composite.add_sink("team-webhook", webhook, NotifierFilter::default());
+
+        // send_routed 不消耗渠道，可反复使用
+        let sinks = ["team-webhook".to_string()];
+        for _ in 0..2 {
+            composite
+                .send_routed(&sinks, NotificationStatus::Success, "任务完成", "", 5000, &context(0))
+                .unwrap();
+        }
+        assert!(desktop_received.lock().unwrap().is_empty());
+        assert_eq!(webhook_received.lock().unwrap().len(), 2);
+
+        // 为空表示全部渠道
+        composite
+            .send_routed(&[], NotificationStatus::Success, "任务完成", "", 5000, &context(0))
+            .unwrap();
+        assert_eq!(desktop_received.lock().unwrap().len(), 1);
+        assert_eq!(webhook_received.lock().unwrap().len(), 3);
+
+        // 指定的渠道都不存在时不发送，也不算失败
+        composite
+            .send_routed(&["missing".to_string()], NotificationStatus::Success, "任务完成", "", 5000, &context(0))
+            .unwrap();
+        assert_eq!(desktop_received.lock().unwrap().len(), 1);
+        assert_eq!(webhook_received.lock().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_log_file_sink() {
+        let path = std::env::temp_dir()
+            .join(format!("ccn-test-notify-{}", std::process::id()))
+            .join("notifications.log");
+        let notifier = LogFileNotificationManager::new(path.clone());
+
+        notifier
+            .send_with_context(NotificationStatus::Error, "任务失败", "第一行\n第二行", 5000, &context(12))
+            .unwrap();
+        notifier
+            .send_notification(NotificationStatus::Success, "任务完成", "npm test", 5000)
+            .unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("[error] 任务失败 - 第一行 第二行 (cmd=cargo build, duration=12s)"));
+        assert!(lines[1].contains("[success] 任务完成 - npm test"));
+
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_expand_home() {
+        assert_eq!(expand_home("/var/log/ccn.log"), PathBuf::from("/var/log/ccn.log"));
+        if let Ok(home) = std::env::var(if cfg!(windows) { "USERPROFILE" } else { "HOME" }) {
+            assert_eq!(expand_home("~/ccn.log"), PathBuf::from(home).join("ccn.log"));
+        }
+    }
+}
@@ -0,0 +1,212 @@
+//! 时长模块
+//!
+//! 配置中的时长可以写整数（单位沿用各配置项原来的秒或毫秒），也可以写带单位的字符串，
+//! 如 `90s`、`5m`、`1h30m`、`500ms`；显示时按当前语言格式化，如 `1h 2m 5s`、`1小时2分5秒`
+
+use anyhow::{bail, Context, Result};
+use serde::de::{self, Deserializer, Unexpected, Visitor};
+use serde::{Deserialize, Serializer};
+use std::fmt;
+use std::time::Duration;
+
+/// 支持的单位及对应的毫秒数，`ms` 必须排在 `m` 之前
+const UNITS: &[(&str, u64)] = &[("ms", 1), ("h", 3_600_000), ("m", 60_000), ("s", 1000)];
+
+/// 解析带单位的时长，如 `90s`、`5m`、`1h30m`、`500ms`，各段之间可以有空格
+pub fn parse_duration(text: &str) -> Result<Duration> {
+    let mut rest = text.trim();
+    if rest.is_empty() {
+        bail!("{}", t!("duration.empty"));
+    }
+
+    let mut millis: u64 = 0;
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        if digits == 0 {
+            bail!("{}", t!("duration.invalid", text = format!("{:?}", text)));
+        }
+        let value: u64 = rest[..digits].parse().with_context(|| t!("duration.too_large", text = format!("{:?}", text)))?;
+        rest = &rest[digits..];
+
+        let Some(&(unit, unit_millis)) = UNITS.iter().find(|(unit, _)| rest.starts_with(unit)) else {
+            bail!("{}", t!("duration.invalid_unit", text = format!("{:?}", text)));
+        };
+        millis = value
+            .checked_mul(unit_millis)
+            .and_then(|part| millis.checked_add(part))
+            .with_context(|| t!("duration.too_large", text = format!("{:?}", text)))?;
+        rest = rest[unit.len()..].trim_start();
+    }
+    Ok(Duration::from_millis(millis))
+}
+
+/// 按当前语言格式化时长，省略为 0 的单位，如 `1h 2m 5s`
+pub fn format_duration(duration: Duration) -> String {
+    let millis = duration.as_millis() as u64;
+    let parts: Vec<String> = [
+        ("duration.hours", millis / 3_600_000),
+        ("duration.minutes", millis / 60_000 % 60),
+        ("duration.seconds", millis / 1000 % 60),
+        ("duration.millis", millis % 1000),
+    ]
+    .into_iter()
+    .filter(|(_, n)| *n > 0)
+    .map(|(key, n)| t!(key, n = n))
+    .collect();
+
+    if parts.is_empty() {
+        return t!("duration.seconds", n = 0);
+    }
+    parts.join(&t!("duration.separator"))
+}
+
+/// 按当前语言格式化秒数
+pub fn format_secs(seconds: u64) -> String {
+    format_duration(Duration::from_secs(seconds))
+}
+
+/// 按当前语言格式化毫秒数
+pub fn format_millis(millis: u64) -> String {
+    format_duration(Duration::from_millis(millis))
+}
+
+/// 将配置值转换为 `unit_millis` 毫秒为单位的整数：纯数字按该单位解析，否则按 [`parse_duration`] 解析
+fn parse_in_unit(text: &str, unit_millis: u64) -> Result<u64> {
+    if let Ok(value) = text.trim().parse::<u64>() {
+        return Ok(value);
+    }
+    let millis = parse_duration(text)?.as_millis() as u64;
+    if !millis.is_multiple_of(unit_millis) {
+        bail!("{}", t!("duration.not_whole_seconds", text = format!("{:?}", text)));
+    }
+    Ok(millis / unit_millis)
+}
+
+struct DurationVisitor {
+    unit_millis: u64,
+}
+
+impl Visitor<'_> for DurationVisitor {
+    type Value = u64;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let key = if self.unit_millis == 1 { "duration.expecting_millis" } else { "duration.expecting_secs" };
+        f.write_str(&t!(key))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> std::result::Result<u64, E> {
+        Ok(value)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> std::result::Result<u64, E> {
+        u64::try_from(value).map_err(|_| E::invalid_value(Unexpected::Signed(value), &self))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> std::result::Result<u64, E> {
+        parse_in_unit(value, self.unit_millis).map_err(|e| E::custom(format!("{:#}", e)))
+    }
+}
+
+fn deserialize_in_unit<'de, D: Deserializer<'de>>(deserializer: D, unit_millis: u64) -> std::result::Result<u64, D::Error> {
+    deserializer.deserialize_any(DurationVisitor { unit_millis })
+}
+
+/// 以秒为单位的配置项，用于 `#[serde(with = "crate::duration::secs")]`
+pub mod secs {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &u64, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_u64(*value)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<u64, D::Error> {
+        deserialize_in_unit(deserializer, 1000)
+    }
+}
+
+/// 以毫秒为单位的配置项
+pub mod millis {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &u64, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_u64(*value)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<u64, D::Error> {
+        deserialize_in_unit(deserializer, 1)
+    }
+}
+
+/// 可省略的以秒为单位的配置项
+pub mod option_secs {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Secs(#[serde(with = "super::secs")] u64);
+
+    pub fn serialize<S: Serializer>(value: &Option<u64>, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => serializer.serialize_some(value),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Option<u64>, D::Error> {
+        Ok(Option::<Secs>::deserialize(deserializer)?.map(|Secs(value)| value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90s").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("5m").unwrap(), Duration::from_secs(300));
+        assert_eq!(parse_duration("1h30m").unwrap(), Duration::from_secs(5400));
+        assert_eq!(parse_duration("1h 2m 5s").unwrap(), Duration::from_secs(3725));
+        assert_eq!(parse_duration("500ms").unwrap(), Duration::from_millis(500));
+        assert_eq!(parse_duration("2m500ms").unwrap(), Duration::from_millis(120_500));
+
+        for text in ["", "soon", "90", "5 minutes", "h", "1.5h", "99999999999999999999s"] {
+            assert!(parse_duration(text).is_err(), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_secs(0), "0秒");
+        assert_eq!(format_secs(45), "45秒");
+        assert_eq!(format_secs(60), "1分");
+        assert_eq!(format_secs(3725), "1小时2分5秒");
+        assert_eq!(format_millis(1500), "1秒500毫秒");
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Fields {
+        #[serde(with = "secs")]
+        secs: u64,
+        #[serde(with = "millis")]
+        millis: u64,
+        #[serde(default, with = "option_secs")]
+        optional: Option<u64>,
+    }
+
+    #[test]
+    fn test_config_fields_accept_integers_and_units() {
+        let fields: Fields = serde_yaml::from_str("secs: 10\nmillis: 5000\n").unwrap();
+        assert_eq!((fields.secs, fields.millis, fields.optional), (10, 5000, None));
+
+        let fields: Fields = serde_yaml::from_str("secs: 1h30m\nmillis: 5s\noptional: 2m\n").unwrap();
+        assert_eq!((fields.secs, fields.millis, fields.optional), (5400, 5000, Some(120)));
+
+        let fields: Fields = serde_yaml::from_str("secs: '30'\nmillis: 500ms\n").unwrap();
+        assert_eq!((fields.secs, fields.millis), (30, 500));
+
+        let error = serde_yaml::from_str::<Fields>("secs: 500ms\nmillis: 1\n").unwrap_err();
+        assert!(error.to_string().contains(&t!("duration.not_whole_seconds", text = "\"500ms\"")), "{}", error);
+        assert!(serde_yaml::from_str::<Fields>("secs: -1\nmillis: 1\n").is_err());
+        assert!(serde_yaml::from_str::<Fields>("secs: soon\nmillis: 1\n").is_err());
+    }
+}
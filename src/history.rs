@@ -0,0 +1,204 @@
+//! 通知历史模块
+//!
+//! 将每个经过 `ccn notify` / `ccn run` 处理的事件追加到 JSONL 文件，供 `ccn history` 查询
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::aggregator::get_state_file_path;
+use crate::notification::NotificationStatus;
+
+/// 一条历史记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub timestamp: DateTime<Local>,
+    pub status: NotificationStatus,
+    pub cmd: String,
+    /// 耗时（秒）
+    pub duration: u64,
+    #[serde(default)]
+    pub project: Option<String>,
+    /// 是否被策略过滤（未发送）
+    pub suppressed: bool,
+}
+
+/// 历史查询条件
+#[derive(Debug, Clone, Default)]
+pub struct HistoryFilter {
+    pub status: Option<NotificationStatus>,
+    /// 起始日期（含）
+    pub since: Option<NaiveDate>,
+    /// 截止日期（含）
+    pub until: Option<NaiveDate>,
+    pub project: Option<String>,
+}
+
+impl HistoryFilter {
+    /// 检查记录是否满足条件
+    pub fn matches(&self, entry: &HistoryEntry) -> bool {
+        let date = entry.timestamp.date_naive();
+
+        self.status.is_none_or(|status| entry.status == status)
+            && self.since.is_none_or(|since| date >= since)
+            && self.until.is_none_or(|until| date <= until)
+            && self
+                .project
+                .as_deref()
+                .is_none_or(|project| entry.project.as_deref() == Some(project))
+    }
+}
+
+/// 通知历史存储
+pub struct NotificationHistory {
+    path: PathBuf,
+}
+
+impl NotificationHistory {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// 追加一条记录
+    pub fn append(&self, entry: &HistoryEntry) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| t!("history.create_dir", path = format!("{:?}", parent)))?;
+        }
+
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+
+        // 单次 write 追加整行，避免并发写入时行被拆开
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| t!("history.open", path = format!("{:?}", self.path)))?;
+        file.write_all(line.as_bytes())
+            .with_context(|| t!("history.write", path = format!("{:?}", self.path)))?;
+
+        Ok(())
+    }
+
+    /// 读取全部记录（按时间顺序），跳过无法解析的行
+    pub fn load(&self) -> Result<Vec<HistoryEntry>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read_to_string(&self.path)
+            .with_context(|| t!("history.read", path = format!("{:?}", self.path)))?;
+
+        let entries = content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .filter_map(|(index, line)| match serde_json::from_str(line) {
+                Ok(entry) => Some(entry),
+                Err(e) => {
+                    log::warn!("{}", t!("history.skip_line", line = index + 1, error = e));
+                    None
+                }
+            })
+            .collect();
+
+        Ok(entries)
+    }
+
+    /// 按条件查询
+    pub fn query(&self, filter: &HistoryFilter) -> Result<Vec<HistoryEntry>> {
+        Ok(self
+            .load()?
+            .into_iter()
+            .filter(|entry| filter.matches(entry))
+            .collect())
+    }
+}
+
+/// 获取历史文件路径（与聚合状态文件位于同一目录）
+pub fn get_history_file_path() -> PathBuf {
+    get_state_file_path().with_file_name("history.jsonl")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn entry(day: u32, status: NotificationStatus, project: &str, suppressed: bool) -> HistoryEntry {
+        HistoryEntry {
+            timestamp: Local.with_ymd_and_hms(2026, 1, day, 10, 0, 0).unwrap(),
+            status,
+            cmd: "cargo build".to_string(),
+            duration: 12,
+            project: Some(project.to_string()),
+            suppressed,
+        }
+    }
+
+    #[test]
+    fn test_append_and_load() {
+        let path = std::env::temp_dir().join(format!("ccn-test-history-{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let history = NotificationHistory::new(path.clone());
+
+        assert!(history.load().unwrap().is_empty());
+
+        history.append(&entry(1, NotificationStatus::Success, "demo", false)).unwrap();
+        history.append(&entry(2, NotificationStatus::Error, "demo", true)).unwrap();
+
+        // 损坏的行被跳过
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        writeln!(file, "{{not json").unwrap();
+        history.append(&entry(3, NotificationStatus::Pending, "other", false)).unwrap();
+
+        let entries = history.load().unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].status, NotificationStatus::Success);
+        assert!(entries[1].suppressed);
+        assert_eq!(entries[2].project.as_deref(), Some("other"));
+
+        let errors = history
+            .query(&HistoryFilter {
+                status: Some(NotificationStatus::Error),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(errors.len(), 1);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_filter_matches() {
+        let filter = HistoryFilter {
+            status: None,
+            since: NaiveDate::from_ymd_opt(2026, 1, 2),
+            until: NaiveDate::from_ymd_opt(2026, 1, 3),
+            project: Some("demo".to_string()),
+        };
+
+        assert!(!filter.matches(&entry(1, NotificationStatus::Success, "demo", false)));
+        assert!(filter.matches(&entry(2, NotificationStatus::Success, "demo", false)));
+        assert!(filter.matches(&entry(3, NotificationStatus::Error, "demo", true)));
+        assert!(!filter.matches(&entry(3, NotificationStatus::Error, "other", false)));
+        assert!(!filter.matches(&entry(4, NotificationStatus::Success, "demo", false)));
+
+        assert!(HistoryFilter::default().matches(&entry(1, NotificationStatus::Pending, "x", false)));
+    }
+
+    #[test]
+    fn test_entry_json_format() {
+        let json = serde_json::to_value(entry(1, NotificationStatus::Error, "demo", true)).unwrap();
+        assert_eq!(json["status"], "error");
+        assert_eq!(json["cmd"], "cargo build");
+        assert_eq!(json["duration"], 12);
+        assert_eq!(json["project"], "demo");
+        assert_eq!(json["suppressed"], true);
+        assert!(json["timestamp"].as_str().unwrap().starts_with("2026-01-01T10:00:00"));
+    }
+}
@@ -0,0 +1,363 @@
+//! Claude Code hook 输入模块
+//!
+//! 解析 Claude Code 通过 stdin 传给 hooks 的 JSON 数据，并转换为通知内容
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use std::io::Read;
+use std::path::Path;
+
+use crate::notification::NotificationStatus;
+
+/// 工具参数摘要的最大长度（字符）
+const MAX_DETAIL_CHARS: usize = 80;
+
+/// Claude Code hook 的 stdin 数据（所有事件共有的字段）
+#[derive(Debug, Clone, Deserialize)]
+#[allow(dead_code)]
+pub struct HookInput {
+    #[serde(default)]
+    pub session_id: Option<String>,
+    #[serde(default)]
+    pub transcript_path: Option<String>,
+    #[serde(default)]
+    pub cwd: Option<String>,
+    #[serde(default)]
+    pub permission_mode: Option<String>,
+    #[serde(flatten)]
+    pub event: HookEvent,
+}
+
+/// 按 `hook_event_name` 区分的事件数据
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "hook_event_name")]
+#[allow(dead_code)]
+pub enum HookEvent {
+    Notification {
+        message: String,
+        #[serde(default)]
+        title: Option<String>,
+        #[serde(default)]
+        notification_type: Option<String>,
+    },
+    PermissionRequest {
+        tool_name: String,
+        #[serde(default)]
+        tool_input: Value,
+    },
+    PreToolUse {
+        tool_name: String,
+        #[serde(default)]
+        tool_input: Value,
+    },
+    PostToolUse {
+        tool_name: String,
+        #[serde(default)]
+        tool_input: Value,
+        #[serde(default)]
+        tool_response: Value,
+    },
+    Stop {
+        #[serde(default)]
+        stop_hook_active: bool,
+    },
+    SubagentStop {
+        #[serde(default)]
+        stop_hook_active: bool,
+    },
+    /// 用户提交提示词（本轮对话开始）
+    UserPromptSubmit {
+        #[serde(default)]
+        prompt: String,
+    },
+    /// 会话开始
+    SessionStart {
+        #[serde(default)]
+        source: Option<String>,
+    },
+    /// 其他暂不关心的事件
+    #[serde(other)]
+    Other,
+}
+
+impl HookInput {
+    /// 从 JSON 字符串解析
+    pub fn from_json(content: &str) -> Result<Self> {
+        serde_json::from_str(content).with_context(|| t!("hook.invalid_json"))
+    }
+
+    /// 从输入流（通常是 stdin）读取并解析
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Self> {
+        let mut content = String::new();
+        reader
+            .read_to_string(&mut content)
+            .with_context(|| t!("hook.read_input"))?;
+
+        if content.trim().is_empty() {
+            anyhow::bail!("{}", t!("hook.empty_input"));
+        }
+
+        Self::from_json(&content)
+    }
+
+    /// 事件名称
+    pub fn event_name(&self) -> &'static str {
+        match &self.event {
+            HookEvent::Notification { .. } => "Notification",
+            HookEvent::PermissionRequest { .. } => "PermissionRequest",
+            HookEvent::PreToolUse { .. } => "PreToolUse",
+            HookEvent::PostToolUse { .. } => "PostToolUse",
+            HookEvent::Stop { .. } => "Stop",
+            HookEvent::SubagentStop { .. } => "SubagentStop",
+            HookEvent::UserPromptSubmit { .. } => "UserPromptSubmit",
+            HookEvent::SessionStart { .. } => "SessionStart",
+            HookEvent::Other => "Other",
+        }
+    }
+
+    /// 是否为会话（本轮对话）开始事件，仅用于记录开始时间而不发送通知
+    pub fn is_session_start(&self) -> bool {
+        matches!(
+            self.event,
+            HookEvent::UserPromptSubmit { .. } | HookEvent::SessionStart { .. }
+        )
+    }
+
+    /// 是否为完成事件（Stop / SubagentStop），耗时从会话开始计算
+    pub fn is_stop(&self) -> bool {
+        matches!(self.event, HookEvent::Stop { .. } | HookEvent::SubagentStop { .. })
+    }
+
+    /// 项目名称（工作目录的最后一级）
+    pub fn project_name(&self) -> Option<String> {
+        let cwd = self.cwd.as_deref()?;
+        Path::new(cwd.trim_end_matches(['/', '\\']))
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+    }
+
+    /// 根据事件推断通知状态
+    pub fn status(&self) -> NotificationStatus {
+        match &self.event {
+            HookEvent::Notification { .. }
+            | HookEvent::PermissionRequest { .. }
+            | HookEvent::PreToolUse { .. } => NotificationStatus::Pending,
+            HookEvent::PostToolUse { tool_response, .. } if is_error_response(tool_response) => {
+                NotificationStatus::Error
+            }
+            _ => NotificationStatus::Success,
+        }
+    }
+
+    /// 用于策略匹配的命令描述（工具名或事件名）
+    pub fn command(&self) -> String {
+        match &self.event {
+            HookEvent::PermissionRequest { tool_name, .. }
+            | HookEvent::PreToolUse { tool_name, .. }
+            | HookEvent::PostToolUse { tool_name, .. } => tool_name.clone(),
+            _ => self.event_name().to_string(),
+        }
+    }
+
+    /// 构建通知标题（带项目名前缀）
+    pub fn title(&self) -> String {
+        let title = match &self.event {
+            HookEvent::Notification { title, notification_type, .. } => {
+                match notification_type.as_deref() {
+                    Some("permission_prompt") => t!("hook.title.permission_prompt"),
+                    Some("idle_prompt") => t!("hook.title.idle_prompt"),
+                    _ => title.clone().unwrap_or_else(|| t!("hook.title.notification")),
+                }
+            }
+            HookEvent::PermissionRequest { .. } => t!("hook.title.permission_request"),
+            HookEvent::PreToolUse { tool_name, .. } => t!("hook.title.pre_tool_use", tool = tool_name),
+            HookEvent::PostToolUse { tool_name, .. } => t!("hook.title.post_tool_use", tool = tool_name),
+            HookEvent::Stop { .. } => t!("hook.title.stop"),
+            HookEvent::SubagentStop { .. } => t!("hook.title.subagent_stop"),
+            HookEvent::UserPromptSubmit { .. }
+            | HookEvent::SessionStart { .. }
+            | HookEvent::Other => t!("hook.title.notification"),
+        };
+
+        match self.project_name() {
+            Some(project) => format!("[{}] {}", project, title),
+            None => title,
+        }
+    }
+
+    /// 构建通知消息，没有可用内容时返回 None
+    pub fn message(&self) -> Option<String> {
+        match &self.event {
+            HookEvent::Notification { message, .. } => Some(message.clone()),
+            HookEvent::PermissionRequest { tool_name, tool_input }
+            | HookEvent::PreToolUse { tool_name, tool_input }
+            | HookEvent::PostToolUse { tool_name, tool_input, .. } => {
+                Some(match summarize_tool_input(tool_input) {
+                    Some(detail) => format!("{}: {}", tool_name, detail),
+                    None => tool_name.clone(),
+                })
+            }
+            HookEvent::Stop { .. } => Some(t!("hook.message.stop")),
+            HookEvent::SubagentStop { .. } => Some(t!("hook.message.subagent_stop")),
+            HookEvent::UserPromptSubmit { .. }
+            | HookEvent::SessionStart { .. }
+            | HookEvent::Other => None,
+        }
+    }
+}
+
+/// 提取工具参数中最有代表性的字段（文件路径、命令等）
+fn summarize_tool_input(tool_input: &Value) -> Option<String> {
+    const KEYS: [&str; 5] = ["file_path", "command", "path", "pattern", "url"];
+
+    let detail = KEYS
+        .iter()
+        .find_map(|key| tool_input.get(key).and_then(|v| v.as_str()))?;
+
+    let detail = detail.lines().next().unwrap_or_default();
+    if detail.chars().count() > MAX_DETAIL_CHARS {
+        let truncated: String = detail.chars().take(MAX_DETAIL_CHARS).collect();
+        Some(format!("{}…", truncated))
+    } else {
+        Some(detail.to_string())
+    }
+}
+
+/// 判断工具返回值是否表示失败
+fn is_error_response(tool_response: &Value) -> bool {
+    tool_response.get("success").and_then(|v| v.as_bool()) == Some(false)
+        || tool_response.get("is_error").and_then(|v| v.as_bool()) == Some(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_notification() {
+        let input = HookInput::from_json(r#"{
+            "session_id": "abc123",
+            "transcript_path": "/home/dev/.claude/projects/demo/abc123.jsonl",
+            "cwd": "/home/dev/projects/demo",
+            "hook_event_name": "Notification",
+            "message": "Claude needs your permission to use Bash",
+            "notification_type": "permission_prompt"
+        }"#).unwrap();
+
+        assert_eq!(input.session_id.as_deref(), Some("abc123"));
+        assert_eq!(input.event_name(), "Notification");
+        assert_eq!(input.project_name().as_deref(), Some("demo"));
+        assert!(matches!(input.status(), NotificationStatus::Pending));
+        assert_eq!(input.title(), "[demo] Claude Code 需要授权");
+        assert_eq!(input.message().as_deref(), Some("Claude needs your permission to use Bash"));
+        assert_eq!(input.command(), "Notification");
+    }
+
+    #[test]
+    fn test_parse_permission_request() {
+        let input = HookInput::from_json(r#"{
+            "session_id": "abc123",
+            "cwd": "/home/dev/projects/demo/",
+            "permission_mode": "default",
+            "hook_event_name": "PermissionRequest",
+            "tool_name": "Write",
+            "tool_input": {"file_path": "/home/dev/projects/demo/src/main.rs", "content": "fn main() {}"}
+        }"#).unwrap();
+
+        assert_eq!(input.event_name(), "PermissionRequest");
+        assert_eq!(input.project_name().as_deref(), Some("demo"));
+        assert!(matches!(input.status(), NotificationStatus::Pending));
+        assert_eq!(input.title(), "[demo] Claude Code 请求权限");
+        assert_eq!(
+            input.message().as_deref(),
+            Some("Write: /home/dev/projects/demo/src/main.rs")
+        );
+        assert_eq!(input.command(), "Write");
+    }
+
+    #[test]
+    fn test_parse_stop_events() {
+        let input = HookInput::from_json(r#"{
+            "session_id": "abc123",
+            "cwd": "/home/dev/projects/demo",
+            "hook_event_name": "Stop",
+            "stop_hook_active": false
+        }"#).unwrap();
+
+        assert!(input.is_stop());
+        assert!(!input.is_session_start());
+        assert!(matches!(input.status(), NotificationStatus::Success));
+        assert_eq!(input.title(), "[demo] Claude Code 已完成");
+        assert_eq!(input.message().as_deref(), Some("本轮对话已完成"));
+
+        let input = HookInput::from_json(r#"{
+            "session_id": "abc123",
+            "hook_event_name": "SubagentStop",
+            "stop_hook_active": true
+        }"#).unwrap();
+
+        assert!(input.is_stop());
+        assert_eq!(input.title(), "子代理已完成");
+        assert_eq!(input.command(), "SubagentStop");
+    }
+
+    #[test]
+    fn test_parse_session_start_events() {
+        let input = HookInput::from_json(r#"{
+            "session_id": "abc123",
+            "hook_event_name": "UserPromptSubmit",
+            "prompt": "fix the build"
+        }"#).unwrap();
+        assert!(input.is_session_start());
+
+        let input = HookInput::from_json(r#"{
+            "session_id": "abc123",
+            "hook_event_name": "SessionStart",
+            "source": "startup"
+        }"#).unwrap();
+        assert!(input.is_session_start());
+        assert!(input.message().is_none());
+    }
+
+    #[test]
+    fn test_parse_unknown_event() {
+        let input = HookInput::from_json(r#"{
+            "session_id": "abc123",
+            "hook_event_name": "PreCompact",
+            "trigger": "auto"
+        }"#).unwrap();
+
+        assert_eq!(input.event_name(), "Other");
+        assert!(input.project_name().is_none());
+        assert_eq!(input.title(), "Claude Code 通知");
+        assert!(input.message().is_none());
+    }
+
+    #[test]
+    fn test_post_tool_use_failure_status() {
+        let input = HookInput::from_json(r#"{
+            "hook_event_name": "PostToolUse",
+            "tool_name": "Bash",
+            "tool_input": {"command": "cargo build\ncargo test"},
+            "tool_response": {"success": false}
+        }"#).unwrap();
+
+        assert!(matches!(input.status(), NotificationStatus::Error));
+        assert_eq!(input.message().as_deref(), Some("Bash: cargo build"));
+    }
+
+    #[test]
+    fn test_long_tool_input_truncated() {
+        let command = "x".repeat(200);
+        let detail = summarize_tool_input(&serde_json::json!({ "command": command })).unwrap();
+        assert_eq!(detail.chars().count(), MAX_DETAIL_CHARS + 1);
+        assert!(detail.ends_with('…'));
+    }
+
+    #[test]
+    fn test_from_reader_empty() {
+        assert!(HookInput::from_reader("  \n".as_bytes()).is_err());
+        assert!(HookInput::from_reader("not json".as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,373 @@
+//! 本地化模块
+//!
+//! 用户可见的文字按键从消息目录中查找：
+//! - 内置 zh-CN 和 en-US 目录（`locales/*.yaml`），嵌套的键展开为点分路径，值中的 `{name}` 为参数
+//! - 配置目录下的 `locales/<语言>.yaml` 可以新增语言或覆盖内置文字，缺少的键依次回退到 en-US、zh-CN
+//! - 语言按 `--lang`、配置项 `language`、`LC_ALL`/`LC_MESSAGES`/`LANG` 的顺序确定，都未设置时为 zh-CN
+
+use anyhow::{bail, Context, Result};
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// 默认语言，也是最后的回退
+pub const DEFAULT_LOCALE: &str = "zh-CN";
+
+/// 自定义目录缺少的键先回退到该语言
+const FALLBACK_LOCALE: &str = "en-US";
+
+/// 内置的消息目录
+const BUILTIN_CATALOGS: &[(&str, &str)] = &[
+    ("zh-CN", include_str!("../locales/zh-CN.yaml")),
+    ("en-US", include_str!("../locales/en-US.yaml")),
+];
+
+/// 按键查找文字，可带 `name = value` 参数替换 `{name}`
+///
+/// ```ignore
+/// println!("{}", t!("config.edit.saved", path = path.display()));
+/// ```
+#[macro_export]
+macro_rules! t {
+    ($key:expr) => {
+        $crate::i18n::text($key)
+    };
+    ($key:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::format($key, &[$((stringify!($name), ($value).to_string())),+])
+    };
+}
+
+/// 一种语言的消息目录
+#[derive(Debug, Default)]
+pub struct Catalog {
+    messages: HashMap<String, String>,
+}
+
+impl Catalog {
+    /// 解析 YAML 消息目录，嵌套的映射展开为点分路径
+    pub fn parse(text: &str) -> Result<Self> {
+        let value: serde_yaml::Value = serde_yaml::from_str(text).context("消息目录格式错误")?;
+        let mut messages = HashMap::new();
+        flatten(&value, String::new(), &mut messages)?;
+        Ok(Self { messages })
+    }
+
+    /// 读取消息目录文件
+    pub fn read(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("无法读取消息目录: {:?}", path))?;
+        Self::parse(&text).with_context(|| format!("无法解析消息目录: {:?}", path))
+    }
+
+    /// 内置的消息目录
+    pub fn builtin(locale: &str) -> Option<Self> {
+        BUILTIN_CATALOGS
+            .iter()
+            .find(|(name, _)| *name == locale)
+            .map(|(_, text)| Self::parse(text).expect("内置消息目录应有效"))
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.messages.get(key).map(String::as_str)
+    }
+
+    #[cfg(test)]
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.messages.keys().map(String::as_str)
+    }
+}
+
+fn flatten(value: &serde_yaml::Value, prefix: String, out: &mut HashMap<String, String>) -> Result<()> {
+    match value {
+        serde_yaml::Value::Mapping(map) => {
+            for (key, child) in map {
+                let key = key.as_str().with_context(|| format!("{}: 键应为字符串", prefix))?;
+                let path = if prefix.is_empty() { key.to_string() } else { format!("{}.{}", prefix, key) };
+                flatten(child, path, out)?;
+            }
+        }
+        serde_yaml::Value::String(text) => {
+            out.insert(prefix, text.clone());
+        }
+        serde_yaml::Value::Null if prefix.is_empty() => {}
+        _ => bail!("{}: 值应为字符串", prefix),
+    }
+    Ok(())
+}
+
+/// 当前语言的文字查找器
+#[derive(Debug)]
+pub struct Localizer {
+    locale: String,
+    /// 按查找顺序排列
+    catalogs: Vec<Catalog>,
+}
+
+impl Localizer {
+    /// 加载 `locale` 的消息目录，`catalog_dir` 中的同名文件优先于内置目录
+    pub fn new(locale: &str, catalog_dir: Option<&Path>) -> Self {
+        let mut catalogs = Vec::new();
+        if let Some(path) = catalog_dir.map(|dir| dir.join(format!("{}.yaml", locale)))
+            && path.is_file()
+        {
+            match Catalog::read(&path) {
+                Ok(catalog) => catalogs.push(catalog),
+                Err(e) => log::warn!("忽略自定义消息目录: {:#}", e),
+            }
+        }
+
+        let mut chain = vec![locale];
+        if locale != DEFAULT_LOCALE {
+            chain.push(FALLBACK_LOCALE);
+        }
+        chain.push(DEFAULT_LOCALE);
+        chain.dedup();
+        catalogs.extend(chain.into_iter().filter_map(Catalog::builtin));
+
+        Self {
+            locale: locale.to_string(),
+            catalogs,
+        }
+    }
+
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    /// 查找文字，所有目录都没有该键时返回键本身
+    pub fn text(&self, key: &str) -> String {
+        self.lookup(key).unwrap_or(key).to_string()
+    }
+
+    /// 查找文字并替换 `{name}` 参数
+    ///
+    /// 只扫描一遍模板，参数值中的 `{name}` 原样保留，未传入的参数也原样保留
+    pub fn format(&self, key: &str, args: &[(&str, String)]) -> String {
+        static PLACEHOLDER: OnceLock<Regex> = OnceLock::new();
+        let placeholder = PLACEHOLDER.get_or_init(|| Regex::new(r"\{(\w+)\}").expect("占位符正则应有效"));
+
+        let text = self.text(key);
+        placeholder
+            .replace_all(&text, |caps: &Captures| {
+                args.iter()
+                    .find(|(name, _)| *name == &caps[1])
+                    .map_or_else(|| caps[0].to_string(), |(_, value)| value.clone())
+            })
+            .into_owned()
+    }
+
+    pub fn lookup(&self, key: &str) -> Option<&str> {
+        let found = self.catalogs.iter().find_map(|catalog| catalog.get(key));
+        if found.is_none() {
+            log::debug!("消息目录中没有键: {}", key);
+        }
+        found
+    }
+}
+
+static LOCALIZER: OnceLock<Localizer> = OnceLock::new();
+
+/// 设置当前语言（只在启动时调用一次）
+pub fn init(localizer: Localizer) {
+    let _ = LOCALIZER.set(localizer);
+}
+
+/// 当前语言的查找器，未初始化时为默认语言
+///
+/// 确定语言时（如读取配置文件出错）也会查找文字，因此未初始化时不占用 `LOCALIZER`，之后的 `init` 仍然生效
+pub fn current() -> &'static Localizer {
+    static DEFAULT: OnceLock<Localizer> = OnceLock::new();
+    LOCALIZER
+        .get()
+        .unwrap_or_else(|| DEFAULT.get_or_init(|| Localizer::new(DEFAULT_LOCALE, None)))
+}
+
+pub fn text(key: &str) -> String {
+    current().text(key)
+}
+
+pub fn format(key: &str, args: &[(&str, String)]) -> String {
+    current().format(key, args)
+}
+
+/// 自定义消息目录所在的目录
+pub fn get_catalog_dir() -> PathBuf {
+    crate::config::get_config_path()
+        .parent()
+        .map(|dir| dir.join("locales"))
+        .unwrap_or_else(|| PathBuf::from("locales"))
+}
+
+/// 可用的语言：内置语言和 `catalog_dir` 中的自定义目录
+pub fn available_locales(catalog_dir: Option<&Path>) -> Vec<String> {
+    let mut locales: Vec<String> = BUILTIN_CATALOGS.iter().map(|(name, _)| name.to_string()).collect();
+    if let Some(entries) = catalog_dir.and_then(|dir| fs::read_dir(dir).ok()) {
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.extension().is_some_and(|ext| ext == "yaml")
+                && let Some(name) = path.file_stem().and_then(|stem| stem.to_str())
+                && !locales.iter().any(|locale| locale == name)
+            {
+                locales.push(name.to_string());
+            }
+        }
+    }
+    locales
+}
+
+/// 将 `en_US.UTF-8`、`en-us` 等写法规范为 `en-US`，`C`、`POSIX` 和空值返回 None
+pub fn normalize_locale(raw: &str) -> Option<String> {
+    let name = raw.split(['.', '@']).next().unwrap_or_default().trim();
+    if name.is_empty() || name == "C" || name == "POSIX" {
+        return None;
+    }
+
+    let mut parts = name.split(['_', '-']);
+    let language = parts.next()?.to_lowercase();
+    match parts.next() {
+        Some(region) if !region.is_empty() => Some(format!("{}-{}", language, region.to_uppercase())),
+        _ => Some(language),
+    }
+}
+
+/// 在可用语言中查找匹配项：先完全匹配，再按语言部分匹配（如 `en` 或 `en-GB` 匹配 `en-US`）
+pub fn match_locale(requested: &str, available: &[String]) -> Option<String> {
+    let requested = normalize_locale(requested)?;
+    let language = requested.split('-').next().unwrap_or_default();
+
+    available
+        .iter()
+        .find(|locale| locale.eq_ignore_ascii_case(&requested))
+        .or_else(|| {
+            available
+                .iter()
+                .find(|locale| locale.split('-').next().is_some_and(|l| l.eq_ignore_ascii_case(language)))
+        })
+        .cloned()
+}
+
+/// 环境变量中的语言设置，按优先级排列
+pub fn env_locales() -> Vec<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .filter(|value| !value.is_empty())
+        .collect()
+}
+
+/// 确定界面语言：`--lang`、配置项 `language`、环境变量，首个有对应消息目录的生效
+pub fn resolve_locale(flag: Option<&str>, configured: Option<&str>, env: &[String], available: &[String]) -> String {
+    for requested in [flag, configured].into_iter().flatten() {
+        match match_locale(requested, available) {
+            Some(locale) => return locale,
+            None => log::warn!("没有语言 {} 的消息目录，可用: {}", requested, available.join(", ")),
+        }
+    }
+
+    env.iter()
+        .find_map(|requested| match_locale(requested, available))
+        .unwrap_or_else(|| DEFAULT_LOCALE.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    fn placeholders(text: &str) -> BTreeSet<String> {
+        Regex::new(r"\{([a-z_]+)\}")
+            .unwrap()
+            .captures_iter(text)
+            .map(|caps| caps[1].to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_every_key_exists_in_every_catalog() {
+        let catalogs: Vec<(&str, Catalog)> = BUILTIN_CATALOGS
+            .iter()
+            .map(|(name, _)| (*name, Catalog::builtin(name).unwrap()))
+            .collect();
+        let all_keys: BTreeSet<&str> = catalogs.iter().flat_map(|(_, catalog)| catalog.keys()).collect();
+
+        let mut problems = Vec::new();
+        for key in &all_keys {
+            let present: Vec<(&str, &str)> = catalogs
+                .iter()
+                .filter_map(|(name, catalog)| catalog.get(key).map(|text| (*name, text)))
+                .collect();
+            for (name, _) in catalogs.iter().filter(|(_, catalog)| catalog.get(key).is_none()) {
+                problems.push(format!("{}: 缺少 {}", name, key));
+            }
+            // 各语言的参数应一致
+            if let Some((first, text)) = present.first() {
+                for (name, other) in &present[1..] {
+                    if placeholders(text) != placeholders(other) {
+                        problems.push(format!("{}: {} 的参数与 {} 不一致", name, key, first));
+                    }
+                }
+            }
+        }
+        assert!(problems.is_empty(), "{}", problems.join("\n"));
+    }
+
+    #[test]
+    fn test_every_used_key_exists() {
+        let key_pattern = Regex::new(r#"\bt!\(\s*"([^"]+)""#).unwrap();
+        let catalog = Catalog::builtin(DEFAULT_LOCALE).unwrap();
+        let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+
+        let mut missing = Vec::new();
+        for entry in fs::read_dir(&src).unwrap().flatten() {
+            let text = fs::read_to_string(entry.path()).unwrap();
+            for caps in key_pattern.captures_iter(&text) {
+                if catalog.get(&caps[1]).is_none() {
+                    missing.push(format!("{}: {}", entry.file_name().to_string_lossy(), &caps[1]));
+                }
+            }
+        }
+        assert!(missing.is_empty(), "消息目录中缺少:\n{}", missing.join("\n"));
+    }
+
+    #[test]
+    fn test_format_and_fallback() {
+        let dir = std::env::temp_dir().join(format!("ccn-test-locales-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("fr-FR.yaml"), "status:\n  success: terminé\n").unwrap();
+
+        let localizer = Localizer::new("fr-FR", Some(&dir));
+        assert_eq!(localizer.text("status.success"), "terminé");
+        // 自定义目录缺少的键回退到 en-US
+        assert_eq!(localizer.text("status.error"), Catalog::builtin("en-US").unwrap().get("status.error").unwrap());
+        assert_eq!(localizer.text("no.such.key"), "no.such.key");
+
+        let zh = Localizer::new("zh-CN", None);
+        assert_eq!(zh.format("notify.message", &[("cmd", "npm test".to_string()), ("duration", "3秒".to_string())]), "npm test (耗时: 3秒)");
+        // 参数值中的占位符不再被替换
+        assert_eq!(
+            zh.format("notify.message", &[("cmd", "echo {duration}".to_string()), ("duration", "15秒".to_string())]),
+            "echo {duration} (耗时: 15秒)"
+        );
+
+        let available = available_locales(Some(&dir));
+        assert!(available.contains(&"fr-FR".to_string()));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_resolve_locale() {
+        let available = vec!["zh-CN".to_string(), "en-US".to_string()];
+        assert_eq!(normalize_locale("en_US.UTF-8").as_deref(), Some("en-US"));
+        assert_eq!(normalize_locale("C.UTF-8"), None);
+        assert_eq!(match_locale("en", &available).as_deref(), Some("en-US"));
+        assert_eq!(match_locale("en_GB.UTF-8", &available).as_deref(), Some("en-US"));
+        assert_eq!(match_locale("fr_FR", &available), None);
+
+        let env = vec!["fr_FR.UTF-8".to_string(), "en_US.UTF-8".to_string()];
+        assert_eq!(resolve_locale(Some("zh"), Some("en-US"), &env, &available), "zh-CN");
+        assert_eq!(resolve_locale(None, Some("en-US"), &[], &available), "en-US");
+        assert_eq!(resolve_locale(Some("xx"), None, &env, &available), "en-US");
+        assert_eq!(resolve_locale(None, None, &["C".to_string()], &available), "zh-CN");
+    }
+}
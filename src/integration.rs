@@ -7,9 +7,17 @@ use serde_json::{json, Value};
 use std::fs;
 use std::path::PathBuf;
 
+use crate::config::HookEventKind;
+
 /// 集成管理器
 pub struct IntegrationManager;
 
+impl Default for IntegrationManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl IntegrationManager {
     pub fn new() -> Self {
         Self
@@ -55,23 +63,23 @@ impl IntegrationManager {
     /// 备份配置文件
     pub fn backup_config(&self, config_path: &PathBuf) -> Result<PathBuf> {
         let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
-        let backup_path = config_path.with_extension(&format!("bak.{}", timestamp));
+        let backup_path = config_path.with_extension(format!("bak.{}", timestamp));
 
         fs::copy(config_path, &backup_path)
-            .with_context(|| format!("无法备份配置文件: {:?}", config_path))?;
+            .with_context(|| t!("config.backup_failed", path = format!("{:?}", config_path)))?;
 
-        log::info!("配置文件已备份到: {:?}", backup_path);
+        log::info!("{}", t!("setup.log.backup", path = format!("{:?}", backup_path)));
         Ok(backup_path)
     }
 
     /// 注入 hooks 配置
-    pub fn inject_hooks(&self, config_path: &PathBuf) -> Result<()> {
+    pub fn inject_hooks(&self, config_path: &PathBuf, events: &[HookEventKind]) -> Result<()> {
         // 读取现有配置
         let content = fs::read_to_string(config_path)
-            .context("无法读取配置文件")?;
+            .with_context(|| t!("setup.read_settings"))?;
 
         let mut config: Value = serde_json::from_str(&content)
-            .context("配置文件 JSON 格式错误")?;
+            .with_context(|| t!("setup.invalid_settings"))?;
 
         // 确保 hooks 对象存在
         if !config.is_object() {
@@ -86,82 +94,36 @@ impl IntegrationManager {
             .as_object_mut()
             .unwrap();
 
-        // 清理旧的 hooks（包括不存在的 PermissionRequest 和 Stop）
+        // 清理旧的 hooks（不存在的事件，以及之前写入的 ccn hooks）
         hooks.remove("PostCommand");
         hooks.remove("CommandError");
-        hooks.remove("PostToolUse");
         hooks.remove("PostToolUseFailure");
-        hooks.remove("PermissionRequest");
-        hooks.remove("Stop");
+        remove_ccn_entries(hooks);
 
-        // 定义 Notification hook（当 Claude Code 发送权限请求通知时触发）
+        // hook 数据通过 stdin 传入，由 `--from-hook` 解析出标题、消息和项目名
         let command = if cfg!(windows) {
-            r#"ccn notify --status=pending --cmd="Claude Code 需要授权" || exit 0"#
+            "ccn notify --from-hook || exit 0"
         } else {
-            "ccn notify --status=pending --cmd='Claude Code 需要授权' || true"
+            "ccn notify --from-hook || true"
         };
 
-        let notification_hook = json!({
-            "matcher": "permission_prompt",
-            "hooks": [{
-                "type": "command",
-                "command": command
-            }]
-        });
-
-        // 注入 Notification hook
-        let notification = hooks.entry("Notification").or_insert_with(|| json!([]));
-        if let Some(arr) = notification.as_array_mut() {
-            // 简单去重检查
-            let has_hook = arr.iter().any(|h| {
-                h["matcher"].as_str().map_or(false, |m| m == "permission_prompt")
-                    && h["hooks"].as_array().map_or(false, |cmds| {
-                        cmds.iter().any(|cmd| {
-                            cmd["command"].as_str().map_or(false, |c| c.contains("ccn notify"))
-                        })
-                    })
-            });
-
-            if !has_hook {
-                arr.push(notification_hook);
-            }
+        for event in events {
+            push_hook_entry(hooks, event.event_name(), hook_matcher(*event), command);
         }
 
-        // 定义 PermissionRequest hook（当 Claude Code 请求文件写/编辑权限时触发）
-        let permission_hook = json!({
-            "matcher": "Write|Edit",
-            "hooks": [{
-                "type": "command",
-                "command": command
-            }]
-        });
-
-        // 注入 PermissionRequest hook
-        let permission_request = hooks.entry("PermissionRequest").or_insert_with(|| json!([]));
-        if let Some(arr) = permission_request.as_array_mut() {
-            // 简单去重检查
-            let has_hook = arr.iter().any(|h| {
-                h["matcher"].as_str().map_or(false, |m| m == "Write|Edit")
-                    && h["hooks"].as_array().map_or(false, |cmds| {
-                        cmds.iter().any(|cmd| {
-                            cmd["command"].as_str().map_or(false, |c| c.contains("ccn notify"))
-                        })
-                    })
-            });
-
-            if !has_hook {
-                arr.push(permission_hook);
-            }
+        // Stop / SubagentStop 需要记录本轮对话的开始时间以计算耗时
+        if events.iter().any(|e| matches!(e, HookEventKind::Stop | HookEventKind::SubagentStop)) {
+            push_hook_entry(hooks, SESSION_START_EVENT, None, command);
         }
 
         // 写回配置文件
         let updated_content = serde_json::to_string_pretty(&config)
-            .context("序列化配置失败")?;
+            .with_context(|| t!("config.serialize"))?;
 
         fs::write(config_path, updated_content)
-            .context("无法写入配置文件")?;
+            .with_context(|| t!("setup.write_settings"))?;
 
-        log::info!("Hooks 已注入到配置文件");
+        log::info!("{}", t!("setup.log.injected"));
         Ok(())
     }
 
@@ -172,8 +134,8 @@ impl IntegrationManager {
         let notifier = get_notification_manager();
         notifier.send_notification(
             NotificationStatus::Success,
-            "CCN 集成成功！",
-            "CCN 已成功集成到 Claude Code",
+            &t!("setup.test.title"),
+            &t!("setup.test.message"),
             5000,
         )?;
 
@@ -184,163 +146,56 @@ impl IntegrationManager {
     pub fn remove_hooks(&self, config_path: &PathBuf) -> Result<()> {
         // 读取现有配置
         let content = fs::read_to_string(config_path)
-            .context("无法读取配置文件")?;
+            .with_context(|| t!("setup.read_settings"))?;
 
         let mut config: Value = serde_json::from_str(&content)
-            .context("配置文件 JSON 格式错误")?;
+            .with_context(|| t!("setup.invalid_settings"))?;
 
         // 移除 hooks
-        if let Some(obj) = config.as_object_mut() {
-            if let Some(hooks) = obj.get_mut("hooks").and_then(|h| h.as_object_mut()) {
-                // 移除旧的 legacy hooks
-                hooks.remove("PostCommand");
-                hooks.remove("CommandError");
-                hooks.remove("PermissionRequest");
-                hooks.remove("Stop");
-
-                // 移除 PostToolUse 中的 ccn hooks
-                if let Some(arr) = hooks.get_mut("PostToolUse").and_then(|v| v.as_array_mut()) {
-                    arr.retain(|h| {
-                        !h["hooks"].as_array().map_or(false, |cmds| {
-                            cmds.iter().any(|cmd| {
-                                cmd["command"].as_str().map_or(false, |s| s.contains("ccn notify"))
-                            })
-                        })
-                    });
-                    if arr.is_empty() {
-                        hooks.remove("PostToolUse");
-                    }
-                }
+        if let Some(hooks) = config.get_mut("hooks").and_then(|h| h.as_object_mut()) {
+            // 移除旧的 legacy hooks
+            hooks.remove("PostCommand");
+            hooks.remove("CommandError");
 
-                // 移除 PostToolUseFailure 中的 ccn hooks
-                if let Some(arr) = hooks.get_mut("PostToolUseFailure").and_then(|v| v.as_array_mut()) {
-                    arr.retain(|h| {
-                        !h["hooks"].as_array().map_or(false, |cmds| {
-                            cmds.iter().any(|cmd| {
-                                cmd["command"].as_str().map_or(false, |s| s.contains("ccn notify"))
-                            })
-                        })
-                    });
-                    if arr.is_empty() {
-                        hooks.remove("PostToolUseFailure");
-                    }
-                }
-
-                // 移除 Notification 中的 ccn hooks
-                if let Some(arr) = hooks.get_mut("Notification").and_then(|v| v.as_array_mut()) {
-                    arr.retain(|h| {
-                        !h["matcher"].as_str().map_or(false, |m| m == "permission_prompt")
-                            || !h["hooks"].as_array().map_or(false, |cmds| {
-                                cmds.iter().any(|cmd| {
-                                    cmd["command"].as_str().map_or(false, |c| c.contains("ccn notify"))
-                                })
-                            })
-                    });
-                    if arr.is_empty() {
-                        hooks.remove("Notification");
-                    }
-                }
-
-                // 移除 PermissionRequest 中的 ccn hooks
-                if let Some(arr) = hooks.get_mut("PermissionRequest").and_then(|v| v.as_array_mut()) {
-                    arr.retain(|h| {
-                        !h["matcher"].as_str().map_or(false, |m| m == "Write|Edit")
-                            || !h["hooks"].as_array().map_or(false, |cmds| {
-                                cmds.iter().any(|cmd| {
-                                    cmd["command"].as_str().map_or(false, |c| c.contains("ccn notify"))
-                                })
-                            })
-                    });
-                    if arr.is_empty() {
-                        hooks.remove("PermissionRequest");
-                    }
-                }
-            }
+            // 移除所有事件中的 ccn hooks，保留用户自己的 hooks
+            remove_ccn_entries(hooks);
         }
 
         // 写回配置文件
         let updated_content = serde_json::to_string_pretty(&config)
-            .context("序列化配置失败")?;
+            .with_context(|| t!("config.serialize"))?;
 
         fs::write(config_path, updated_content)
-            .context("无法写入配置文件")?;
+            .with_context(|| t!("setup.write_settings"))?;
 
-        log::info!("Hooks 已从配置文件移除");
+        log::info!("{}", t!("setup.log.removed"));
         Ok(())
     }
 
     /// 检查是否已集成
     pub fn is_integrated(&self, config_path: &PathBuf) -> Result<bool> {
         let content = fs::read_to_string(config_path)
-            .context("无法读取配置文件")?;
+            .with_context(|| t!("setup.read_settings"))?;
 
         let config: Value = serde_json::from_str(&content)
-            .context("配置文件 JSON 格式错误")?;
-
-        if let Some(obj) = config.as_object() {
-            if let Some(hooks) = obj.get("hooks") {
-                if let Some(hooks_obj) = hooks.as_object() {
-                    // 检查 Notification hook
-                    let has_notification = hooks_obj.get("Notification")
-                        .and_then(|v| v.as_array())
-                        .map_or(false, |arr| {
-                            arr.iter().any(|h| {
-                                h["matcher"].as_str().map_or(false, |m| m == "permission_prompt")
-                                    && h["hooks"].as_array().map_or(false, |cmds| {
-                                        cmds.iter().any(|cmd| {
-                                            cmd["command"].as_str().map_or(false, |c| c.contains("ccn notify"))
-                                        })
-                                    })
-                            })
-                        });
-
-                    // 检查 PermissionRequest hook
-                    let has_permission_request = hooks_obj.get("PermissionRequest")
-                        .and_then(|v| v.as_array())
-                        .map_or(false, |arr| {
-                            arr.iter().any(|h| {
-                                h["matcher"].as_str().map_or(false, |m| m == "Write|Edit")
-                                    && h["hooks"].as_array().map_or(false, |cmds| {
-                                        cmds.iter().any(|cmd| {
-                                            cmd["command"].as_str().map_or(false, |c| c.contains("ccn notify"))
-                                        })
-                                    })
-                            })
-                        });
-
-                    // 检查旧的 Stop hook
-                    let has_stop = hooks_obj.get("Stop")
-                        .and_then(|v| v.as_array())
-                        .map_or(false, |arr| {
-                            arr.iter().any(|h| {
-                                h["type"].as_str().map_or(false, |t| t == "command")
-                                    && h["command"].as_str().map_or(false, |c| c.contains("ccn notify"))
-                            })
-                        });
-
-                    // 检查旧的 PostToolUse hook
-                    let has_post_tool = hooks_obj.get("PostToolUse")
-                        .and_then(|v| v.as_array())
-                        .map_or(false, |arr| {
-                            arr.iter().any(|h| {
-                                h["hooks"].as_array().map_or(false, |cmds| {
-                                    cmds.iter().any(|cmd| {
-                                        cmd["command"].as_str().map_or(false, |s| s.contains("ccn notify"))
-                                    })
-                                })
-                            })
-                        });
-
-                    // 同时也检查旧的配置，以便向后兼容检测
-                    let has_legacy = hooks_obj.contains_key("PostCommand") ||
-                                   hooks_obj.contains_key("CommandError");
-
-                    return Ok(has_notification || has_permission_request || has_stop || has_post_tool || has_legacy);
-                }
-            }
-        }
+            .with_context(|| t!("setup.invalid_settings"))?;
 
-        Ok(false)
+        let Some(hooks_obj) = config.get("hooks").and_then(|h| h.as_object()) else {
+            return Ok(false);
+        };
+
+        // 检查任意事件中是否存在 ccn hook
+        let has_ccn_hook = hooks_obj.values().any(|entries| {
+            entries
+                .as_array()
+                .is_some_and(|arr| arr.iter().any(is_ccn_entry))
+        });
+
+        // 同时也检查旧的配置，以便向后兼容检测
+        let has_legacy = hooks_obj.contains_key("PostCommand") ||
+                       hooks_obj.contains_key("CommandError");
+
+        Ok(has_ccn_hook || has_legacy)
     }
 
     /// 验证集成：测试 hooks 命令是否可执行
@@ -360,13 +215,13 @@ impl IntegrationManager {
             return Ok(VerificationResult {
                 ccn_in_path: false,
                 test_notification_sent: false,
-                error: Some("ccn 命令不在 PATH 中，请重新运行 `ccn setup` 并重启终端".to_string()),
+                error: Some(t!("verify.not_in_path")),
             });
         }
 
         // 尝试发送测试通知
         let test_result = Command::new("ccn")
-            .args(&["notify", "--status=success", "--duration=1", "--cmd=test"])
+            .args(["notify", "--status=success", "--duration=1", "--cmd=test"])
             .output();
 
         let test_success = match test_result {
@@ -375,7 +230,7 @@ impl IntegrationManager {
                 return Ok(VerificationResult {
                     ccn_in_path: true,
                     test_notification_sent: false,
-                    error: Some(format!("测试通知失败: {}", e)),
+                    error: Some(t!("verify.test_error", error = e)),
                 });
             }
         };
@@ -383,7 +238,7 @@ impl IntegrationManager {
         Ok(VerificationResult {
             ccn_in_path: true,
             test_notification_sent: test_success,
-            error: if test_success { None } else { Some("测试通知执行失败".to_string()) },
+            error: if test_success { None } else { Some(t!("verify.test_failed")) },
         })
     }
 }
@@ -399,6 +254,71 @@ pub struct VerificationResult {
     pub error: Option<String>,
 }
 
+/// 用于记录本轮对话开始时间的辅助事件
+const SESSION_START_EVENT: &str = "UserPromptSubmit";
+
+/// 获取事件对应的 matcher（Stop 等事件不支持 matcher）
+fn hook_matcher(event: HookEventKind) -> Option<&'static str> {
+    match event {
+        HookEventKind::Notification => Some("permission_prompt"),
+        HookEventKind::PermissionRequest => Some("Write|Edit"),
+        HookEventKind::Stop | HookEventKind::SubagentStop => None,
+    }
+}
+
+/// 判断 hook 条目是否由 ccn 写入
+fn is_ccn_entry(entry: &Value) -> bool {
+    let is_ccn_command = |cmd: &Value| {
+        cmd["command"].as_str().is_some_and(|c| c.contains("ccn notify"))
+    };
+
+    // 新格式：{ "matcher": ..., "hooks": [{ "type": "command", "command": ... }] }
+    // 旧格式：{ "type": "command", "command": ... }
+    entry["hooks"].as_array().is_some_and(|cmds| cmds.iter().any(is_ccn_command))
+        || is_ccn_command(entry)
+}
+
+/// 向事件追加 ccn hook 条目（已存在时跳过）
+fn push_hook_entry(
+    hooks: &mut serde_json::Map<String, Value>,
+    event: &str,
+    matcher: Option<&str>,
+    command: &str,
+) {
+    let mut entry = json!({
+        "hooks": [{
+            "type": "command",
+            "command": command
+        }]
+    });
+    if let Some(matcher) = matcher {
+        entry["matcher"] = json!(matcher);
+    }
+
+    let entries = hooks.entry(event).or_insert_with(|| json!([]));
+    if let Some(arr) = entries.as_array_mut() {
+        // 简单去重检查
+        let has_hook = arr
+            .iter()
+            .any(|h| h["matcher"].as_str() == matcher && is_ccn_entry(h));
+
+        if !has_hook {
+            arr.push(entry);
+        }
+    }
+}
+
+/// 移除所有事件中的 ccn hook 条目，事件为空时一并移除
+fn remove_ccn_entries(hooks: &mut serde_json::Map<String, Value>) {
+    hooks.retain(|_, entries| match entries.as_array_mut() {
+        Some(arr) => {
+            arr.retain(|h| !is_ccn_entry(h));
+            !arr.is_empty()
+        }
+        None => true,
+    });
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -419,7 +339,7 @@ mod tests {
         let test_file = temp_dir.join("test_settings.json");
 
         // 创建测试文件
-        if let Ok(_) = fs::write(&test_file, "{}") {
+        if fs::write(&test_file, "{}").is_ok() {
             let backup_result = manager.backup_config(&test_file);
 
             // 验证备份文件名格式
@@ -463,8 +383,8 @@ mod tests {
             error: Some("测试错误".to_string()),
         };
 
-        assert_eq!(result.ccn_in_path, true);
-        assert_eq!(result.test_notification_sent, false);
+        assert!(result.ccn_in_path);
+        assert!(!result.test_notification_sent);
         assert!(result.error.is_some());
         assert_eq!(result.error.unwrap(), "测试错误");
     }
@@ -523,4 +443,57 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_inject_and_remove_hooks() {
+        let manager = IntegrationManager::new();
+        let test_file = std::env::temp_dir()
+            .join(format!("ccn-test-settings-{}.json", std::process::id()));
+
+        // 用户自己的 Stop hook 和旧版 ccn Stop hook
+        let original = json!({
+            "model": "opus",
+            "hooks": {
+                "Stop": [
+                    { "hooks": [{ "type": "command", "command": "say done" }] },
+                    { "type": "command", "command": "ccn notify --status=success --cmd=done" }
+                ]
+            }
+        });
+        fs::write(&test_file, original.to_string()).unwrap();
+
+        let events = [HookEventKind::Notification, HookEventKind::Stop, HookEventKind::SubagentStop];
+        manager.inject_hooks(&test_file, &events).unwrap();
+        // 重复注入不应产生重复条目
+        manager.inject_hooks(&test_file, &events).unwrap();
+
+        let config: Value = serde_json::from_str(&fs::read_to_string(&test_file).unwrap()).unwrap();
+        let hooks = &config["hooks"];
+
+        assert_eq!(hooks["Notification"].as_array().unwrap().len(), 1);
+        assert_eq!(hooks["Notification"][0]["matcher"], "permission_prompt");
+        assert!(hooks.get("PermissionRequest").is_none());
+
+        // 保留用户的 Stop hook，旧版 ccn hook 被替换为新格式
+        let stop = hooks["Stop"].as_array().unwrap();
+        assert_eq!(stop.len(), 2);
+        assert_eq!(stop[0]["hooks"][0]["command"], "say done");
+        assert!(stop[1].get("matcher").is_none());
+        assert!(is_ccn_entry(&stop[1]));
+
+        assert_eq!(hooks["SubagentStop"].as_array().unwrap().len(), 1);
+        assert_eq!(hooks[SESSION_START_EVENT].as_array().unwrap().len(), 1);
+        assert!(manager.is_integrated(&test_file).unwrap());
+
+        manager.remove_hooks(&test_file).unwrap();
+
+        let config: Value = serde_json::from_str(&fs::read_to_string(&test_file).unwrap()).unwrap();
+        let hooks = config["hooks"].as_object().unwrap();
+        assert_eq!(config["model"], "opus");
+        assert_eq!(hooks.len(), 1);
+        assert_eq!(hooks["Stop"].as_array().unwrap().len(), 1);
+        assert!(!manager.is_integrated(&test_file).unwrap());
+
+        let _ = fs::remove_file(&test_file);
+    }
 }
@@ -2,14 +2,29 @@
 //!
 //! 这是一个为 Claude Code 设计的通知系统，可以在任务完成时发送系统级通知。
 
+#[macro_use]
+pub mod i18n;
 pub mod cli;
 pub mod config;
+pub mod config_edit;
+pub mod config_validate;
+pub mod hook;
+pub mod history;
 pub mod notification;
+pub mod dispatcher;
+pub mod daemon;
 pub mod policy;
+pub mod runner;
+pub mod session;
+pub mod stats;
 pub mod aggregator;
+pub mod state_file;
 pub mod integration;
 pub mod wizard;
+pub mod webhook;
 pub mod sound;
+pub mod template;
+pub mod duration;
 
 #[cfg(windows)]
 pub mod path_manager;
@@ -37,7 +52,7 @@ pub mod platform {
 
     /// 检查是否支持原生通知
     pub fn supports_native_notifications() -> bool {
-        OS_WINDOWS || OS_MACOS
+        OS_WINDOWS || OS_MACOS || OS_LINUX
     }
 
     #[cfg(test)]
@@ -47,7 +62,7 @@ pub mod platform {
         #[test]
         fn test_platform_detection() {
             // 确保只有一个平台被检测为 true
-            let platforms = vec![OS_WINDOWS, OS_MACOS, OS_LINUX];
+            let platforms = [OS_WINDOWS, OS_MACOS, OS_LINUX];
             let detected_count = platforms.iter().filter(|&&p| p).count();
 
             // 应该至少有一个平台被检测到（可能是 unknown）
@@ -64,8 +79,8 @@ pub mod platform {
         #[test]
         fn test_supports_native_notifications() {
             let supported = supports_native_notifications();
-            // Windows 和 macOS 应该支持原生通知
-            if OS_WINDOWS || OS_MACOS {
+            // Windows、macOS 和 Linux (D-Bus) 应该支持原生通知
+            if OS_WINDOWS || OS_MACOS || OS_LINUX {
                 assert!(supported);
             }
         }
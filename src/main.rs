@@ -1,11 +1,26 @@
+#[macro_use]
+mod i18n;
 mod cli;
 mod config;
+mod config_edit;
+mod config_validate;
+mod hook;
+mod history;
 mod notification;
+mod dispatcher;
+mod daemon;
 mod policy;
+mod runner;
+mod session;
+mod stats;
 mod integration;
 mod wizard;
+mod webhook;
 mod aggregator;
+mod state_file;
 mod sound;
+mod template;
+mod duration;
 #[cfg(windows)]
 mod path_manager;
 
@@ -3,15 +3,40 @@
 //! 平台抽象层，处理不同操作系统的通知功能
 
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::dispatcher::CompositeNotificationManager;
 
 /// 通知状态
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum NotificationStatus {
     Success,
     Error,
     Pending,
 }
 
+impl NotificationStatus {
+    /// 状态名称（与 `--status` 参数取值一致）
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationStatus::Success => "success",
+            NotificationStatus::Error => "error",
+            NotificationStatus::Pending => "pending",
+        }
+    }
+}
+
+/// 通知的附加上下文（供 webhook 等需要原始数据的后端使用）
+#[derive(Debug, Clone, Default)]
+pub struct NotificationContext {
+    /// 执行的命令
+    pub cmd: String,
+    /// 任务耗时（秒）
+    pub duration_sec: u64,
+}
+
 /// 通知管理器 trait
 pub trait NotificationManager {
     /// 发送通知
@@ -23,6 +48,18 @@ pub trait NotificationManager {
         duration_ms: u64,
     ) -> Result<()>;
 
+    /// 发送带上下文的通知，默认忽略上下文
+    fn send_with_context(
+        &self,
+        status: NotificationStatus,
+        title: &str,
+        message: &str,
+        duration_ms: u64,
+        _context: &NotificationContext,
+    ) -> Result<()> {
+        self.send_notification(status, title, message, duration_ms)
+    }
+
     /// 检查通知是否可用
     fn is_available(&self) -> bool;
 }
@@ -41,10 +78,21 @@ pub fn get_notification_manager() -> Box<dyn NotificationManager> {
 
     #[cfg(not(any(windows, target_os = "macos")))]
     {
-        Box::new(platform::FallbackNotificationManager)
+        // 会话总线不可达时才降级到控制台输出
+        let manager = platform::LinuxNotificationManager::new();
+        if manager.is_available() {
+            Box::new(manager)
+        } else {
+            Box::new(platform::FallbackNotificationManager)
+        }
     }
 }
 
+/// 根据配置获取通知管理器（按 `notifiers` 分发到多个通知渠道）
+pub fn get_configured_notification_manager(config: &Config) -> Box<dyn NotificationManager> {
+    Box::new(CompositeNotificationManager::from_config(config))
+}
+
 // 平台特定实现
 
 #[cfg(windows)]
@@ -76,11 +124,11 @@ mod platform {
         }
 
         /// 获取状态文本
-        fn get_status_text(status: NotificationStatus) -> &'static str {
+        fn get_status_text(status: NotificationStatus) -> String {
             match status {
-                NotificationStatus::Success => "成功",
-                NotificationStatus::Error => "错误",
-                NotificationStatus::Pending => "进行中",
+                NotificationStatus::Success => t!("notification.status.success"),
+                NotificationStatus::Error => t!("notification.status.error"),
+                NotificationStatus::Pending => t!("notification.status.pending"),
             }
         }
 
@@ -90,7 +138,7 @@ mod platform {
 
             let result = NOTIFIER.get_or_init(|| {
                 ToastNotificationManager::CreateToastNotifierWithId(&HSTRING::from(AUMID))
-                    .map_err(|e| anyhow::anyhow!("创建 ToastNotifier 失败: {:?}", e))
+                    .map_err(|e| anyhow::anyhow!("{}", t!("notification.toast.create_notifier", error = format!("{:?}", e))))
             });
 
             match result {
@@ -113,65 +161,65 @@ mod platform {
             let status_text = Self::get_status_text(status);
             let formatted_title = format!("{}{} - {}", icon, status_text, title);
 
-            log::debug!("准备发送 Windows Toast 通知: {} - {}", formatted_title, message);
+            log::debug!("{}", t!("notification.toast.sending", title = formatted_title, message = message));
 
             // 尝试发送 Toast 通知
             match (|| -> Result<()> {
                 // 获取 Toast XML 模板
                 let toast_xml = ToastNotificationManager::GetTemplateContent(ToastTemplateType::ToastText02)
-                    .map_err(|e| anyhow::anyhow!("获取模板失败: {:?}", e))?;
+                    .map_err(|e| anyhow::anyhow!("{}", t!("notification.toast.template", error = format!("{:?}", e))))?;
 
                 // 填充文本元素
                 let text_elements = toast_xml
                     .GetElementsByTagName(&HSTRING::from("text"))
-                    .map_err(|e| anyhow::anyhow!("获取 text 元素失败: {:?}", e))?;
+                    .map_err(|e| anyhow::anyhow!("{}", t!("notification.toast.text_elements", error = format!("{:?}", e))))?;
 
                 // 设置第一个文本（标题）
                 if let Some(title_node) = text_elements.Item(0).ok() {
                     let title_text = toast_xml
                         .CreateTextNode(&HSTRING::from(&formatted_title))
-                        .map_err(|e| anyhow::anyhow!("创建标题节点失败: {:?}", e))?;
+                        .map_err(|e| anyhow::anyhow!("{}", t!("notification.toast.title_node", error = format!("{:?}", e))))?;
                     let title_node_ref: IXmlNode = title_text
                         .cast()
-                        .map_err(|e| anyhow::anyhow!("转换 IXmlNode 失败: {:?}", e))?;
+                        .map_err(|e| anyhow::anyhow!("{}", t!("notification.toast.cast_node", error = format!("{:?}", e))))?;
                     title_node.AppendChild(&title_node_ref)
-                        .map_err(|e| anyhow::anyhow!("追加标题失败: {:?}", e))?;
+                        .map_err(|e| anyhow::anyhow!("{}", t!("notification.toast.append_title", error = format!("{:?}", e))))?;
                 }
 
                 // 设置第二个文本（消息）
                 if let Some(message_node) = text_elements.Item(1).ok() {
                     let message_text = toast_xml
                         .CreateTextNode(&HSTRING::from(message))
-                        .map_err(|e| anyhow::anyhow!("创建消息节点失败: {:?}", e))?;
+                        .map_err(|e| anyhow::anyhow!("{}", t!("notification.toast.message_node", error = format!("{:?}", e))))?;
                     let message_node_ref: IXmlNode = message_text
                         .cast()
-                        .map_err(|e| anyhow::anyhow!("转换 IXmlNode 失败: {:?}", e))?;
+                        .map_err(|e| anyhow::anyhow!("{}", t!("notification.toast.cast_node", error = format!("{:?}", e))))?;
                     message_node.AppendChild(&message_node_ref)
-                        .map_err(|e| anyhow::anyhow!("追加消息失败: {:?}", e))?;
+                        .map_err(|e| anyhow::anyhow!("{}", t!("notification.toast.append_message", error = format!("{:?}", e))))?;
                 }
 
                 // 创建 Toast 通知对象
                 let toast = ToastNotification::CreateToastNotification(&toast_xml)
-                    .map_err(|e| anyhow::anyhow!("创建 Toast 通知失败: {:?}", e))?;
+                    .map_err(|e| anyhow::anyhow!("{}", t!("notification.toast.create", error = format!("{:?}", e))))?;
 
                 // 获取 notifier 并显示通知
                 let notifier = Self::get_notifier()?;
                 notifier.Show(&toast)
-                    .map_err(|e| anyhow::anyhow!("显示通知失败: {:?}", e))?;
+                    .map_err(|e| anyhow::anyhow!("{}", t!("notification.toast.show", error = format!("{:?}", e))))?;
 
                 Ok(())
             })() {
                 Ok(_) => {
-                    log::info!("Toast 通知已发送: {}", formatted_title);
+                    log::info!("{}", t!("notification.toast.sent", title = formatted_title));
                     // 等待通知显示，确保 Windows 有足够时间处理
                     std::thread::sleep(std::time::Duration::from_secs(2));
                     Ok(())
                 }
                 Err(e) => {
-                    log::warn!("Toast 通知失败: {}, 降级到控制台输出", e);
+                    log::warn!("{}", t!("notification.toast.failed", error = e));
                     // 降级到控制台输出
                     let icon = Self::get_status_icon(status);
-                    println!("[通知] {} {}: {}", icon, title, message);
+                    println!("[{}] {} {}: {}", t!("notification.console"), icon, title, message);
                     Ok(())
                 }
             }
@@ -224,11 +272,11 @@ mod platform {
                 .output()?;
 
             if output.status.success() {
-                log::info!("使用 terminal-notifier 发送通知成功");
+                log::info!("{}", t!("notification.macos.sent", tool = "terminal-notifier"));
                 Ok(())
             } else {
                 let stderr = String::from_utf8_lossy(&output.stderr);
-                Err(anyhow::anyhow!("terminal-notifier 失败: {}", stderr))
+                Err(anyhow::anyhow!("{}", t!("notification.macos.tool_failed", tool = "terminal-notifier", error = stderr)))
             }
         }
 
@@ -246,11 +294,11 @@ mod platform {
                 .output()?;
 
             if output.status.success() {
-                log::info!("使用 osascript 发送通知成功");
+                log::info!("{}", t!("notification.macos.sent", tool = "osascript"));
                 Ok(())
             } else {
                 let stderr = String::from_utf8_lossy(&output.stderr);
-                Err(anyhow::anyhow!("osascript 失败: {}", stderr))
+                Err(anyhow::anyhow!("{}", t!("notification.macos.tool_failed", tool = "osascript", error = stderr)))
             }
         }
     }
@@ -285,7 +333,7 @@ mod platform {
                 match Self::send_with_terminal_notifier(&formatted_title, &formatted_message, sound) {
                     Ok(_) => return Ok(()),
                     Err(e) => {
-                        log::warn!("terminal-notifier 失败，尝试 osascript: {}", e);
+                        log::warn!("{}", t!("notification.macos.fallback_osascript", error = e));
                     }
                 }
             }
@@ -295,8 +343,8 @@ mod platform {
                 Ok(_) => Ok(()),
                 Err(e) => {
                     // 最后的后备方案：控制台输出
-                    log::warn!("macOS 通知失败: {}, 降级到控制台输出", e);
-                    println!("[macOS 通知] {} {}: {}", icon, formatted_title, formatted_message);
+                    log::warn!("{}", t!("notification.macos.failed", error = e));
+                    println!("[{}] {} {}: {}", t!("notification.console_macos"), icon, formatted_title, formatted_message);
                     Ok(())
                 }
             }
@@ -312,8 +360,149 @@ mod platform {
 #[cfg(not(any(windows, target_os = "macos")))]
 mod platform {
     use super::{NotificationManager, NotificationStatus};
-    use anyhow::Result;
+    use anyhow::{Context, Result};
+    use std::collections::HashMap;
+    use zbus::blocking::Connection;
+    use zbus::zvariant::Value;
+
+    /// freedesktop 通知服务的总线名称、对象路径和接口
+    const NOTIFICATIONS_DEST: &str = "org.freedesktop.Notifications";
+    const NOTIFICATIONS_PATH: &str = "/org/freedesktop/Notifications";
+    const NOTIFICATIONS_IFACE: &str = "org.freedesktop.Notifications";
+
+    /// 应用名称（显示在通知中心）
+    const APP_NAME: &str = "Claude Code Notify";
+
+    /// 通知紧急程度（freedesktop 规范中的 urgency hint）
+    const URGENCY_NORMAL: u8 = 1;
+    const URGENCY_CRITICAL: u8 = 2;
+
+    /// Linux 通知管理器（通过 D-Bus 调用 org.freedesktop.Notifications）
+    pub struct LinuxNotificationManager {
+        connection: Option<Connection>,
+    }
+
+    impl Default for LinuxNotificationManager {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl LinuxNotificationManager {
+        /// 连接到当前会话总线
+        pub fn new() -> Self {
+            let connection = match Connection::session() {
+                Ok(connection) => Some(connection),
+                Err(e) => {
+                    log::debug!("{}", t!("notification.dbus.no_session", error = e));
+                    None
+                }
+            };
+
+            Self { connection }
+        }
+
+        /// 连接到指定地址的总线（如私有 dbus-daemon）
+        #[cfg(test)]
+        pub fn with_address(address: &str) -> Result<Self> {
+            let connection = zbus::blocking::connection::Builder::address(address)
+                .and_then(|builder| builder.build())
+                .with_context(|| format!("无法连接到 D-Bus 总线: {}", address))?;
+
+            Ok(Self {
+                connection: Some(connection),
+            })
+        }
+
+        /// 获取状态对应的图标（freedesktop 图标命名规范）
+        pub(crate) fn get_status_icon(status: NotificationStatus) -> &'static str {
+            match status {
+                NotificationStatus::Success => "dialog-information",
+                NotificationStatus::Error => "dialog-error",
+                NotificationStatus::Pending => "dialog-question",
+            }
+        }
+
+        /// 获取状态对应的紧急程度
+        pub(crate) fn get_urgency(status: NotificationStatus) -> u8 {
+            match status {
+                NotificationStatus::Error => URGENCY_CRITICAL,
+                NotificationStatus::Success | NotificationStatus::Pending => URGENCY_NORMAL,
+            }
+        }
+
+        /// 调用 Notify 方法，返回通知 ID
+        fn notify(
+            connection: &Connection,
+            status: NotificationStatus,
+            title: &str,
+            message: &str,
+            duration_ms: u64,
+        ) -> Result<u32> {
+            let actions: Vec<&str> = Vec::new();
+            let mut hints: HashMap<&str, Value> = HashMap::new();
+            hints.insert("urgency", Value::U8(Self::get_urgency(status)));
+
+            // expire_timeout 为毫秒，0 表示永不过期，超出 i32 范围时截断
+            let expire_timeout = i32::try_from(duration_ms).unwrap_or(i32::MAX);
+
+            let reply = connection
+                .call_method(
+                    Some(NOTIFICATIONS_DEST),
+                    NOTIFICATIONS_PATH,
+                    Some(NOTIFICATIONS_IFACE),
+                    "Notify",
+                    &(
+                        APP_NAME,
+                        0u32,
+                        Self::get_status_icon(status),
+                        title,
+                        message,
+                        actions,
+                        hints,
+                        expire_timeout,
+                    ),
+                )
+                .with_context(|| t!("notification.dbus.call_failed"))?;
+
+            let id: u32 = reply.body().deserialize().with_context(|| t!("notification.dbus.parse_id"))?;
+            Ok(id)
+        }
+    }
+
+    impl NotificationManager for LinuxNotificationManager {
+        fn send_notification(
+            &self,
+            status: NotificationStatus,
+            title: &str,
+            message: &str,
+            duration_ms: u64,
+        ) -> Result<()> {
+            let Some(connection) = &self.connection else {
+                return FallbackNotificationManager.send_notification(status, title, message, duration_ms);
+            };
+
+            log::debug!("{}", t!("notification.dbus.sending", title = title, message = message));
+
+            match Self::notify(connection, status, title, message, duration_ms) {
+                Ok(id) => {
+                    log::info!("{}", t!("notification.dbus.sent", id = id, title = title));
+                    Ok(())
+                }
+                Err(e) => {
+                    // 总线可达但没有通知服务时，降级到控制台输出
+                    log::warn!("{}", t!("notification.dbus.failed", error = format!("{:#}", e)));
+                    FallbackNotificationManager.send_notification(status, title, message, duration_ms)
+                }
+            }
+        }
+
+        fn is_available(&self) -> bool {
+            self.connection.is_some()
+        }
+    }
 
+    /// 后备通知管理器（输出到终端）
     pub struct FallbackNotificationManager;
 
     impl NotificationManager for FallbackNotificationManager {
@@ -331,8 +520,8 @@ mod platform {
                 NotificationStatus::Pending => "⏳",
             };
 
-            log::info!("后备通知: {} {} - {}", icon, title, message);
-            println!("[通知] {} {}: {}", icon, title, message);
+            log::info!("{}", t!("notification.fallback", icon = icon, title = title, message = message));
+            println!("[{}] {} {}: {}", t!("notification.console"), icon, title, message);
             Ok(())
         }
 
@@ -341,3 +530,186 @@ mod platform {
         }
     }
 }
+
+#[cfg(all(test, not(any(windows, target_os = "macos"))))]
+mod tests {
+    use super::platform::LinuxNotificationManager;
+    use super::{NotificationManager, NotificationStatus};
+    use std::collections::HashMap;
+    use std::io::{BufRead, BufReader};
+    use std::process::{Child, Command, Stdio};
+    use std::sync::{Arc, Mutex};
+    use zbus::zvariant::{OwnedValue, Value};
+
+    /// 私有 dbus-daemon 配置（仅监听临时 socket，允许任意名称）
+    const BUS_CONFIG: &str = r#"<!DOCTYPE busconfig PUBLIC "-//freedesktop//DTD D-Bus Bus Configuration 1.0//EN"
+ "http://www.freedesktop.org/standards/dbus/1.0/busconfig.dtd">
+<busconfig>
+  <type>session</type>
+  <listen>unix:tmpdir=/tmp</listen>
+  <auth>EXTERNAL</auth>
+  <policy context="default">
+    <allow send_destination="*" eavesdrop="true"/>
+    <allow eavesdrop="true"/>
+    <allow own="*"/>
+  </policy>
+</busconfig>
+"#;
+
+    /// 测试期间运行的私有 dbus-daemon
+    struct PrivateBus {
+        child: Child,
+        address: String,
+    }
+
+    impl PrivateBus {
+        /// 启动私有总线，系统未安装 dbus-daemon 时返回 None
+        fn start() -> Option<Self> {
+            let config_path = std::env::temp_dir()
+                .join(format!("ccn-test-bus-{}.conf", std::process::id()));
+            std::fs::write(&config_path, BUS_CONFIG).ok()?;
+
+            let mut child = Command::new("dbus-daemon")
+                .arg(format!("--config-file={}", config_path.display()))
+                .arg("--nofork")
+                .arg("--print-address=1")
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+                .ok()?;
+
+            let mut address = String::new();
+            BufReader::new(child.stdout.take()?).read_line(&mut address).ok()?;
+            let _ = std::fs::remove_file(&config_path);
+
+            Some(Self {
+                child,
+                address: address.trim().to_string(),
+            })
+        }
+    }
+
+    impl Drop for PrivateBus {
+        fn drop(&mut self) {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+
+    /// 收到的通知
+    #[derive(Debug, Clone)]
+    struct ReceivedNotification {
+        app_icon: String,
+        summary: String,
+        body: String,
+        urgency: Option<u8>,
+        expire_timeout: i32,
+    }
+
+    /// 模拟的 org.freedesktop.Notifications 服务
+    struct MockNotificationServer {
+        received: Arc<Mutex<Vec<ReceivedNotification>>>,
+    }
+
+    #[zbus::interface(name = "org.freedesktop.Notifications")]
+    impl MockNotificationServer {
+        #[allow(clippy::too_many_arguments)]
+        fn notify(
+            &self,
+            _app_name: String,
+            _replaces_id: u32,
+            app_icon: String,
+            summary: String,
+            body: String,
+            _actions: Vec<String>,
+            hints: HashMap<String, OwnedValue>,
+            expire_timeout: i32,
+        ) -> u32 {
+            let urgency = hints.get("urgency").and_then(|v| match &**v {
+                Value::U8(u) => Some(*u),
+                _ => None,
+            });
+
+            let mut received = self.received.lock().unwrap();
+            received.push(ReceivedNotification {
+                app_icon,
+                summary,
+                body,
+                urgency,
+                expire_timeout,
+            });
+            received.len() as u32
+        }
+    }
+
+    #[test]
+    fn test_status_icon_and_urgency() {
+        assert_eq!(LinuxNotificationManager::get_status_icon(NotificationStatus::Success), "dialog-information");
+        assert_eq!(LinuxNotificationManager::get_status_icon(NotificationStatus::Error), "dialog-error");
+        assert_eq!(LinuxNotificationManager::get_status_icon(NotificationStatus::Pending), "dialog-question");
+
+        assert_eq!(LinuxNotificationManager::get_urgency(NotificationStatus::Success), 1);
+        assert_eq!(LinuxNotificationManager::get_urgency(NotificationStatus::Error), 2);
+        assert_eq!(LinuxNotificationManager::get_urgency(NotificationStatus::Pending), 1);
+    }
+
+    #[test]
+    fn test_send_notification_over_dbus() {
+        let Some(bus) = PrivateBus::start() else {
+            eprintln!("未找到 dbus-daemon，跳过测试");
+            return;
+        };
+
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let _server = zbus::blocking::connection::Builder::address(bus.address.as_str())
+            .unwrap()
+            .name("org.freedesktop.Notifications")
+            .unwrap()
+            .serve_at(
+                "/org/freedesktop/Notifications",
+                MockNotificationServer {
+                    received: Arc::clone(&received),
+                },
+            )
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let manager = LinuxNotificationManager::with_address(&bus.address).unwrap();
+        assert!(manager.is_available());
+
+        manager
+            .send_notification(NotificationStatus::Error, "任务失败", "cargo build (耗时: 12秒)", 8000)
+            .unwrap();
+        manager
+            .send_notification(NotificationStatus::Success, "任务完成", "npm test", 5000)
+            .unwrap();
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 2);
+
+        assert_eq!(received[0].summary, "任务失败");
+        assert_eq!(received[0].body, "cargo build (耗时: 12秒)");
+        assert_eq!(received[0].app_icon, "dialog-error");
+        assert_eq!(received[0].urgency, Some(2));
+        assert_eq!(received[0].expire_timeout, 8000);
+
+        assert_eq!(received[1].app_icon, "dialog-information");
+        assert_eq!(received[1].urgency, Some(1));
+        assert_eq!(received[1].expire_timeout, 5000);
+    }
+
+    #[test]
+    fn test_send_without_notification_server_falls_back() {
+        let Some(bus) = PrivateBus::start() else {
+            eprintln!("未找到 dbus-daemon，跳过测试");
+            return;
+        };
+
+        // 总线可达但没有通知服务，应降级到控制台输出而不是报错
+        let manager = LinuxNotificationManager::with_address(&bus.address).unwrap();
+        assert!(manager
+            .send_notification(NotificationStatus::Pending, "需要授权", "Write", 5000)
+            .is_ok());
+    }
+}
@@ -19,7 +19,7 @@ impl PathManager {
 
         // 2. 检查是否已存在
         if Self::contains_path(&current_path, directory) {
-            log::info!("PATH 已包含: {:?}", directory);
+            log::info!("{}", t!("setup.path.log.already_present", path = format!("{:?}", directory)));
             return Ok(false);
         }
 
@@ -32,7 +32,7 @@ impl PathManager {
         // 5. 通知系统环境变量已更改
         Self::notify_environment_change();
 
-        log::info!("已添加到 PATH: {:?}", directory);
+        log::info!("{}", t!("setup.path.log.added", path = format!("{:?}", directory)));
         Ok(true)
     }
 
@@ -40,7 +40,7 @@ impl PathManager {
     #[cfg(not(windows))]
     pub fn add_to_path(_directory: &PathBuf) -> Result<bool> {
         // Unix 系统通常通过包管理器安装，已在 PATH 中
-        log::info!("非 Windows 平台，跳过 PATH 修改");
+        log::info!("{}", t!("setup.path.log.skipped"));
         Ok(false)
     }
 
@@ -52,7 +52,7 @@ impl PathManager {
 
         // 2. 检查是否存在
         if !Self::contains_path(&current_path, directory) {
-            log::info!("PATH 中不存在: {:?}", directory);
+            log::info!("{}", t!("setup.path.log.not_present", path = format!("{:?}", directory)));
             return Ok(false);
         }
 
@@ -65,14 +65,14 @@ impl PathManager {
         // 5. 通知系统环境变量已更改
         Self::notify_environment_change();
 
-        log::info!("已从 PATH 移除: {:?}", directory);
+        log::info!("{}", t!("setup.path.log.removed", path = format!("{:?}", directory)));
         Ok(true)
     }
 
     /// 从用户 PATH 中移除指定目录 (非 Windows 平台)
     #[cfg(not(windows))]
     pub fn remove_from_path(_directory: &PathBuf) -> Result<bool> {
-        log::info!("非 Windows 平台，跳过 PATH 修改");
+        log::info!("{}", t!("setup.path.log.skipped"));
         Ok(false)
     }
 
@@ -1,50 +1,441 @@
 //! 智能策略引擎模块
 //!
-//! 处理通知阈值过滤、聚合和场景模板匹配
+//! 处理通知阈值过滤、免打扰时间表、聚合和场景模板匹配
 
-use crate::config::Config;
+use chrono::{DateTime, Local, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use regex::Regex;
+
+use crate::config::{
+    Anchor, Config, FocusAssistantMode, MatchMode, MatchRule, QuietAction, RuleAction, RuleConfig,
+    TimeRange,
+};
 use crate::notification::NotificationStatus;
 
+/// 编译后的匹配规则
+struct CompiledRule {
+    name: String,
+    rule: MatchRule,
+    /// 规则无效时为编译错误
+    regex: Result<Regex, String>,
+}
+
+impl CompiledRule {
+    fn new(name: String, rule: MatchRule) -> Self {
+        let regex = compile_rule(&rule).map_err(|e| e.to_string());
+        if let Err(e) = &regex {
+            log::warn!("{}", t!("policy.log.invalid_rule", name = name, error = e));
+        }
+        Self { name, rule, regex }
+    }
+
+    fn is_match(&self, cmd: &str) -> bool {
+        self.regex.as_ref().is_ok_and(|regex| regex.is_match(cmd.trim()))
+    }
+
+    fn trace(&self, cmd: &str) -> RuleTrace {
+        RuleTrace {
+            name: self.name.clone(),
+            rule: self.rule.to_string(),
+            matched: self.is_match(cmd),
+            error: self.regex.as_ref().err().cloned(),
+        }
+    }
+}
+
+/// 将匹配规则编译为正则表达式
+pub(crate) fn compile_rule(rule: &MatchRule) -> Result<Regex, regex::Error> {
+    let (body, anchor) = match rule.mode {
+        MatchMode::Substring => (regex::escape(&rule.pattern), rule.anchor),
+        MatchMode::Regex => (rule.pattern.clone(), rule.anchor),
+        MatchMode::Glob => (glob_to_regex(&rule.pattern), Anchor::Both),
+        MatchMode::Exact => (regex::escape(&rule.pattern), Anchor::Both),
+    };
+
+    let pattern = match anchor {
+        Anchor::None => body,
+        Anchor::Start => format!("^(?:{})", body),
+        Anchor::End => format!("(?:{})$", body),
+        Anchor::Both => format!("^(?:{})$", body),
+    };
+
+    Regex::new(&pattern)
+}
+
+/// 将 glob（`*`、`?`）转换为正则表达式
+fn glob_to_regex(glob: &str) -> String {
+    let mut pattern = String::new();
+    for c in glob.chars() {
+        match c {
+            '*' => pattern.push_str(".*"),
+            '?' => pattern.push('.'),
+            c => pattern.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+        }
+    }
+    pattern
+}
+
+/// 单条规则的匹配结果
+#[derive(Debug, Clone)]
+pub struct RuleTrace {
+    pub name: String,
+    pub rule: String,
+    pub matched: bool,
+    pub error: Option<String>,
+}
+
+/// 规则求值的输入
+#[derive(Debug, Clone)]
+pub struct RuleContext<'a> {
+    pub status: NotificationStatus,
+    /// 耗时（秒），0 表示未知
+    pub duration: u64,
+    pub cmd: &'a str,
+    /// 项目路径（工作目录）
+    pub project_path: Option<&'a str>,
+    /// 触发通知的 hook 事件名称
+    pub event: Option<&'a str>,
+    pub now: DateTime<Utc>,
+}
+
+/// 规则求值结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleOutcome {
+    /// 命中的规则名称，None 表示使用默认动作
+    pub rule: Option<String>,
+    pub action: RuleAction,
+    pub force_sound: bool,
+    pub sinks: Vec<String>,
+}
+
+impl RuleOutcome {
+    pub fn should_notify(&self) -> bool {
+        self.action != RuleAction::Drop
+    }
+}
+
+/// 编译后的通知规则
+struct CompiledPolicyRule {
+    name: String,
+    config: RuleConfig,
+    cmd: Option<CompiledRule>,
+    project: Option<CompiledRule>,
+}
+
+impl CompiledPolicyRule {
+    fn new(index: usize, config: RuleConfig) -> Self {
+        let name = config.name.clone().unwrap_or_else(|| format!("#{}", index + 1));
+        let cmd = config.when.cmd.clone().map(|rule| CompiledRule::new(format!("{} cmd", name), rule));
+        let project = config
+            .when
+            .project
+            .clone()
+            .map(|rule| CompiledRule::new(format!("{} project", name), rule));
+
+        Self {
+            name,
+            config,
+            cmd,
+            project,
+        }
+    }
+
+    /// 所有条件都满足才算命中；包含无效模式的规则永不命中
+    fn matches(&self, context: &RuleContext, weekday: Weekday, time: NaiveTime) -> bool {
+        let when = &self.config.when;
+
+        (when.status.is_empty() || when.status.contains(&context.status))
+            && when.duration.is_none_or(|range| range.contains(context.duration))
+            && self.cmd.as_ref().is_none_or(|rule| rule.is_match(context.cmd))
+            && self
+                .project
+                .as_ref()
+                .is_none_or(|rule| context.project_path.is_some_and(|path| rule.is_match(path)))
+            && (when.event.is_empty()
+                || context.event.is_some_and(|event| when.event.iter().any(|e| e == event)))
+            && when.time.as_ref().is_none_or(|range| time_range_contains(range, weekday, time))
+    }
+
+    fn outcome(&self) -> RuleOutcome {
+        RuleOutcome {
+            rule: Some(self.name.clone()),
+            action: self.config.action,
+            force_sound: self.config.force_sound,
+            sinks: self.config.sinks.clone(),
+        }
+    }
+
+    fn error(&self) -> Option<String> {
+        [&self.cmd, &self.project]
+            .into_iter()
+            .flatten()
+            .find_map(|rule| rule.regex.as_ref().err().cloned())
+    }
+
+    /// 规则的简短描述，如 `status=[error] duration=10..60 → notify`
+    fn describe(&self) -> String {
+        let when = &self.config.when;
+        let mut parts = Vec::new();
+
+        if !when.status.is_empty() {
+            let statuses: Vec<&str> = when.status.iter().map(|s| s.as_str()).collect();
+            parts.push(format!("status=[{}]", statuses.join(",")));
+        }
+        if let Some(range) = when.duration {
+            let bound = |value: Option<u64>| value.map(|v| v.to_string()).unwrap_or_default();
+            parts.push(format!("duration={}..{}", bound(range.min), bound(range.max)));
+        }
+        if let Some(cmd) = &when.cmd {
+            parts.push(format!("cmd={}", cmd));
+        }
+        if let Some(project) = &when.project {
+            parts.push(format!("project={}", project));
+        }
+        if !when.event.is_empty() {
+            parts.push(format!("event=[{}]", when.event.join(",")));
+        }
+        if let Some(range) = &when.time {
+            let days: Vec<String> = range.days.iter().map(|d| d.to_string()).collect();
+            parts.push(format!(
+                "time={}-{}{}",
+                range.start.format("%H:%M"),
+                range.end.format("%H:%M"),
+                if days.is_empty() { String::new() } else { format!("[{}]", days.join(",")) }
+            ));
+        }
+        if parts.is_empty() {
+            parts.push("*".to_string());
+        }
+
+        let mut description = format!("{} → {:?}", parts.join(" "), self.config.action).to_lowercase();
+        if self.config.force_sound {
+            description.push_str(" +sound");
+        }
+        if !self.config.sinks.is_empty() {
+            description.push_str(&format!(" sinks=[{}]", self.config.sinks.join(",")));
+        }
+        description
+    }
+}
+
+/// `ccn policy explain` 的结果
+#[derive(Debug, Clone)]
+pub struct PolicyExplanation {
+    /// 通知规则（按顺序）
+    pub rules: Vec<RuleTrace>,
+    /// 模板规则（按优先级）
+    pub templates: Vec<RuleTrace>,
+    /// 最终使用的模板
+    pub template: String,
+    pub outcome: RuleOutcome,
+    pub quiet_action: Option<QuietAction>,
+}
+
 /// 策略引擎
 pub struct PolicyEngine {
     config: Config,
+    /// 按顺序求值的通知规则
+    rules: Vec<CompiledPolicyRule>,
+    /// 按优先级排序的模板规则
+    templates: Vec<CompiledRule>,
+    /// `schedule.timezone` 解析后的时区，None 表示使用系统时区
+    timezone: Option<Tz>,
 }
 
 impl PolicyEngine {
     pub fn new(config: Config) -> Self {
-        Self { config }
+        let rules = config
+            .effective_rules()
+            .into_iter()
+            .enumerate()
+            .map(|(index, rule)| CompiledPolicyRule::new(index, rule))
+            .collect();
+
+        // 优先级高的在前，相同优先级按模板名排序，保证匹配顺序确定
+        let mut custom: Vec<_> = config.templates.custom.iter().collect();
+        custom.sort_by(|(a_name, a), (b_name, b)| {
+            b.priority.cmp(&a.priority).then_with(|| a_name.cmp(b_name))
+        });
+        let templates = custom
+            .into_iter()
+            .map(|(name, template)| {
+                let rule = template.matcher.clone().unwrap_or_else(|| MatchRule::substring(name));
+                CompiledRule::new(name.clone(), rule)
+            })
+            .collect();
+
+        // 配置校验会拒绝无法识别的时区，这里只在未经校验的配置中兜底
+        let timezone = match config.schedule.timezone.as_str() {
+            "local" => None,
+            timezone => timezone
+                .parse::<Tz>()
+                .inspect_err(|_| log::warn!("{}", t!("policy.log.invalid_timezone", timezone = timezone)))
+                .ok(),
+        };
+
+        Self {
+            config,
+            rules,
+            templates,
+            timezone,
+        }
     }
 
-    /// 检查是否应该发送通知（基于阈值和策略）
+    /// 检查是否应该发送通知（只考虑状态、耗时和命令）
+    #[cfg(test)]
     pub fn should_notify(&self, status: NotificationStatus, duration_sec: u64, cmd: &str) -> bool {
-        // 错误状态强制通知
-        if matches!(status, NotificationStatus::Error) {
-            return true;
+        let context = RuleContext {
+            status,
+            duration: duration_sec,
+            cmd,
+            project_path: None,
+            event: None,
+            now: Utc::now(),
+        };
+        self.evaluate(&context).should_notify()
+    }
+
+    /// 按顺序求值通知规则，首个命中的规则生效，没有命中时使用默认动作
+    pub fn evaluate(&self, context: &RuleContext) -> RuleOutcome {
+        let (weekday, time) = self.local_weekday_time(context.now);
+
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(context, weekday, time))
+            .map(|rule| rule.outcome())
+            .unwrap_or_else(|| RuleOutcome {
+                rule: None,
+                action: self.config.default_action,
+                force_sound: false,
+                sinks: vec![],
+            })
+    }
+
+    /// 解释一条通知的策略匹配过程
+    pub fn explain(&self, context: &RuleContext) -> PolicyExplanation {
+        let (weekday, time) = self.local_weekday_time(context.now);
+
+        PolicyExplanation {
+            rules: self
+                .rules
+                .iter()
+                .map(|rule| RuleTrace {
+                    name: rule.name.clone(),
+                    rule: rule.describe(),
+                    matched: rule.matches(context, weekday, time),
+                    error: rule.error(),
+                })
+                .collect(),
+            templates: self.templates.iter().map(|rule| rule.trace(context.cmd)).collect(),
+            template: self.match_template(context.cmd).unwrap_or_else(|| "default".to_string()),
+            outcome: self.evaluate(context),
+            quiet_action: self.quiet_action(context.status, context.now),
         }
+    }
+
+    /// 获取当前免打扰时段对该状态的处理方式，不在免打扰时段时返回 None
+    ///
+    /// 多个时段同时生效时取第一个；`focus_assistant_mode` 为 `always` 时忽略免打扰时段，
+    /// 为 `never` 时 `silence` 也按 `suppress` 处理
+    pub fn quiet_action(&self, status: NotificationStatus, now: DateTime<Utc>) -> Option<QuietAction> {
+        if self.config.focus_assistant_mode == FocusAssistantMode::Always {
+            return None;
+        }
+        let (weekday, time) = self.local_weekday_time(now);
+
+        let action = self
+            .config
+            .schedule
+            .quiet_hours
+            .iter()
+            .filter(|range| !range.except.contains(&status))
+            .find(|range| time_range_contains(&range.range, weekday, time))
+            .map(|range| range.action)?;
+
+        match (&self.config.focus_assistant_mode, action) {
+            (FocusAssistantMode::Never, QuietAction::Silence) => Some(QuietAction::Suppress),
+            _ => Some(action),
+        }
+    }
+
+    /// 当前是否处于 defer 时段（用于判断暂存的通知是否可以发送）
+    pub fn in_defer_period(&self, now: DateTime<Utc>) -> bool {
+        if self.config.focus_assistant_mode == FocusAssistantMode::Always {
+            return false;
+        }
+        let (weekday, time) = self.local_weekday_time(now);
+
+        self.config
+            .schedule
+            .quiet_hours
+            .iter()
+            .any(|range| range.action == QuietAction::Defer && time_range_contains(&range.range, weekday, time))
+    }
 
-        // duration 为 0 表示未知/不可用（如 hooks 场景），跳过阈值检查
-        if duration_sec == 0 {
-            return true;
+    /// 当前 defer 时段的结束时间（用于安排摘要的发送），不在 defer 时段时返回 None
+    ///
+    /// 重叠或相邻的 defer 时段视为一段；一周内都不结束时返回 None
+    pub fn defer_period_end(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        if !self.in_defer_period(now) {
+            return None;
         }
 
-        // 检查白名单
-        for whitelist_cmd in &self.config.threshold.whitelist {
-            if cmd.contains(whitelist_cmd) {
-                return true;
-            }
+        // 只有在某个时段的结束时刻或午夜（全天时段）才可能离开 defer 时段
+        let mut boundaries: Vec<NaiveTime> = self
+            .config
+            .schedule
+            .quiet_hours
+            .iter()
+            .filter(|range| range.action == QuietAction::Defer)
+            .map(|range| range.range.end)
+            .chain([NaiveTime::MIN])
+            .collect();
+        boundaries.sort();
+        boundaries.dedup();
+
+        let today = self.utc_to_local(now).date();
+        today
+            .iter_days()
+            .take(8)
+            .flat_map(|date| boundaries.iter().map(move |time| date.and_time(*time)))
+            .filter_map(|local| self.local_to_utc(local))
+            .filter(|end| *end > now)
+            .find(|end| !self.in_defer_period(*end))
+    }
+
+    /// 检查是否应该播放声音：需要启用声音，且不处于（按 `focus_assistant_mode` 生效的）免打扰时段
+    pub fn should_play_sound(&self, status: NotificationStatus, now: DateTime<Utc>) -> bool {
+        self.config.sound_enabled && self.quiet_action(status, now).is_none()
+    }
+
+    /// 将时间转换为配置时区的本地时间
+    fn utc_to_local(&self, now: DateTime<Utc>) -> NaiveDateTime {
+        match self.timezone {
+            Some(tz) => now.with_timezone(&tz).naive_local(),
+            None => now.with_timezone(&Local).naive_local(),
         }
+    }
 
-        // 检查时间阈值
-        duration_sec >= self.config.threshold.min_duration
+    /// 将配置时区的本地时间转换为 UTC，夏令时跳过的时刻返回 None
+    fn local_to_utc(&self, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+        match self.timezone {
+            Some(tz) => tz.from_local_datetime(&local).earliest().map(|time| time.with_timezone(&Utc)),
+            None => Local.from_local_datetime(&local).earliest().map(|time| time.with_timezone(&Utc)),
+        }
     }
 
-    /// 匹配场景模板
+    /// 将时间转换到配置的时区，返回星期和时刻
+    fn local_weekday_time(&self, now: DateTime<Utc>) -> (Weekday, NaiveTime) {
+        use chrono::Datelike;
+
+        let local = self.utc_to_local(now);
+        (local.weekday(), local.time())
+    }
+
+    /// 匹配场景模板（按优先级，首个命中的模板生效）
     pub fn match_template(&self, cmd: &str) -> Option<String> {
-        // 尝试匹配自定义模板
-        for (name, _) in &self.config.templates.custom {
-            if cmd.contains(name) {
-                return Some(name.clone());
-            }
+        if let Some(rule) = self.templates.iter().find(|rule| rule.is_match(cmd)) {
+            return Some(rule.name.clone());
         }
 
         // 返回默认模板
@@ -64,9 +455,25 @@ impl PolicyEngine {
     }
 }
 
+/// 检查时刻是否落在时间段内
+///
+/// 跨午夜的时段（如 22:00-08:00）按开始那天的星期判断，即周五 22:00 开始的时段覆盖周六凌晨
+fn time_range_contains(range: &TimeRange, weekday: Weekday, time: NaiveTime) -> bool {
+    let active_on = |day: Weekday| range.days.is_empty() || range.days.contains(&day);
+
+    if range.start < range.end {
+        active_on(weekday) && range.start <= time && time < range.end
+    } else if range.start > range.end {
+        (active_on(weekday) && time >= range.start) || (active_on(weekday.pred()) && time < range.end)
+    } else {
+        active_on(weekday)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::TimeZone;
 
     #[test]
     fn test_should_notify_error() {
@@ -93,7 +500,7 @@ mod tests {
     #[test]
     fn test_whitelist() {
         let mut config = Config::default();
-        config.threshold.whitelist = vec!["deploy".to_string()];
+        config.threshold.whitelist = vec!["deploy".into()];
         let engine = PolicyEngine::new(config);
 
         // 白名单命令即使短也通知
@@ -108,7 +515,12 @@ mod tests {
             crate::config::TemplateConfig {
                 icon: "build.png".to_string(),
                 sound: "build.wav".to_string(),
+                sounds: crate::config::StatusSounds::default(),
                 duration: 8000,
+                title: None,
+                body: None,
+                matcher: None,
+                priority: 0,
             },
         );
         let engine = PolicyEngine::new(config);
@@ -119,4 +531,307 @@ mod tests {
         // 默认模板
         assert_eq!(engine.match_template("npm test"), Some("default".to_string()));
     }
+
+    fn quiet_config(days: Vec<Weekday>, start: &str, end: &str, action: QuietAction) -> Config {
+        let mut config = Config::default();
+        config.schedule.timezone = "UTC".to_string();
+        config.schedule.quiet_hours = vec![crate::config::QuietHoursConfig {
+            range: TimeRange {
+                days,
+                start: start.parse().unwrap(),
+                end: end.parse().unwrap(),
+            },
+            action,
+            except: vec![NotificationStatus::Error],
+        }];
+        config
+    }
+
+    /// 2026-01-02 是周五
+    fn utc(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 1, day, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_quiet_hours_overnight() {
+        let engine = PolicyEngine::new(quiet_config(vec![], "22:00", "08:00", QuietAction::Suppress));
+
+        assert_eq!(engine.quiet_action(NotificationStatus::Success, utc(2, 23, 0)), Some(QuietAction::Suppress));
+        assert_eq!(engine.quiet_action(NotificationStatus::Success, utc(3, 7, 59)), Some(QuietAction::Suppress));
+        assert_eq!(engine.quiet_action(NotificationStatus::Success, utc(3, 8, 0)), None);
+        assert_eq!(engine.quiet_action(NotificationStatus::Success, utc(2, 21, 59)), None);
+
+        // 错误不受影响
+        assert_eq!(engine.quiet_action(NotificationStatus::Error, utc(2, 23, 0)), None);
+    }
+
+    #[test]
+    fn test_defer_period_end() {
+        let mut config = quiet_config(vec![], "22:00", "08:00", QuietAction::Defer);
+        assert_eq!(PolicyEngine::new(config.clone()).defer_period_end(utc(2, 23, 0)), Some(utc(3, 8, 0)));
+        assert_eq!(PolicyEngine::new(config.clone()).defer_period_end(utc(3, 7, 59)), Some(utc(3, 8, 0)));
+        assert_eq!(PolicyEngine::new(config.clone()).defer_period_end(utc(3, 12, 0)), None);
+
+        // 相邻的时段合并：周末全天 defer 接在周五夜间之后
+        let weekend = quiet_config(vec![Weekday::Sat, Weekday::Sun], "00:00", "00:00", QuietAction::Defer);
+        config.schedule.quiet_hours.extend(weekend.schedule.quiet_hours);
+        assert_eq!(PolicyEngine::new(config.clone()).defer_period_end(utc(2, 23, 0)), Some(utc(5, 8, 0)));
+
+        // 按配置的时区计算
+        config.schedule.timezone = "Asia/Shanghai".to_string();
+        assert_eq!(PolicyEngine::new(config).defer_period_end(utc(5, 23, 0)), Some(utc(6, 0, 0)));
+    }
+
+    #[test]
+    fn test_quiet_hours_weekdays() {
+        let engine = PolicyEngine::new(quiet_config(vec![Weekday::Fri], "22:00", "08:00", QuietAction::Defer));
+
+        // 周五晚上开始，覆盖到周六早上
+        assert!(engine.in_defer_period(utc(2, 22, 30)));
+        assert!(engine.in_defer_period(utc(3, 6, 0)));
+
+        // 周六晚上不在时段内，周五凌晨属于周四开始的时段
+        assert!(!engine.in_defer_period(utc(3, 22, 30)));
+        assert!(!engine.in_defer_period(utc(2, 6, 0)));
+    }
+
+    #[test]
+    fn test_quiet_hours_timezone() {
+        let mut config = quiet_config(vec![], "09:00", "10:00", QuietAction::Silence);
+        config.schedule.timezone = "Asia/Shanghai".to_string();
+        let engine = PolicyEngine::new(config);
+
+        // UTC 01:30 = 北京时间 09:30
+        assert_eq!(engine.quiet_action(NotificationStatus::Pending, utc(5, 1, 30)), Some(QuietAction::Silence));
+        assert_eq!(engine.quiet_action(NotificationStatus::Pending, utc(5, 9, 30)), None);
+    }
+
+    #[test]
+    fn test_should_play_sound() {
+        let mut config = quiet_config(vec![], "22:00", "08:00", QuietAction::Silence);
+        let quiet = utc(2, 23, 0);
+
+        assert!(!PolicyEngine::new(config.clone()).should_play_sound(NotificationStatus::Success, quiet));
+        assert!(PolicyEngine::new(config.clone()).should_play_sound(NotificationStatus::Success, utc(2, 12, 0)));
+
+        config.focus_assistant_mode = FocusAssistantMode::Always;
+        assert!(PolicyEngine::new(config.clone()).should_play_sound(NotificationStatus::Success, quiet));
+
+        // never 只影响免打扰时段，其他时间按 sound_enabled 播放
+        config.focus_assistant_mode = FocusAssistantMode::Never;
+        assert!(PolicyEngine::new(config.clone()).should_play_sound(NotificationStatus::Error, utc(2, 12, 0)));
+        assert!(!PolicyEngine::new(config.clone()).should_play_sound(NotificationStatus::Success, quiet));
+
+        config.sound_enabled = false;
+        assert!(!PolicyEngine::new(config.clone()).should_play_sound(NotificationStatus::Error, utc(2, 12, 0)));
+    }
+
+    #[test]
+    fn test_focus_assistant_mode_overrides_quiet_action() {
+        let quiet = utc(2, 23, 0);
+
+        // always 忽略免打扰时段：通知照常显示，暂存的通知可以立即发送
+        let mut config = quiet_config(vec![], "22:00", "08:00", QuietAction::Defer);
+        config.focus_assistant_mode = FocusAssistantMode::Always;
+        let engine = PolicyEngine::new(config);
+        assert_eq!(engine.quiet_action(NotificationStatus::Success, quiet), None);
+        assert!(!engine.in_defer_period(quiet));
+
+        // never 在免打扰时段内不打扰：silence 也丢弃，defer 和 suppress 不变
+        for (action, expected) in [
+            (QuietAction::Silence, QuietAction::Suppress),
+            (QuietAction::Defer, QuietAction::Defer),
+            (QuietAction::Suppress, QuietAction::Suppress),
+        ] {
+            let mut config = quiet_config(vec![], "22:00", "08:00", action);
+            config.focus_assistant_mode = FocusAssistantMode::Never;
+            let engine = PolicyEngine::new(config);
+            assert_eq!(engine.quiet_action(NotificationStatus::Success, quiet), Some(expected));
+            assert_eq!(engine.quiet_action(NotificationStatus::Success, utc(2, 12, 0)), None);
+        }
+    }
+
+    fn template(rule: Option<MatchRule>, priority: i32) -> crate::config::TemplateConfig {
+        crate::config::TemplateConfig {
+            icon: "auto".to_string(),
+            sound: "default".to_string(),
+            sounds: crate::config::StatusSounds::default(),
+            duration: 5000,
+            title: None,
+            body: None,
+            matcher: rule,
+            priority,
+        }
+    }
+
+    fn rule(pattern: &str, mode: MatchMode, anchor: Anchor) -> MatchRule {
+        MatchRule {
+            pattern: pattern.to_string(),
+            mode,
+            anchor,
+        }
+    }
+
+    #[test]
+    fn test_match_modes() {
+        let matches = |rule: MatchRule, cmd: &str| compile_rule(&rule).unwrap().is_match(cmd);
+
+        assert!(matches(MatchRule::substring("build"), "rebuild-cache"));
+        assert!(!matches(rule("build", MatchMode::Substring, Anchor::Start), "rebuild-cache"));
+        assert!(matches(rule("cargo", MatchMode::Substring, Anchor::Start), "cargo build"));
+        assert!(matches(rule("build", MatchMode::Substring, Anchor::End), "npm run build"));
+
+        assert!(matches(rule("npm run *", MatchMode::Glob, Anchor::None), "npm run build"));
+        assert!(!matches(rule("npm run *", MatchMode::Glob, Anchor::None), "pnpm run build"));
+        assert!(matches(rule("make ?", MatchMode::Glob, Anchor::None), "make 1"));
+        assert!(matches(rule("a.b", MatchMode::Glob, Anchor::None), "a.b"));
+        assert!(!matches(rule("a.b", MatchMode::Glob, Anchor::None), "axb"));
+
+        assert!(matches(rule(r"\bbuild\b", MatchMode::Regex, Anchor::None), "npm run build"));
+        assert!(!matches(rule(r"\bbuild\b", MatchMode::Regex, Anchor::None), "rebuild-cache"));
+        assert!(!matches(rule("test|build", MatchMode::Regex, Anchor::Both), "rebuild"));
+
+        assert!(matches(rule("make", MatchMode::Exact, Anchor::None), "make"));
+        assert!(!matches(rule("make", MatchMode::Exact, Anchor::None), "make all"));
+    }
+
+    #[test]
+    fn test_template_priority_order() {
+        let mut config = Config::default();
+        config.templates.custom.insert("build".to_string(), template(None, 0));
+        config.templates.custom.insert("cargo".to_string(), template(None, 0));
+        config.templates.custom.insert(
+            "release".to_string(),
+            template(Some(rule("*--release*", MatchMode::Glob, Anchor::None)), 10),
+        );
+        let engine = PolicyEngine::new(config);
+
+        // 同优先级按名称排序
+        assert_eq!(engine.match_template("cargo build"), Some("build".to_string()));
+
+        // 高优先级先匹配
+        assert_eq!(engine.match_template("cargo build --release"), Some("release".to_string()));
+    }
+
+    fn context(status: NotificationStatus, duration: u64, cmd: &str) -> RuleContext<'_> {
+        RuleContext {
+            status,
+            duration,
+            cmd,
+            project_path: None,
+            event: None,
+            now: utc(2, 12, 0),
+        }
+    }
+
+    #[test]
+    fn test_invalid_rule_ignored() {
+        let mut config = Config::default();
+        config.threshold.whitelist = vec![
+            rule("(unclosed", MatchMode::Regex, Anchor::None),
+            rule("deploy", MatchMode::Exact, Anchor::None),
+        ];
+        let engine = PolicyEngine::new(config);
+
+        let outcome = engine.evaluate(&context(NotificationStatus::Success, 3, "deploy"));
+        assert_eq!(outcome.rule.as_deref(), Some("whitelist #2"));
+        assert!(outcome.should_notify());
+
+        let outcome = engine.evaluate(&context(NotificationStatus::Success, 3, "deploy prod"));
+        assert_eq!(outcome.rule.as_deref(), Some("below-threshold"));
+        assert!(!outcome.should_notify());
+
+        let explanation = engine.explain(&context(NotificationStatus::Success, 3, "deploy"));
+        assert!(explanation.rules[2].error.is_some());
+        assert!(!explanation.rules[2].matched);
+        assert!(explanation.rules[3].matched);
+        assert_eq!(explanation.template, "default");
+    }
+
+    fn rule_config(when: crate::config::RuleConditions, action: RuleAction) -> RuleConfig {
+        RuleConfig {
+            name: None,
+            when,
+            action,
+            force_sound: false,
+            sinks: vec![],
+        }
+    }
+
+    #[test]
+    fn test_rules_top_down() {
+        use crate::config::{DurationRange, RuleConditions};
+
+        let mut config = Config::default();
+        config.schedule.timezone = "UTC".to_string();
+        config.rules = vec![
+            rule_config(
+                RuleConditions {
+                    project: Some(rule("*/scratch/*", MatchMode::Glob, Anchor::None)),
+                    ..Default::default()
+                },
+                RuleAction::Drop,
+            ),
+            RuleConfig {
+                sinks: vec!["webhook".to_string()],
+                force_sound: true,
+                ..rule_config(
+                    RuleConditions {
+                        status: vec![NotificationStatus::Error],
+                        event: vec!["Stop".to_string()],
+                        ..Default::default()
+                    },
+                    RuleAction::Notify,
+                )
+            },
+            rule_config(
+                RuleConditions {
+                    duration: Some(DurationRange { min: Some(1), max: Some(30) }),
+                    time: Some(TimeRange {
+                        days: vec![],
+                        start: "09:00".parse().unwrap(),
+                        end: "18:00".parse().unwrap(),
+                    }),
+                    ..Default::default()
+                },
+                RuleAction::Aggregate,
+            ),
+        ];
+        config.default_action = RuleAction::Drop;
+        let engine = PolicyEngine::new(config);
+
+        // 第一条规则优先
+        let scratch = RuleContext {
+            project_path: Some("/home/me/scratch/demo"),
+            event: Some("Stop"),
+            ..context(NotificationStatus::Error, 5, "make")
+        };
+        assert_eq!(engine.evaluate(&scratch).action, RuleAction::Drop);
+
+        let stop_error = RuleContext {
+            project_path: Some("/home/me/work/demo"),
+            event: Some("Stop"),
+            ..context(NotificationStatus::Error, 5, "make")
+        };
+        let outcome = engine.evaluate(&stop_error);
+        assert_eq!(outcome.rule.as_deref(), Some("#2"));
+        assert!(outcome.force_sound);
+        assert_eq!(outcome.sinks, vec!["webhook"]);
+
+        // 没有 hook 事件时不匹配 event 条件
+        let outcome = engine.evaluate(&context(NotificationStatus::Error, 5, "make"));
+        assert_eq!(outcome.action, RuleAction::Aggregate);
+
+        // 时间段外使用默认动作
+        let night = RuleContext {
+            now: utc(2, 20, 0),
+            ..context(NotificationStatus::Success, 5, "make")
+        };
+        assert_eq!(engine.evaluate(&night), RuleOutcome {
+            rule: None,
+            action: RuleAction::Drop,
+            force_sound: false,
+            sinks: vec![],
+        });
+    }
 }
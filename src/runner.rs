@@ -0,0 +1,194 @@
+//! 命令执行模块
+//!
+//! `ccn run -- <command>` 的实现：启动子进程、透传输出、计时，并把退出状态和信号传回调用方
+
+use anyhow::{Context, Result};
+use std::process::{Command, ExitStatus};
+use std::time::{Duration, Instant};
+
+use crate::notification::NotificationStatus;
+
+/// 子进程执行结果
+#[derive(Debug, Clone)]
+pub struct RunOutcome {
+    /// 正常退出时的退出码
+    pub exit_code: Option<i32>,
+    /// 被信号终止时的信号值（仅 Unix）
+    pub signal: Option<i32>,
+    /// 墙钟耗时
+    pub elapsed: Duration,
+}
+
+impl RunOutcome {
+    fn from_exit_status(status: ExitStatus, elapsed: Duration) -> Self {
+        #[cfg(unix)]
+        let signal = {
+            use std::os::unix::process::ExitStatusExt;
+            status.signal()
+        };
+
+        #[cfg(not(unix))]
+        let signal = None;
+
+        Self {
+            exit_code: status.code(),
+            signal,
+            elapsed,
+        }
+    }
+
+    /// 是否成功（退出码为 0）
+    pub fn success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+
+    /// 映射为通知状态
+    pub fn status(&self) -> NotificationStatus {
+        if self.success() {
+            NotificationStatus::Success
+        } else {
+            NotificationStatus::Error
+        }
+    }
+
+    /// ccn 自身应使用的退出码（被信号终止时遵循 shell 约定 128 + 信号值）
+    pub fn propagated_exit_code(&self) -> i32 {
+        match (self.exit_code, self.signal) {
+            (Some(code), _) => code,
+            (None, Some(signal)) => 128 + signal,
+            (None, None) => 1,
+        }
+    }
+
+    /// 用于通知消息的退出状态描述
+    pub fn describe(&self) -> String {
+        match (self.exit_code, self.signal) {
+            (Some(code), _) => t!("run.exit_code", code = code),
+            (None, Some(signal)) => t!("run.signal", signal = signal),
+            (None, None) => t!("run.unknown_exit"),
+        }
+    }
+}
+
+/// 执行命令并等待结束，标准输入/输出/错误直接继承自当前进程
+pub fn run_command(args: &[String]) -> Result<RunOutcome> {
+    let (program, rest) = args
+        .split_first()
+        .ok_or_else(|| anyhow::anyhow!("{}", t!("run.missing_command")))?;
+
+    let mut command = Command::new(program);
+    command.args(rest);
+
+    let start = Instant::now();
+    let status = wait_with_signals(&mut command)
+        .with_context(|| t!("run.spawn_failed", program = program))?;
+
+    Ok(RunOutcome::from_exit_status(status, start.elapsed()))
+}
+
+/// 启动子进程并等待，期间把其他进程发给 ccn 的信号转发给子进程
+///
+/// 终端产生的信号（如 Ctrl+C）本来就会发给整个前台进程组，这里只需让 ccn 自己不被终止
+#[cfg(unix)]
+fn wait_with_signals(command: &mut Command) -> std::io::Result<ExitStatus> {
+    use signal_hook::consts::{SIGHUP, SIGINT, SIGQUIT, SIGTERM};
+    use signal_hook::iterator::SignalsInfo;
+    use signal_hook::iterator::exfiltrator::WithOrigin;
+
+    // 在启动子进程前注册，子进程 exec 后会恢复默认处理
+    let mut signals = SignalsInfo::<WithOrigin>::new([SIGINT, SIGTERM, SIGHUP, SIGQUIT])?;
+    let handle = signals.handle();
+
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            handle.close();
+            return Err(e);
+        }
+    };
+
+    let child_pid = child.id() as libc::pid_t;
+    let forwarder = std::thread::spawn(move || {
+        for origin in signals.forever() {
+            // 只转发由其他进程（kill 等）发送的信号
+            if origin.process.is_some() {
+                log::debug!("{}", t!("run.forward_signal", signal = origin.signal, pid = child_pid));
+                unsafe {
+                    libc::kill(child_pid, origin.signal);
+                }
+            }
+        }
+    });
+
+    let status = child.wait();
+    handle.close();
+    let _ = forwarder.join();
+    status
+}
+
+#[cfg(not(unix))]
+fn wait_with_signals(command: &mut Command) -> std::io::Result<ExitStatus> {
+    // Ctrl+C 会同时发给子进程，ccn 自己忽略它以便在子进程结束后发送通知
+    if let Err(e) = ctrlc::set_handler(|| {}) {
+        log::warn!("{}", t!("run.ctrlc_failed", error = e));
+    }
+
+    command.spawn()?.wait()
+}
+
+/// 以与子进程相同的方式结束当前进程（被信号终止时重新触发该信号）
+pub fn exit_like(outcome: &RunOutcome) -> ! {
+    #[cfg(unix)]
+    if let Some(signal) = outcome.signal {
+        let _ = signal_hook::low_level::emulate_default_handler(signal);
+    }
+
+    std::process::exit(outcome.propagated_exit_code())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn sh(script: &str) -> Vec<String> {
+        vec!["sh".to_string(), "-c".to_string(), script.to_string()]
+    }
+
+    #[test]
+    fn test_run_success() {
+        let outcome = run_command(&sh("exit 0")).unwrap();
+        assert!(outcome.success());
+        assert!(matches!(outcome.status(), NotificationStatus::Success));
+        assert_eq!(outcome.propagated_exit_code(), 0);
+    }
+
+    #[test]
+    fn test_run_failure_exit_code() {
+        let outcome = run_command(&sh("exit 3")).unwrap();
+        assert!(!outcome.success());
+        assert!(matches!(outcome.status(), NotificationStatus::Error));
+        assert_eq!(outcome.propagated_exit_code(), 3);
+        assert_eq!(outcome.describe(), t!("run.exit_code", code = 3));
+    }
+
+    #[test]
+    fn test_run_killed_by_signal() {
+        let outcome = run_command(&sh("kill -TERM $$")).unwrap();
+        assert_eq!(outcome.exit_code, None);
+        assert_eq!(outcome.signal, Some(libc::SIGTERM));
+        assert!(matches!(outcome.status(), NotificationStatus::Error));
+        assert_eq!(outcome.propagated_exit_code(), 128 + libc::SIGTERM);
+    }
+
+    #[test]
+    fn test_run_measures_duration() {
+        let outcome = run_command(&sh("sleep 0.2")).unwrap();
+        assert!(outcome.elapsed >= Duration::from_millis(200));
+    }
+
+    #[test]
+    fn test_run_missing_command() {
+        assert!(run_command(&["ccn-test-no-such-command".to_string()]).is_err());
+        assert!(run_command(&[]).is_err());
+    }
+}
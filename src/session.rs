@@ -0,0 +1,134 @@
+//! 会话跟踪模块
+//!
+//! 记录 Claude Code 会话（本轮对话）的开始时间，用于计算 Stop 事件的耗时
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::aggregator::get_state_file_path;
+use crate::state_file::{self, StateLock};
+
+/// 会话记录的保留时间（秒），超过后清理
+const SESSION_TTL_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// 会话状态文件
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SessionState {
+    /// session_id -> 开始时间戳（秒）
+    sessions: HashMap<String, u64>,
+}
+
+/// 会话跟踪器
+pub struct SessionTracker {
+    state_file: PathBuf,
+}
+
+impl SessionTracker {
+    pub fn new(state_file: PathBuf) -> Self {
+        Self { state_file }
+    }
+
+    /// 记录会话开始（重复调用会刷新开始时间）
+    pub fn record_start(&self, session_id: &str) -> Result<()> {
+        self.record_start_at(session_id, now())
+    }
+
+    /// 获取会话已经过的秒数，未记录时返回 None
+    pub fn elapsed(&self, session_id: &str) -> Option<u64> {
+        let state = self.load_state().ok()?;
+        let start = state.sessions.get(session_id)?;
+        Some(now().saturating_sub(*start))
+    }
+
+    fn record_start_at(&self, session_id: &str, timestamp: u64) -> Result<()> {
+        let _lock = StateLock::acquire(&self.state_file)?;
+        let mut state = self.load_state().unwrap_or_default();
+
+        // 清理过期会话
+        let current = now();
+        state
+            .sessions
+            .retain(|_, start| current.saturating_sub(*start) < SESSION_TTL_SECS);
+
+        state.sessions.insert(session_id.to_string(), timestamp);
+        self.save_state(&state)
+    }
+
+    fn load_state(&self) -> Result<SessionState> {
+        if !self.state_file.exists() {
+            return Ok(SessionState::default());
+        }
+
+        let content = fs::read_to_string(&self.state_file)?;
+        serde_json::from_str(&content).map_err(Into::into)
+    }
+
+    fn save_state(&self, state: &SessionState) -> Result<()> {
+        if let Some(parent) = self.state_file.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| t!("state.create_dir", path = format!("{:?}", parent)))?;
+        }
+
+        let content = serde_json::to_string(state)?;
+        state_file::write_atomic(&self.state_file, content.as_bytes())
+    }
+}
+
+/// 获取当前时间戳（秒）
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// 获取会话状态文件路径（与聚合状态文件位于同一目录）
+pub fn get_session_file_path() -> PathBuf {
+    get_state_file_path().with_file_name("sessions.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_state_file(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("ccn-test-{}-{}.json", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_elapsed_since_start() {
+        let state_file = temp_state_file("session-elapsed");
+        let tracker = SessionTracker::new(state_file.clone());
+
+        tracker.record_start_at("abc", now() - 42).unwrap();
+        let elapsed = tracker.elapsed("abc").unwrap();
+        assert!((42..=43).contains(&elapsed));
+
+        // 未记录的会话
+        assert!(tracker.elapsed("unknown").is_none());
+
+        // 重新记录会刷新开始时间
+        tracker.record_start("abc").unwrap();
+        assert!(tracker.elapsed("abc").unwrap() <= 1);
+
+        let _ = fs::remove_file(&state_file);
+    }
+
+    #[test]
+    fn test_expired_sessions_pruned() {
+        let state_file = temp_state_file("session-prune");
+        let tracker = SessionTracker::new(state_file.clone());
+
+        tracker.record_start_at("old", now() - SESSION_TTL_SECS - 1).unwrap();
+        tracker.record_start("new").unwrap();
+
+        assert!(tracker.elapsed("old").is_none());
+        assert!(tracker.elapsed("new").is_some());
+
+        let _ = fs::remove_file(&state_file);
+    }
+}
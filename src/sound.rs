@@ -1,18 +1,58 @@
 //! 音频播放模块
 //!
-//! 支持播放系统提示音和自定义 WAV 文件
+//! 支持播放系统提示音和自定义音频文件。Linux 上的系统提示音按
+//! [freedesktop 声音主题规范](https://specifications.freedesktop.org/sound-theme-spec/latest/)
+//! 在 `$XDG_DATA_HOME/sounds` 和 `$XDG_DATA_DIRS/sounds` 中查找，没有安装主题时播放内置的合成提示音
 
 use anyhow::Result;
 use std::path::Path;
 
+use crate::notification::NotificationStatus;
+#[cfg(all(unix, not(target_os = "macos")))]
+use std::path::PathBuf;
+
+/// 默认的声音主题，也是查找提示音时最后的回退
+pub const DEFAULT_SOUND_THEME: &str = "freedesktop";
+
 /// 音频播放器
 pub struct SoundPlayer {
     enabled: bool,
+    /// 系统提示音使用的声音主题（仅 Linux）
+    #[cfg_attr(not(all(unix, not(target_os = "macos"))), allow(dead_code))]
+    theme: String,
+    /// 音量，1.0 为原始音量
+    #[cfg_attr(not(feature = "sound"), allow(dead_code))]
+    volume: f32,
 }
 
 impl SoundPlayer {
     pub fn new(enabled: bool) -> Self {
-        Self { enabled }
+        Self {
+            enabled,
+            theme: DEFAULT_SOUND_THEME.to_string(),
+            volume: 1.0,
+        }
+    }
+
+    /// 设置系统提示音使用的声音主题
+    pub fn with_theme(mut self, theme: &str) -> Self {
+        self.theme = theme.to_string();
+        self
+    }
+
+    /// 设置音量，1.0 为原始音量
+    pub fn with_volume(mut self, volume: f32) -> Self {
+        self.volume = volume;
+        self
+    }
+
+    /// 播放音效
+    pub fn play(&self, sound: &Sound) -> Result<()> {
+        match sound {
+            Sound::Silent => Ok(()),
+            Sound::System(sound_type) => self.play_system_sound(*sound_type),
+            Sound::File(path) => self.play_sound_file(path),
+        }
     }
 
     /// 播放系统提示音
@@ -26,9 +66,14 @@ impl SoundPlayer {
             self.play_windows_system_sound(sound_type)?;
         }
 
-        #[cfg(not(windows))]
+        #[cfg(all(unix, not(target_os = "macos")))]
         {
-            log::info!("系统提示音: {:?}", sound_type);
+            self.play_theme_sound(sound_type)?;
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            log::info!("{}", t!("sound.log.system", sound = format!("{:?}", sound_type)));
         }
 
         Ok(())
@@ -43,7 +88,7 @@ impl SoundPlayer {
         let path = Path::new(file_path);
 
         if !path.exists() {
-            log::warn!("音频文件不存在: {:?}", file_path);
+            log::warn!("{}", t!("sound.file_missing", path = format!("{:?}", file_path)));
             return Ok(());
         }
 
@@ -55,7 +100,7 @@ impl SoundPlayer {
 
         #[cfg(not(feature = "sound"))]
         {
-            log::info!("播放音频文件（未启用 sound 功能）: {:?}", file_path);
+            log::info!("{}", t!("sound.log.file_without_feature", path = format!("{:?}", file_path)));
         }
 
         Ok(())
@@ -65,38 +110,313 @@ impl SoundPlayer {
     fn play_windows_system_sound(&self, sound_type: SystemSound) -> Result<()> {
         // Windows 系统音效播放需要额外的 Windows API
         // 暂时记录日志，后续可以通过添加更多 Windows features 实现
-        log::info!("播放系统提示音: {:?}", sound_type);
+        log::info!("{}", t!("sound.log.system", sound = format!("{:?}", sound_type)));
+        Ok(())
+    }
+
+    /// 播放声音主题中的提示音，找不到或无法播放时播放内置提示音
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn play_theme_sound(&self, sound_type: SystemSound) -> Result<()> {
+        let Some(path) = find_theme_sound(&self.theme, sound_type.theme_names(), &sound_data_dirs()) else {
+            log::debug!(
+                "{}",
+                t!("sound.log.theme_missing", theme = self.theme, sound = format!("{:?}", sound_type))
+            );
+            return self.play_tone(sound_type);
+        };
+
+        log::debug!("{}", t!("sound.log.theme_sound", path = format!("{:?}", path)));
+        #[cfg(feature = "sound")]
+        {
+            if let Err(e) = self.play_with_rodio(&path) {
+                log::warn!("{}", t!("sound.log.theme_failed", path = format!("{:?}", path), error = format!("{:#}", e)));
+                return self.play_tone(sound_type);
+            }
+        }
+
+        #[cfg(not(feature = "sound"))]
+        {
+            log::info!("{}", t!("sound.log.file_without_feature", path = format!("{:?}", path)));
+        }
+
+        Ok(())
+    }
+
+    /// 播放内置的合成提示音
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn play_tone(&self, sound_type: SystemSound) -> Result<()> {
+        #[cfg(feature = "sound")]
+        {
+            use rodio::source::{SineWave, Source};
+            use std::time::Duration;
+
+            let (_stream, sink) = self.open_sink()?;
+            for &(frequency, millis) in sound_type.tone() {
+                sink.append(
+                    SineWave::new(frequency)
+                        .take_duration(Duration::from_millis(millis))
+                        .fade_in(Duration::from_millis(10))
+                        .amplify(0.2),
+                );
+            }
+            sink.sleep_until_end();
+        }
+
+        #[cfg(not(feature = "sound"))]
+        {
+            log::info!("{}", t!("sound.log.tone_without_feature", sound = format!("{:?}", sound_type)));
+        }
+
         Ok(())
     }
 
     #[cfg(feature = "sound")]
     fn play_with_rodio(&self, path: &Path) -> Result<()> {
-        use rodio::{Decoder, OutputStream};
+        use anyhow::Context;
+        use rodio::Decoder;
         use std::fs::File;
         use std::io::BufReader;
 
-        // 获取输出流
-        let (_stream, stream_handle) = OutputStream::try_default()?;
+        // 打开并解码音频文件
+        let file = File::open(path).with_context(|| t!("sound.open_file", path = format!("{:?}", path)))?;
+        let decoder = Decoder::new(BufReader::new(file)).with_context(|| t!("sound.decode_file", path = format!("{:?}", path)))?;
 
-        // 打开音频文件
-        let file = File::open(path)?;
-        let source = BufReader::new(file);
+        // 播放并等待结束
+        let (_stream, sink) = self.open_sink()?;
+        sink.append(decoder);
+        sink.sleep_until_end();
 
-        // 解码音频
-        let decoder = rodio::Decoder::new(source)?;
+        Ok(())
+    }
 
-        // 播放
-        rodio::play_source(decoder, &stream_handle)?;
-        std::thread::sleep(std::time::Duration::from_millis(500));
+    /// 打开默认输出设备，返回的输出流需要在播放期间保持存活
+    #[cfg(feature = "sound")]
+    fn open_sink(&self) -> Result<(rodio::OutputStream, rodio::Sink)> {
+        use anyhow::Context;
 
-        Ok(())
+        let (stream, handle) = rodio::OutputStream::try_default().with_context(|| t!("sound.open_device"))?;
+        let sink = rodio::Sink::try_new(&handle).with_context(|| t!("sound.create_sink"))?;
+        sink.set_volume(self.volume);
+        Ok((stream, sink))
+    }
+}
+
+/// 要播放的音效
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Sound {
+    /// 不播放
+    Silent,
+    /// 系统提示音
+    System(SystemSound),
+    /// 音频文件
+    File(String),
+}
+
+impl Sound {
+    /// 解析配置中的音效：`default` 或空为状态对应的系统提示音，`none` 为不播放，其他值为音频文件路径
+    pub fn from_config(value: &str, status: NotificationStatus) -> Self {
+        match value.trim() {
+            "" | "default" => Sound::System(status.into()),
+            "none" => Sound::Silent,
+            path => Sound::File(path.to_string()),
+        }
     }
 }
 
 /// 系统提示音类型
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SystemSound {
     Success,
     Error,
     Notification,
 }
+
+impl From<NotificationStatus> for SystemSound {
+    fn from(status: NotificationStatus) -> Self {
+        match status {
+            NotificationStatus::Success => SystemSound::Success,
+            NotificationStatus::Error => SystemSound::Error,
+            NotificationStatus::Pending => SystemSound::Notification,
+        }
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+impl SystemSound {
+    /// 声音主题中对应的声音名称，按优先级排列
+    fn theme_names(self) -> &'static [&'static str] {
+        match self {
+            SystemSound::Success => &["complete", "message"],
+            SystemSound::Error => &["dialog-error", "dialog-warning", "bell"],
+            SystemSound::Notification => &["message-new-instant", "message", "bell"],
+        }
+    }
+
+    /// 内置提示音的音符：频率（Hz）和时长（毫秒）
+    #[cfg_attr(not(feature = "sound"), allow(dead_code))]
+    fn tone(self) -> &'static [(f32, u64)] {
+        match self {
+            SystemSound::Success => &[(880.0, 100), (1318.5, 160)],
+            SystemSound::Error => &[(440.0, 150), (329.6, 250)],
+            SystemSound::Notification => &[(987.8, 150)],
+        }
+    }
+}
+
+/// 声音主题支持的文件扩展名，按优先级排列
+#[cfg(all(unix, not(target_os = "macos")))]
+const SOUND_EXTENSIONS: &[&str] = &["oga", "ogg", "wav"];
+
+/// 声音主题的查找目录：`$XDG_DATA_HOME/sounds`（默认 `~/.local/share/sounds`）和 `$XDG_DATA_DIRS` 下的 `sounds`
+#[cfg(all(unix, not(target_os = "macos")))]
+fn sound_data_dirs() -> Vec<PathBuf> {
+    let user = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")));
+    let system = std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+
+    user.into_iter()
+        .chain(system.split(':').filter(|dir| !dir.is_empty()).map(PathBuf::from))
+        .map(|dir| dir.join("sounds"))
+        .collect()
+}
+
+/// 声音主题 `index.theme` 中用到的字段
+#[cfg(all(unix, not(target_os = "macos")))]
+#[derive(Debug, PartialEq)]
+struct ThemeIndex {
+    inherits: Vec<String>,
+    directories: Vec<String>,
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+impl ThemeIndex {
+    /// 读取第一个存在的 `<目录>/<主题>/index.theme`；没有时只查找 `stereo` 子目录
+    fn read(theme: &str, data_dirs: &[PathBuf]) -> Self {
+        let text = data_dirs
+            .iter()
+            .find_map(|dir| std::fs::read_to_string(dir.join(theme).join("index.theme")).ok())
+            .unwrap_or_default();
+        Self::parse(&text)
+    }
+
+    fn parse(text: &str) -> Self {
+        let list = |key: &str| -> Vec<String> {
+            text.lines()
+                .filter_map(|line| line.trim().strip_prefix(key)?.trim_start().strip_prefix('='))
+                .flat_map(|value| value.split(','))
+                .map(|item| item.trim().to_string())
+                .filter(|item| !item.is_empty())
+                .collect()
+        };
+
+        let mut directories = list("Directories");
+        if directories.is_empty() {
+            directories.push("stereo".to_string());
+        }
+        Self {
+            inherits: list("Inherits"),
+            directories,
+        }
+    }
+}
+
+/// 在声音主题中查找提示音
+///
+/// 主题链为 `theme`、其继承的主题，最后是 freedesktop 默认主题；
+/// 依次尝试 `names` 中的每个名称，同一名称在整个主题链中找不到时才尝试下一个
+#[cfg(all(unix, not(target_os = "macos")))]
+fn find_theme_sound(theme: &str, names: &[&str], data_dirs: &[PathBuf]) -> Option<PathBuf> {
+    let mut chain: Vec<(String, ThemeIndex)> = Vec::new();
+    let mut pending = vec![theme.to_string()];
+    while !pending.is_empty() || !chain.iter().any(|(name, _)| name == DEFAULT_SOUND_THEME) {
+        let name = pending.pop().unwrap_or_else(|| DEFAULT_SOUND_THEME.to_string());
+        if chain.iter().any(|(existing, _)| *existing == name) {
+            continue;
+        }
+        let index = ThemeIndex::read(&name, data_dirs);
+        // 继承的主题按声明顺序查找
+        pending.extend(index.inherits.iter().rev().cloned());
+        chain.push((name, index));
+    }
+
+    names.iter().find_map(|sound| {
+        chain.iter().find_map(|(name, index)| {
+            index.directories.iter().find_map(|subdir| {
+                data_dirs.iter().find_map(|dir| {
+                    SOUND_EXTENSIONS
+                        .iter()
+                        .map(|extension| dir.join(name).join(subdir).join(format!("{}.{}", sound, extension)))
+                        .find(|path| path.is_file())
+                })
+            })
+        })
+    })
+}
+
+#[cfg(all(test, unix, not(target_os = "macos")))]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn temp_sounds(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ccn-test-sounds-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn touch(path: PathBuf) -> PathBuf {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, b"").unwrap();
+        path
+    }
+
+    #[test]
+    fn test_parse_theme_index() {
+        let index = ThemeIndex::parse("[Sound Theme]\nName=Yaru\nInherits=ubuntu, freedesktop\nDirectories=stereo\n");
+        assert_eq!(index.inherits, vec!["ubuntu", "freedesktop"]);
+        assert_eq!(index.directories, vec!["stereo"]);
+        assert_eq!(ThemeIndex::parse("").directories, vec!["stereo"]);
+    }
+
+    #[test]
+    fn test_find_theme_sound() {
+        let temp = temp_sounds("find");
+        let user = temp.join("user/sounds");
+        let system = temp.join("system/sounds");
+        let dirs = vec![user.clone(), system.clone()];
+
+        let complete = touch(system.join("freedesktop/stereo/complete.oga"));
+        touch(system.join("freedesktop/stereo/bell.oga"));
+        assert_eq!(find_theme_sound("freedesktop", SystemSound::Success.theme_names(), &dirs), Some(complete.clone()));
+        // 未安装的主题回退到 freedesktop
+        assert_eq!(find_theme_sound("missing", SystemSound::Success.theme_names(), &dirs), Some(complete));
+
+        // 主题及其继承的主题优先于 freedesktop，且先匹配优先级高的名称
+        fs::create_dir_all(user.join("custom")).unwrap();
+        fs::write(user.join("custom/index.theme"), "[Sound Theme]\nInherits=base\nDirectories=stereo\n").unwrap();
+        let error = touch(system.join("base/stereo/dialog-error.wav"));
+        assert_eq!(find_theme_sound("custom", SystemSound::Error.theme_names(), &dirs), Some(error));
+        let complete = touch(user.join("custom/stereo/complete.ogg"));
+        assert_eq!(find_theme_sound("custom", SystemSound::Success.theme_names(), &dirs), Some(complete));
+
+        assert_eq!(find_theme_sound("custom", &["message-new-email"], &dirs), None);
+        fs::remove_dir_all(&temp).unwrap();
+    }
+
+    #[test]
+    fn test_inherit_cycle_terminates() {
+        let temp = temp_sounds("cycle");
+        let dirs = vec![temp.clone()];
+        touch(temp.join("a/index.theme"));
+        fs::write(temp.join("a/index.theme"), "Inherits=b\n").unwrap();
+        touch(temp.join("b/index.theme"));
+        fs::write(temp.join("b/index.theme"), "Inherits=a\n").unwrap();
+        assert_eq!(find_theme_sound("a", &["bell"], &dirs), None);
+        fs::remove_dir_all(&temp).unwrap();
+    }
+}
@@ -0,0 +1,118 @@
+//! 状态文件模块
+//!
+//! 多个 ccn 进程（并行的 Claude Code 会话）会同时读写同一个状态文件：
+//! 读-改-写期间持有咨询锁，写入时先写临时文件再重命名，保证其他进程看到的总是完整的 JSON
+
+use anyhow::{Context, Result};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// 状态文件的独占锁，离开作用域时释放
+pub struct StateLock {
+    _file: File,
+}
+
+impl StateLock {
+    /// 阻塞等待并获取状态文件的独占锁
+    ///
+    /// 锁加在旁边的 `.lock` 文件上，而不是状态文件本身，因为状态文件会被重命名替换
+    pub fn acquire(state_file: &Path) -> Result<Self> {
+        let lock_path = lock_file_path(state_file);
+        if let Some(parent) = lock_path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| t!("state.create_dir", path = format!("{:?}", parent)))?;
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .write(true)
+            .truncate(false)
+            .open(&lock_path)
+            .with_context(|| t!("state.open_lock", path = format!("{:?}", lock_path)))?;
+        file.lock()
+            .with_context(|| t!("state.lock", path = format!("{:?}", lock_path)))?;
+
+        Ok(Self { _file: file })
+    }
+}
+
+/// 原子写入：先写同目录下的临时文件并同步到磁盘，再重命名覆盖目标文件
+pub fn write_atomic(path: &Path, content: &[u8]) -> Result<()> {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let temp_path = path.with_file_name(format!(
+        ".{}.{}.{}.tmp",
+        file_name,
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let result = (|| -> Result<()> {
+        let mut file = File::create(&temp_path)?;
+        file.write_all(content)?;
+        file.sync_all()?;
+        fs::rename(&temp_path, path)?;
+        Ok(())
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result.with_context(|| t!("state.write", path = format!("{:?}", path)))
+}
+
+/// 将无法解析的状态文件移到 `<name>.corrupt`，保留现场以便排查
+pub fn quarantine(path: &Path) {
+    let backup = path.with_file_name(format!(
+        "{}.corrupt",
+        path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default()
+    ));
+
+    match fs::rename(path, &backup) {
+        Ok(()) => log::warn!("{}", t!("state.corrupt", path = format!("{:?}", backup))),
+        Err(e) => log::warn!("{}", t!("state.corrupt_no_backup", path = format!("{:?}", path), error = e)),
+    }
+}
+
+fn lock_file_path(state_file: &Path) -> PathBuf {
+    let mut name = state_file.as_os_str().to_owned();
+    name.push(".lock");
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_atomic_replaces_content() {
+        let dir = std::env::temp_dir().join(format!("ccn-test-state-file-{}", std::process::id()));
+        let path = dir.join("state.json");
+        fs::create_dir_all(&dir).unwrap();
+
+        write_atomic(&path, b"{\"a\":1}").unwrap();
+        write_atomic(&path, b"{\"a\":2}").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"a\":2}");
+
+        // 不留下临时文件
+        let leftovers: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().ends_with(".tmp"))
+            .collect();
+        assert!(leftovers.is_empty());
+
+        quarantine(&path);
+        assert!(!path.exists());
+        assert!(dir.join("state.json.corrupt").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
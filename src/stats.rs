@@ -0,0 +1,222 @@
+//! 使用统计模块
+//!
+//! 基于通知历史计算任意时间范围内的统计：按天、按项目的次数和成功率，耗时分位数，以及最常见的命令
+
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::history::HistoryEntry;
+use crate::notification::NotificationStatus;
+
+/// 按状态计数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct StatusCounts {
+    pub total: usize,
+    pub success: usize,
+    pub error: usize,
+    pub pending: usize,
+    /// 被策略过滤（未发送通知）的次数
+    pub suppressed: usize,
+}
+
+impl StatusCounts {
+    fn add(&mut self, entry: &HistoryEntry) {
+        self.total += 1;
+        match entry.status {
+            NotificationStatus::Success => self.success += 1,
+            NotificationStatus::Error => self.error += 1,
+            NotificationStatus::Pending => self.pending += 1,
+        }
+        if entry.suppressed {
+            self.suppressed += 1;
+        }
+    }
+
+    /// 成功率（成功 / (成功 + 失败)），没有已完成的任务时返回 None
+    pub fn success_rate(&self) -> Option<f64> {
+        let finished = self.success + self.error;
+        (finished > 0).then(|| self.success as f64 / finished as f64)
+    }
+}
+
+/// 耗时分布（秒）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct DurationStats {
+    /// 参与统计的记录数（耗时为 0 的记录表示未知，不参与）
+    pub count: usize,
+    pub min: u64,
+    pub p50: u64,
+    pub p90: u64,
+    pub p99: u64,
+    pub max: u64,
+    pub mean: f64,
+}
+
+impl DurationStats {
+    /// 计算耗时分布，没有有效耗时时返回 None
+    pub fn from_durations(durations: impl IntoIterator<Item = u64>) -> Option<Self> {
+        let mut sorted: Vec<u64> = durations.into_iter().filter(|d| *d > 0).collect();
+        if sorted.is_empty() {
+            return None;
+        }
+        sorted.sort_unstable();
+
+        let sum: u64 = sorted.iter().sum();
+        Some(Self {
+            count: sorted.len(),
+            min: sorted[0],
+            p50: percentile(&sorted, 50),
+            p90: percentile(&sorted, 90),
+            p99: percentile(&sorted, 99),
+            max: sorted[sorted.len() - 1],
+            mean: sum as f64 / sorted.len() as f64,
+        })
+    }
+}
+
+/// 最近秩法计算分位数，`sorted` 必须非空且已排序
+fn percentile(sorted: &[u64], p: usize) -> u64 {
+    let rank = (p * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+/// 命令出现次数
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CommandCount {
+    pub cmd: String,
+    pub count: usize,
+}
+
+/// 统计报告
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageReport {
+    /// 记录覆盖的首尾日期
+    pub first_date: Option<NaiveDate>,
+    pub last_date: Option<NaiveDate>,
+    pub overall: StatusCounts,
+    pub durations: Option<DurationStats>,
+    pub by_day: BTreeMap<NaiveDate, StatusCounts>,
+    pub by_project: BTreeMap<String, StatusCounts>,
+    pub top_commands: Vec<CommandCount>,
+}
+
+impl UsageReport {
+    /// 从历史记录生成报告，`top` 为最常见命令的数量
+    pub fn from_entries(entries: &[HistoryEntry], top: usize) -> Self {
+        let mut overall = StatusCounts::default();
+        let mut by_day: BTreeMap<NaiveDate, StatusCounts> = BTreeMap::new();
+        let mut by_project: BTreeMap<String, StatusCounts> = BTreeMap::new();
+        let mut commands: HashMap<&str, usize> = HashMap::new();
+
+        for entry in entries {
+            overall.add(entry);
+            by_day.entry(entry.timestamp.date_naive()).or_default().add(entry);
+
+            let project = entry.project.as_deref().unwrap_or("-");
+            by_project.entry(project.to_string()).or_default().add(entry);
+
+            if !entry.cmd.is_empty() {
+                *commands.entry(entry.cmd.as_str()).or_default() += 1;
+            }
+        }
+
+        // 次数降序，次数相同时按命令名排序，保证输出稳定
+        let mut top_commands: Vec<CommandCount> = commands
+            .into_iter()
+            .map(|(cmd, count)| CommandCount {
+                cmd: cmd.to_string(),
+                count,
+            })
+            .collect();
+        top_commands.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.cmd.cmp(&b.cmd)));
+        top_commands.truncate(top);
+
+        Self {
+            first_date: by_day.keys().next().copied(),
+            last_date: by_day.keys().next_back().copied(),
+            overall,
+            durations: DurationStats::from_durations(entries.iter().map(|e| e.duration)),
+            by_day,
+            by_project,
+            top_commands,
+        }
+    }
+}
+
+/// 格式化成功率
+pub fn format_rate(rate: Option<f64>) -> String {
+    match rate {
+        Some(rate) => format!("{:.1}%", rate * 100.0),
+        None => "-".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Local, TimeZone};
+
+    fn entry(day: u32, status: NotificationStatus, cmd: &str, duration: u64, project: &str) -> HistoryEntry {
+        HistoryEntry {
+            timestamp: Local.with_ymd_and_hms(2026, 1, day, 10, 0, 0).unwrap(),
+            status,
+            cmd: cmd.to_string(),
+            duration,
+            project: Some(project.to_string()),
+            suppressed: false,
+        }
+    }
+
+    #[test]
+    fn test_percentiles() {
+        let stats = DurationStats::from_durations(1..=100).unwrap();
+        assert_eq!(stats.count, 100);
+        assert_eq!((stats.min, stats.p50, stats.p90, stats.p99, stats.max), (1, 50, 90, 99, 100));
+        assert_eq!(stats.mean, 50.5);
+
+        // 耗时为 0 视为未知
+        let stats = DurationStats::from_durations([0, 0, 7]).unwrap();
+        assert_eq!((stats.count, stats.p50, stats.p99), (1, 7, 7));
+        assert!(DurationStats::from_durations([0]).is_none());
+    }
+
+    #[test]
+    fn test_report_groups() {
+        let entries = vec![
+            entry(1, NotificationStatus::Success, "cargo build", 10, "api"),
+            entry(1, NotificationStatus::Error, "cargo test", 30, "api"),
+            entry(2, NotificationStatus::Success, "cargo build", 20, "web"),
+            entry(2, NotificationStatus::Pending, "", 0, "web"),
+        ];
+
+        let report = UsageReport::from_entries(&entries, 1);
+
+        assert_eq!(report.overall.total, 4);
+        assert_eq!(report.overall.success_rate(), Some(2.0 / 3.0));
+        assert_eq!(report.first_date, NaiveDate::from_ymd_opt(2026, 1, 1));
+        assert_eq!(report.last_date, NaiveDate::from_ymd_opt(2026, 1, 2));
+
+        let day1 = report.by_day[&NaiveDate::from_ymd_opt(2026, 1, 1).unwrap()];
+        assert_eq!((day1.total, day1.success, day1.error), (2, 1, 1));
+        assert_eq!(report.by_project["web"].pending, 1);
+
+        assert_eq!(report.durations.unwrap().count, 3);
+        assert_eq!(
+            report.top_commands,
+            vec![CommandCount {
+                cmd: "cargo build".to_string(),
+                count: 2
+            }]
+        );
+    }
+
+    #[test]
+    fn test_empty_report() {
+        let report = UsageReport::from_entries(&[], 5);
+        assert_eq!(report.overall, StatusCounts::default());
+        assert!(report.durations.is_none());
+        assert!(report.first_date.is_none());
+        assert_eq!(format_rate(report.overall.success_rate()), "-");
+    }
+}
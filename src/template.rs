@@ -0,0 +1,424 @@
+//! 消息模板模块
+//!
+//! 渲染通知标题和内容的格式字符串：
+//! - 占位符：`{cmd}`，可接过滤器 `{cmd|basename|truncate:30}`
+//! - 条件：`{if exit_code}...{else}...{end}`，支持 `{if !project}`、`{if status == error}`、`{if exit_code != 0}`；
+//!   变量未设置时 `==` 和 `!=` 都不成立
+//! - `{{` 和 `}}` 表示字面的花括号
+
+use anyhow::{bail, Result};
+
+use crate::duration::format_secs;
+use crate::notification::NotificationStatus;
+
+/// 可用的变量，说明见消息目录中的 `template.var.<名称>`
+pub const VARIABLES: &[&str] = &[
+    "status",
+    "status_text",
+    "cmd",
+    "duration",
+    "duration_human",
+    "project",
+    "cwd",
+    "hostname",
+    "session_id",
+    "exit_code",
+    "event",
+    "title",
+    "message",
+];
+
+/// 可用的过滤器，说明见消息目录中的 `template.filter.<名称>`
+pub const FILTERS: &[&str] = &["truncate", "basename", "upper", "lower", "default"];
+
+/// 渲染模板所需的变量
+#[derive(Debug, Clone, Default)]
+pub struct TemplateContext {
+    pub status: Option<NotificationStatus>,
+    pub cmd: String,
+    pub duration: u64,
+    pub project: Option<String>,
+    pub cwd: Option<String>,
+    pub hostname: Option<String>,
+    pub session_id: Option<String>,
+    pub exit_code: Option<i32>,
+    pub event: Option<String>,
+    pub title: String,
+    pub message: String,
+}
+
+impl TemplateContext {
+    /// 变量的值，未设置时为 None
+    pub fn get(&self, name: &str) -> Option<String> {
+        match name {
+            "status" => self.status.map(|status| status.as_str().to_string()),
+            "status_text" => self.status.map(status_text),
+            "cmd" => Some(self.cmd.clone()),
+            "duration" => Some(self.duration.to_string()),
+            "duration_human" => (self.duration > 0).then(|| format_secs(self.duration)),
+            "project" => self.project.clone(),
+            "cwd" => self.cwd.clone(),
+            "hostname" => self.hostname.clone(),
+            "session_id" => self.session_id.clone(),
+            "exit_code" => self.exit_code.map(|code| code.to_string()),
+            "event" => self.event.clone(),
+            "title" => Some(self.title.clone()),
+            "message" => Some(self.message.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// 状态对应的文字
+pub fn status_text(status: NotificationStatus) -> String {
+    match status {
+        NotificationStatus::Success => t!("status.success"),
+        NotificationStatus::Error => t!("status.error"),
+        NotificationStatus::Pending => t!("status.pending"),
+    }
+}
+
+/// 解析后的模板
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Variable { name: String, filters: Vec<Filter> },
+    If { condition: Condition, then: Vec<Node>, otherwise: Vec<Node> },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Filter {
+    Truncate(usize),
+    Basename,
+    Upper,
+    Lower,
+    Default(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Condition {
+    name: String,
+    negate: bool,
+    /// `==`/`!=` 比较的值，None 表示判断变量是否非空
+    compare: Option<String>,
+}
+
+/// 解析中尚未闭合的 `{if}`
+struct OpenIf {
+    condition: Condition,
+    /// `{if}` 之前的外层节点
+    parent: Vec<Node>,
+    /// 进入 `{else}` 后为已完成的 then 分支
+    then: Option<Vec<Node>>,
+    /// 在模板中的字符位置，用于报错
+    position: usize,
+}
+
+impl Template {
+    /// 解析模板，语法错误和未知的变量、过滤器会返回错误
+    pub fn parse(source: &str) -> Result<Self> {
+        let chars: Vec<char> = source.chars().collect();
+        let mut stack: Vec<OpenIf> = Vec::new();
+        let mut nodes: Vec<Node> = Vec::new();
+        let mut text = String::new();
+        let mut index = 0;
+
+        while index < chars.len() {
+            match (chars[index], chars.get(index + 1)) {
+                ('{', Some('{')) | ('}', Some('}')) => {
+                    text.push(chars[index]);
+                    index += 2;
+                }
+                ('}', _) => bail!("{}", t!("template.error.unmatched_close", position = index + 1)),
+                ('{', _) => {
+                    let start = index;
+                    let end = chars[start..]
+                        .iter()
+                        .position(|c| *c == '}')
+                        .map(|offset| start + offset)
+                        .ok_or_else(|| anyhow::anyhow!("{}", t!("template.error.unclosed", position = start + 1)))?;
+                    let tag: String = chars[start + 1..end].iter().collect();
+                    let tag = tag.trim();
+                    index = end + 1;
+
+                    if !text.is_empty() {
+                        nodes.push(Node::Text(std::mem::take(&mut text)));
+                    }
+
+                    if let Some(condition) = tag.strip_prefix("if ") {
+                        let condition = parse_condition(condition).map_err(|e| e.context(t!("template.error.position", position = start + 1)))?;
+                        stack.push(OpenIf {
+                            condition,
+                            parent: std::mem::take(&mut nodes),
+                            then: None,
+                            position: start + 1,
+                        });
+                    } else if tag == "else" {
+                        let open = stack
+                            .last_mut()
+                            .filter(|open| open.then.is_none())
+                            .ok_or_else(|| anyhow::anyhow!("{}", t!("template.error.else_without_if", position = start + 1)))?;
+                        open.then = Some(std::mem::take(&mut nodes));
+                    } else if tag == "end" {
+                        let open = stack
+                            .pop()
+                            .ok_or_else(|| anyhow::anyhow!("{}", t!("template.error.end_without_if", position = start + 1)))?;
+                        let branch = std::mem::replace(&mut nodes, open.parent);
+                        let (then, otherwise) = match open.then {
+                            Some(then) => (then, branch),
+                            None => (branch, Vec::new()),
+                        };
+                        nodes.push(Node::If {
+                            condition: open.condition,
+                            then,
+                            otherwise,
+                        });
+                    } else {
+                        nodes.push(parse_variable(tag).map_err(|e| e.context(t!("template.error.position", position = start + 1)))?);
+                    }
+                }
+                (c, _) => {
+                    text.push(c);
+                    index += 1;
+                }
+            }
+        }
+
+        if let Some(open) = stack.last() {
+            bail!("{}", t!("template.error.missing_end", position = open.position));
+        }
+        if !text.is_empty() {
+            nodes.push(Node::Text(text));
+        }
+        Ok(Self { nodes })
+    }
+
+    pub fn render(&self, context: &TemplateContext) -> String {
+        let mut output = String::new();
+        render_nodes(&self.nodes, context, &mut output);
+        output
+    }
+}
+
+/// 解析并渲染模板
+pub fn render(source: &str, context: &TemplateContext) -> Result<String> {
+    Ok(Template::parse(source)?.render(context))
+}
+
+fn check_variable(name: &str) -> Result<()> {
+    if !VARIABLES.contains(&name) {
+        bail!("{}", t!("template.error.unknown_variable", name = format!("{:?}", name)));
+    }
+    Ok(())
+}
+
+fn parse_variable(tag: &str) -> Result<Node> {
+    let mut parts = tag.split('|').map(str::trim);
+    let name = parts.next().unwrap_or_default();
+    check_variable(name)?;
+
+    let filters = parts
+        .map(|filter| {
+            let (filter_name, argument) = match filter.split_once(':') {
+                Some((filter_name, argument)) => (filter_name.trim(), Some(argument)),
+                None => (filter, None),
+            };
+            Ok(match (filter_name, argument) {
+                ("truncate", Some(length)) => Filter::Truncate(
+                    length
+                        .trim()
+                        .parse()
+                        .map_err(|_| anyhow::anyhow!("{}", t!("template.error.truncate_length", value = format!("{:?}", length))))?,
+                ),
+                ("truncate", None) => bail!("{}", t!("template.error.truncate_missing")),
+                ("basename", None) => Filter::Basename,
+                ("upper", None) => Filter::Upper,
+                ("lower", None) => Filter::Lower,
+                ("default", Some(text)) => Filter::Default(text.to_string()),
+                _ => bail!("{}", t!("template.error.unknown_filter", name = format!("{:?}", filter))),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Node::Variable {
+        name: name.to_string(),
+        filters,
+    })
+}
+
+fn parse_condition(condition: &str) -> Result<Condition> {
+    let condition = condition.trim();
+    let (expression, compare, negate) = if let Some((name, value)) = condition.split_once("!=") {
+        (name, Some(value), true)
+    } else if let Some((name, value)) = condition.split_once("==") {
+        (name, Some(value), false)
+    } else {
+        match condition.strip_prefix('!') {
+            Some(name) => (name, None, true),
+            None => (condition, None, false),
+        }
+    };
+
+    let name = expression.trim();
+    check_variable(name)?;
+    let compare = compare.map(|value| {
+        let value = value.trim();
+        value
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .or_else(|| value.strip_prefix('\'').and_then(|value| value.strip_suffix('\'')))
+            .unwrap_or(value)
+            .to_string()
+    });
+
+    Ok(Condition {
+        name: name.to_string(),
+        negate,
+        compare,
+    })
+}
+
+fn render_nodes(nodes: &[Node], context: &TemplateContext, output: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => output.push_str(text),
+            Node::Variable { name, filters } => {
+                let value = context.get(name).unwrap_or_default();
+                output.push_str(&filters.iter().fold(value, apply_filter));
+            }
+            Node::If { condition, then, otherwise } => {
+                let value = context.get(&condition.name);
+                let matched = match (&condition.compare, value) {
+                    // 未设置的变量无法比较，`==` 和 `!=` 都不成立
+                    (Some(_), None) => false,
+                    (Some(expected), Some(value)) => (value == *expected) != condition.negate,
+                    (None, value) => value.is_some_and(|value| !value.is_empty()) != condition.negate,
+                };
+                let branch = if matched { then } else { otherwise };
+                render_nodes(branch, context, output);
+            }
+        }
+    }
+}
+
+fn apply_filter(value: String, filter: &Filter) -> String {
+    match filter {
+        Filter::Truncate(length) => {
+            if value.chars().count() <= *length {
+                value
+            } else if *length == 0 {
+                String::new()
+            } else {
+                let mut truncated: String = value.chars().take(length - 1).collect();
+                truncated.push('…');
+                truncated
+            }
+        }
+        Filter::Basename => value
+            .trim_end_matches(['/', '\\'])
+            .rsplit(['/', '\\'])
+            .next()
+            .unwrap_or_default()
+            .to_string(),
+        Filter::Upper => value.to_uppercase(),
+        Filter::Lower => value.to_lowercase(),
+        Filter::Default(text) if value.is_empty() => text.clone(),
+        Filter::Default(_) => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> TemplateContext {
+        TemplateContext {
+            status: Some(NotificationStatus::Error),
+            cmd: "cargo build --release".to_string(),
+            duration: 3725,
+            project: Some("ccn".to_string()),
+            cwd: Some("/home/dev/work/ccn/".to_string()),
+            hostname: Some("devbox".to_string()),
+            session_id: None,
+            exit_code: Some(101),
+            event: None,
+            title: "任务失败".to_string(),
+            message: "cargo build --release (耗时: 3725秒)".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_placeholders() {
+        let rendered = render("[{hostname}] {project}: {cmd} {status_text}，用时 {duration_human}", &context()).unwrap();
+        assert_eq!(rendered, "[devbox] ccn: cargo build --release 失败，用时 1小时2分5秒");
+        assert_eq!(render("{title} / {message}", &context()).unwrap(), "任务失败 / cargo build --release (耗时: 3725秒)");
+        assert_eq!(render("{{cmd}} = {cmd}", &context()).unwrap(), "{cmd} = cargo build --release");
+    }
+
+    #[test]
+    fn test_filters() {
+        let context = context();
+        assert_eq!(render("{cmd|truncate:8}", &context).unwrap(), "cargo b…");
+        assert_eq!(render("{cmd|truncate:100}", &context).unwrap(), "cargo build --release");
+        assert_eq!(render("{cwd|basename}", &context).unwrap(), "ccn");
+        assert_eq!(render("{cwd|basename|upper}", &context).unwrap(), "CCN");
+        assert_eq!(render("{session_id|default:无会话}", &context).unwrap(), "无会话");
+        assert_eq!(render("{project | default:无 | upper}", &context).unwrap(), "CCN");
+    }
+
+    #[test]
+    fn test_conditionals() {
+        let context = context();
+        assert_eq!(render("{if exit_code}退出码 {exit_code}{end}", &context).unwrap(), "退出码 101");
+        assert_eq!(render("{if session_id}会话{else}无会话{end}", &context).unwrap(), "无会话");
+        assert_eq!(render("{if !session_id}无会话{end}", &context).unwrap(), "无会话");
+        assert_eq!(render("{if status == error}❌{else}✅{end}", &context).unwrap(), "❌");
+        assert_eq!(render("{if status != 'error'}✅{else}❌{end}", &context).unwrap(), "❌");
+        assert_eq!(
+            render("{if status == error}失败{if exit_code != 0}（{exit_code}）{end}{else}成功{end}!", &context).unwrap(),
+            "失败（101）!"
+        );
+
+        let success = TemplateContext {
+            status: Some(NotificationStatus::Success),
+            exit_code: None,
+            ..context
+        };
+        assert_eq!(
+            render("{if status == error}失败{if exit_code != 0}（{exit_code}）{end}{else}成功{end}!", &success).unwrap(),
+            "成功!"
+        );
+
+        // 普通通知和 hook 事件没有退出码：与未设置的变量比较时 `!=` 也不成立
+        let no_exit_code = TemplateContext {
+            status: Some(NotificationStatus::Error),
+            ..success.clone()
+        };
+        assert_eq!(
+            render("{if status == error}失败{if exit_code != 0}（{exit_code}）{end}{else}成功{end}!", &no_exit_code).unwrap(),
+            "失败!"
+        );
+        assert_eq!(render("{if exit_code == 0}成功{else}未知{end}", &no_exit_code).unwrap(), "未知");
+        assert_eq!(render("{if !exit_code}无退出码{end}", &no_exit_code).unwrap(), "无退出码");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |source: &str| format!("{:#}", Template::parse(source).unwrap_err());
+        assert!(error("{cmdd}").ends_with(&t!("template.error.unknown_variable", name = "\"cmdd\"")));
+        assert!(error("{cmd|shout}").ends_with(&t!("template.error.unknown_filter", name = "\"shout\"")));
+        assert!(error("{cmd|truncate:x}").ends_with(&t!("template.error.truncate_length", value = "\"x\"")));
+        assert_eq!(error("{cmd"), t!("template.error.unclosed", position = 1));
+        assert_eq!(error("a } b"), t!("template.error.unmatched_close", position = 3));
+        assert_eq!(error("{if cmd}x"), t!("template.error.missing_end", position = 1));
+        assert_eq!(error("x{end}"), t!("template.error.end_without_if", position = 2));
+        assert_eq!(error("{else}"), t!("template.error.else_without_if", position = 1));
+        assert_eq!(error("{if cmd}{else}{else}{end}"), t!("template.error.else_without_if", position = 15));
+        assert!(error("ok {if nope}x{end}").starts_with(&t!("template.error.position", position = 4)));
+    }
+}
@@ -0,0 +1,351 @@
+//! Webhook 通知模块
+//!
+//! 通过 HTTP POST 将通知以 JSON 形式发送到配置的 URL（适用于远程开发机等无桌面环境）
+
+use anyhow::{Context, Result};
+use regex::{Captures, Regex};
+use serde_json::{json, Value};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::config::WebhookConfig;
+use crate::notification::{NotificationContext, NotificationManager, NotificationStatus};
+
+/// Webhook 通知管理器
+pub struct WebhookNotificationManager {
+    config: WebhookConfig,
+    agent: ureq::Agent,
+    hostname: String,
+}
+
+impl WebhookNotificationManager {
+    pub fn new(config: WebhookConfig) -> Self {
+        let agent = ureq::Agent::config_builder()
+            .timeout_global(Some(Duration::from_millis(config.timeout)))
+            .http_status_as_error(false)
+            .build()
+            .into();
+
+        let hostname = gethostname::gethostname().to_string_lossy().to_string();
+
+        Self {
+            config,
+            agent,
+            hostname,
+        }
+    }
+
+    /// 构建请求体：未配置 body 时使用默认字段，否则替换其中的占位符
+    pub(crate) fn build_body(
+        &self,
+        status: NotificationStatus,
+        title: &str,
+        message: &str,
+        context: &NotificationContext,
+    ) -> Value {
+        let default_body = json!({
+            "status": status.as_str(),
+            "title": title,
+            "message": message,
+            "cmd": context.cmd,
+            "duration": context.duration_sec,
+            "hostname": self.hostname,
+            "timestamp": chrono::Local::now().to_rfc3339(),
+        });
+
+        match &self.config.body {
+            Some(template) => render_placeholders(template, &default_body),
+            None => default_body,
+        }
+    }
+
+    /// 发送一次请求，返回是否值得重试
+    fn post_once(&self, body: &Value) -> std::result::Result<(), (anyhow::Error, bool)> {
+        let mut request = self.agent.post(&self.config.url);
+        for (name, value) in &self.config.headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+
+        match request.send_json(body) {
+            Ok(response) => {
+                let code = response.status().as_u16();
+                if (200..300).contains(&code) {
+                    Ok(())
+                } else {
+                    // 5xx 和 429 可以重试，其他 4xx 重试也没用
+                    let retryable = code >= 500 || code == 429;
+                    Err((anyhow::anyhow!("{}", t!("notification.webhook.http_status", code = code)), retryable))
+                }
+            }
+            Err(e) => Err((anyhow::anyhow!("{}", t!("notification.webhook.request_failed", error = e)), true)),
+        }
+    }
+}
+
+impl NotificationManager for WebhookNotificationManager {
+    fn send_notification(
+        &self,
+        status: NotificationStatus,
+        title: &str,
+        message: &str,
+        duration_ms: u64,
+    ) -> Result<()> {
+        self.send_with_context(status, title, message, duration_ms, &NotificationContext::default())
+    }
+
+    fn send_with_context(
+        &self,
+        status: NotificationStatus,
+        title: &str,
+        message: &str,
+        _duration_ms: u64,
+        context: &NotificationContext,
+    ) -> Result<()> {
+        let body = self.build_body(status, title, message, context);
+        let mut delay = Duration::from_millis(self.config.retry_delay);
+        let mut attempt = 0;
+
+        loop {
+            match self.post_once(&body) {
+                Ok(()) => {
+                    log::info!("{}", t!("notification.webhook.sent", url = self.config.url));
+                    return Ok(());
+                }
+                Err((e, retryable)) if retryable && attempt < self.config.retries => {
+                    attempt += 1;
+                    log::warn!(
+                        "{}",
+                        t!("notification.webhook.retry", error = e, delay = format!("{:?}", delay), attempt = attempt)
+                    );
+                    std::thread::sleep(delay);
+                    delay *= 2;
+                }
+                Err((e, _)) => {
+                    return Err(e).with_context(|| t!("notification.webhook.failed", url = self.config.url));
+                }
+            }
+        }
+    }
+
+    fn is_available(&self) -> bool {
+        !self.config.url.is_empty()
+    }
+}
+
+/// 递归替换 JSON 中字符串的 `{field}` 占位符
+///
+/// 整个字符串恰好是一个占位符时保留原始类型（如 `"{duration}"` 会变成数字）。
+/// 占位符只替换一遍，替换进来的值中的 `{...}` 原样保留；未知的占位符也原样保留
+fn render_placeholders(template: &Value, fields: &Value) -> Value {
+    match template {
+        Value::String(text) => {
+            if let Some(value) = text
+                .strip_prefix('{')
+                .and_then(|t| t.strip_suffix('}'))
+                .and_then(|name| fields.get(name))
+            {
+                return value.clone();
+            }
+
+            static PLACEHOLDER: OnceLock<Regex> = OnceLock::new();
+            let placeholder = PLACEHOLDER.get_or_init(|| Regex::new(r"\{(\w+)\}").unwrap());
+            let rendered = placeholder.replace_all(text, |caps: &Captures| match fields.get(&caps[1]) {
+                Some(Value::String(s)) => s.clone(),
+                Some(other) => other.to_string(),
+                None => caps[0].to_string(),
+            });
+            Value::String(rendered.into_owned())
+        }
+        Value::Array(items) => Value::Array(
+            items.iter().map(|item| render_placeholders(item, fields)).collect(),
+        ),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| (key.clone(), render_placeholders(value, fields)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    /// 收到的 HTTP 请求
+    #[derive(Debug, Clone)]
+    struct ReceivedRequest {
+        headers: Vec<(String, String)>,
+        body: Value,
+    }
+
+    /// 本地 HTTP 替身服务器，依次使用给定的状态码响应
+    fn start_server(responses: Vec<u16>) -> (String, Arc<Mutex<Vec<ReceivedRequest>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let received_clone = Arc::clone(&received);
+
+        std::thread::spawn(move || {
+            for code in responses {
+                let Ok((stream, _)) = listener.accept() else { return };
+                let mut reader = BufReader::new(stream);
+
+                let mut headers = Vec::new();
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let line = line.trim_end();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        let (name, value) = (name.trim().to_lowercase(), value.trim().to_string());
+                        if name == "content-length" {
+                            content_length = value.parse().unwrap();
+                        }
+                        headers.push((name, value));
+                    }
+                }
+
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                received_clone.lock().unwrap().push(ReceivedRequest {
+                    headers,
+                    body: serde_json::from_slice(&body).unwrap(),
+                });
+
+                let response = format!(
+                    "HTTP/1.1 {} Test\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    code
+                );
+                reader.get_mut().write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        (url, received)
+    }
+
+    fn webhook_config(url: &str) -> WebhookConfig {
+        WebhookConfig {
+            url: url.to_string(),
+            timeout: 2000,
+            retries: 0,
+            retry_delay: 10,
+            headers: BTreeMap::new(),
+            body: None,
+        }
+    }
+
+    fn context() -> NotificationContext {
+        NotificationContext {
+            cmd: "cargo build".to_string(),
+            duration_sec: 42,
+        }
+    }
+
+    #[test]
+    fn test_post_default_body_with_headers() {
+        let (url, received) = start_server(vec![200]);
+        let mut config = webhook_config(&url);
+        config.headers.insert("X-Token".to_string(), "secret".to_string());
+
+        let manager = WebhookNotificationManager::new(config);
+        manager
+            .send_with_context(NotificationStatus::Error, "任务失败", "cargo build (耗时: 42秒)", 5000, &context())
+            .unwrap();
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+
+        let body = &received[0].body;
+        assert_eq!(body["status"], "error");
+        assert_eq!(body["title"], "任务失败");
+        assert_eq!(body["message"], "cargo build (耗时: 42秒)");
+        assert_eq!(body["cmd"], "cargo build");
+        assert_eq!(body["duration"], 42);
+        assert!(body["hostname"].is_string());
+
+        assert!(received[0].headers.contains(&("x-token".to_string(), "secret".to_string())));
+        assert!(received[0]
+            .headers
+            .iter()
+            .any(|(name, value)| name == "content-type" && value.contains("application/json")));
+    }
+
+    #[test]
+    fn test_retry_on_server_error() {
+        let (url, received) = start_server(vec![503, 500, 200]);
+        let mut config = webhook_config(&url);
+        config.retries = 2;
+
+        let manager = WebhookNotificationManager::new(config);
+        manager
+            .send_notification(NotificationStatus::Success, "任务完成", "npm test", 5000)
+            .unwrap();
+
+        assert_eq!(received.lock().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_no_retry_on_client_error() {
+        let (url, received) = start_server(vec![400, 200]);
+        let mut config = webhook_config(&url);
+        config.retries = 3;
+
+        let manager = WebhookNotificationManager::new(config);
+        assert!(manager
+            .send_notification(NotificationStatus::Success, "任务完成", "npm test", 5000)
+            .is_err());
+
+        assert_eq!(received.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_unreachable_url_fails() {
+        // 绑定后立即释放端口，保证连接被拒绝
+        let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let mut config = webhook_config(&format!("http://127.0.0.1:{}/hook", port));
+        config.retries = 1;
+
+        let manager = WebhookNotificationManager::new(config);
+        assert!(manager
+            .send_notification(NotificationStatus::Success, "任务完成", "npm test", 5000)
+            .is_err());
+    }
+
+    #[test]
+    fn test_custom_body_placeholders() {
+        let mut config = webhook_config("http://127.0.0.1:9/hook");
+        config.body = Some(json!({
+            "text": "[{hostname}] {title}: {message}",
+            "duration": "{duration}",
+            "tags": ["ccn", "{status}"],
+            "priority": 1
+        }));
+
+        let manager = WebhookNotificationManager::new(config);
+        let body = manager.build_body(NotificationStatus::Success, "任务完成", "npm test", &context());
+
+        assert_eq!(body["text"], format!("[{}] 任务完成: npm test", manager.hostname));
+        assert_eq!(body["duration"], 42);
+        assert_eq!(body["tags"], json!(["ccn", "success"]));
+        assert_eq!(body["priority"], 1);
+    }
+
+    #[test]
+    fn test_placeholders_in_values_are_not_expanded() {
+        let mut config = webhook_config("http://127.0.0.1:9/hook");
+        config.body = Some(json!({ "text": "{title}: {message} {unknown}" }));
+
+        let manager = WebhookNotificationManager::new(config);
+        let body = manager.build_body(NotificationStatus::Success, "任务完成", "printf '{title} {status}'", &context());
+
+        assert_eq!(body["text"], "任务完成: printf '{title} {status}' {unknown}");
+    }
+}
@@ -5,11 +5,18 @@
 use anyhow::Result;
 use std::io::{self, Write};
 
-use crate::config::{save_config, Config, FocusAssistantMode};
+use crate::duration::{format_millis, format_secs};
+use crate::config::{save_config, Config, FocusAssistantMode, HookEventKind, MatchRule};
 
 /// 配置向导
 pub struct ConfigWizard;
 
+impl Default for ConfigWizard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ConfigWizard {
     pub fn new() -> Self {
         Self
@@ -18,22 +25,25 @@ impl ConfigWizard {
     /// 运行配置向导
     pub fn run(&self) -> Result<Config> {
         println!("╔════════════════════════════════════════╗");
-        println!("║   Claude Code Notify 配置向导         ║");
+        println!("  {}", t!("wizard.title"));
         println!("╚════════════════════════════════════════╝");
         println!();
 
-        // 加载现有配置或创建默认配置
-        let mut config = Config::default();
-
         // 配置声音
-        config.sound_enabled = self.ask_bool("是否启用通知声音？", true)?;
+        let sound_enabled = self.ask_bool(&t!("wizard.sound"), true)?;
+
+        // 加载现有配置或创建默认配置
+        let mut config = Config {
+            sound_enabled,
+            ..Config::default()
+        };
 
         // 配置专注助手模式
-        println!("\n专注助手模式：");
-        println!("  1. respect - 尊重系统专注助手设置");
-        println!("  2. always - 始终播放声音");
-        println!("  3. never - 从不播放声音");
-        let mode_choice = self.ask_choice("选择专注助手模式", &["respect", "always", "never"], 0)?;
+        println!("\n{}", t!("wizard.focus.heading"));
+        println!("  1. respect - {}", t!("wizard.focus.respect"));
+        println!("  2. always - {}", t!("wizard.focus.always"));
+        println!("  3. never - {}", t!("wizard.focus.never"));
+        let mode_choice = self.ask_choice(&t!("wizard.focus.prompt"), &["respect", "always", "never"], 0)?;
         config.focus_assistant_mode = match mode_choice {
             0 => FocusAssistantMode::Respect,
             1 => FocusAssistantMode::Always,
@@ -42,37 +52,49 @@ impl ConfigWizard {
         };
 
         // 配置最小阈值
-        println!("\n最小通知阈值：");
-        println!("  执行时间低于此值的任务将不会发送通知（错误除外）");
-        config.threshold.min_duration = self.ask_number("输入最小阈值（秒）", 10, 1, 3600)?;
+        println!("\n{}", t!("wizard.threshold.heading"));
+        println!("  {}", t!("wizard.threshold.help"));
+        config.threshold.min_duration = self.ask_number(&t!("wizard.threshold.prompt"), 10, 1, 3600)?;
 
         // 配置白名单
-        println!("\n白名单命令：");
-        println!("  在白名单中的命令无论耗时多少都会发送通知");
-        println!("  输入命令关键字（如 deploy, release），或直接回车跳过");
-        let whitelist_input = self.ask_input("输入白名单命令（用逗号分隔）", "")?;
+        println!("\n{}", t!("wizard.whitelist.heading"));
+        println!("  {}", t!("wizard.whitelist.help"));
+        println!("  {}", t!("wizard.whitelist.hint"));
+        let whitelist_input = self.ask_input(&t!("wizard.whitelist.prompt"), "")?;
         if !whitelist_input.is_empty() {
             config.threshold.whitelist = whitelist_input
                 .split(',')
-                .map(|s| s.trim().to_string())
+                .map(str::trim)
                 .filter(|s| !s.is_empty())
+                .map(MatchRule::substring)
                 .collect();
         }
 
         // 配置聚合
-        config.aggregation.enabled = self.ask_bool("\n是否启用通知聚合？", true)?;
+        config.aggregation.enabled = self.ask_bool(&format!("\n{}", t!("wizard.aggregation.enabled")), true)?;
         if config.aggregation.enabled {
-            config.aggregation.window = self.ask_number("聚合时间窗口（毫秒）", 5000, 1000, 60000)?;
-            config.aggregation.max_toasts = self.ask_number("最大聚合数量", 3, 1, 10)? as usize;
+            config.aggregation.window = self.ask_number(&t!("wizard.aggregation.window"), 5000, 1000, 60000)?;
+            config.aggregation.max_toasts = self.ask_number(&t!("wizard.aggregation.max_toasts"), 3, 1, 10)? as usize;
+        }
+
+        // 配置 hook 事件
+        println!("\n{}", t!("wizard.hooks.heading"));
+        println!("  {}", t!("wizard.hooks.help"));
+        config.hooks.events = vec![HookEventKind::Notification, HookEventKind::PermissionRequest];
+        if self.ask_bool(&t!("wizard.hooks.stop"), true)? {
+            config.hooks.events.push(HookEventKind::Stop);
+        }
+        if self.ask_bool(&t!("wizard.hooks.subagent_stop"), false)? {
+            config.hooks.events.push(HookEventKind::SubagentStop);
         }
 
         // 配置日志级别
-        println!("\n日志级别：");
-        println!("  1. debug - 详细调试信息");
-        println!("  2. info - 一般信息（默认）");
-        println!("  3. warn - 警告信息");
-        println!("  4. error - 仅错误信息");
-        let log_choice = self.ask_choice("选择日志级别", &["debug", "info", "warn", "error"], 1)?;
+        println!("\n{}", t!("wizard.log.heading"));
+        println!("  1. debug - {}", t!("wizard.log.debug"));
+        println!("  2. info - {}", t!("wizard.log.info"));
+        println!("  3. warn - {}", t!("wizard.log.warn"));
+        println!("  4. error - {}", t!("wizard.log.error"));
+        let log_choice = self.ask_choice(&t!("wizard.log.prompt"), &["debug", "info", "warn", "error"], 1)?;
         config.logging.level = match log_choice {
             0 => "debug".to_string(),
             1 => "info".to_string(),
@@ -85,13 +107,13 @@ impl ConfigWizard {
         self.show_preview(&config);
 
         // 确认保存
-        let confirmed = self.ask_bool("\n确认保存配置？", true)?;
+        let confirmed = self.ask_bool(&format!("\n{}", t!("wizard.confirm_save")), true)?;
 
         if confirmed {
             save_config(&config)?;
-            println!("\n✅ 配置已保存！");
+            println!("\n✅ {}", t!("wizard.saved"));
         } else {
-            println!("\n配置未保存");
+            println!("\n{}", t!("wizard.not_saved"));
         }
 
         Ok(config)
@@ -100,18 +122,24 @@ impl ConfigWizard {
     /// 显示配置预览
     fn show_preview(&self, config: &Config) {
         println!("\n╔════════════════════════════════════════╗");
-        println!("║          配置预览                     ║");
+        println!("  {}", t!("wizard.preview.title"));
         println!("╚════════════════════════════════════════╝");
-        println!("声音: {}", if config.sound_enabled { "✅ 启用" } else { "❌ 禁用" });
-        println!("专注助手: {:?}", config.focus_assistant_mode);
-        println!("最小阈值: {} 秒", config.threshold.min_duration);
-        println!("白名单: {:?}", config.threshold.whitelist);
-        println!("聚合: {}", if config.aggregation.enabled { "✅ 启用" } else { "❌ 禁用" });
+        let on_off = |enabled: bool| {
+            if enabled { format!("✅ {}", t!("config.summary.on")) } else { format!("❌ {}", t!("config.summary.off")) }
+        };
+        println!("{}", t!("config.summary.sound", value = on_off(config.sound_enabled)));
+        println!("{}", t!("config.summary.focus", value = format!("{:?}", config.focus_assistant_mode)));
+        println!("{}", t!("config.summary.threshold", value = format_secs(config.threshold.min_duration)));
+        let whitelist: Vec<String> = config.threshold.whitelist.iter().map(|rule| rule.to_string()).collect();
+        println!("{}", t!("config.summary.whitelist", value = whitelist.join(", ")));
+        println!("{}", t!("config.summary.aggregation", value = on_off(config.aggregation.enabled)));
         if config.aggregation.enabled {
-            println!("  - 聚合窗口: {} 毫秒", config.aggregation.window);
-            println!("  - 最大数量: {}", config.aggregation.max_toasts);
+            println!("  - {}", t!("config.summary.window", value = format_millis(config.aggregation.window)));
+            println!("  - {}", t!("wizard.preview.max_toasts", value = config.aggregation.max_toasts));
         }
-        println!("日志级别: {}", config.logging.level);
+        println!("{}", t!("config.summary.log_level", value = config.logging.level));
+        let event_names: Vec<&str> = config.hooks.events.iter().map(|e| e.event_name()).collect();
+        println!("{}", t!("config.summary.hooks", value = event_names.join(", ")));
     }
 
     /// 询问布尔值问题
@@ -134,7 +162,7 @@ impl ConfigWizard {
                 "y" | "yes" | "是" => return Ok(true),
                 "n" | "no" | "否" => return Ok(false),
                 _ => {
-                    println!("请输入 y 或 n");
+                    println!("{}", t!("wizard.input.yes_no"));
                 }
             }
         }
@@ -143,7 +171,7 @@ impl ConfigWizard {
     /// 询问数字
     fn ask_number(&self, prompt: &str, default: u64, min: u64, max: u64) -> Result<u64> {
         loop {
-            print!("{} [默认: {}]: ", prompt, default);
+            print!("{} [{}]: ", prompt, t!("wizard.input.default", value = default));
             io::stdout().flush()?;
 
             let mut input = String::new();
@@ -158,10 +186,10 @@ impl ConfigWizard {
             match input.parse::<u64>() {
                 Ok(n) if n >= min && n <= max => return Ok(n),
                 Ok(_) => {
-                    println!("请输入 {} 到 {} 之间的数字", min, max);
+                    println!("{}", t!("wizard.input.range", min = min, max = max));
                 }
                 Err(_) => {
-                    println!("请输入有效的数字");
+                    println!("{}", t!("wizard.input.number"));
                 }
             }
         }
@@ -169,7 +197,7 @@ impl ConfigWizard {
 
     /// 询问文本输入
     fn ask_input(&self, prompt: &str, default: &str) -> Result<String> {
-        print!("{} [默认: {}]: ", prompt, default);
+        print!("{} [{}]: ", prompt, t!("wizard.input.default", value = default));
         io::stdout().flush()?;
 
         let mut input = String::new();
@@ -191,7 +219,7 @@ impl ConfigWizard {
             for (i, opt) in options.iter().enumerate() {
                 println!("  {}. {}", i + 1, opt);
             }
-            print!("请选择 [默认: {}]: ", default + 1);
+            print!("{} [{}]: ", t!("wizard.input.choose"), t!("wizard.input.default", value = default + 1));
             io::stdout().flush()?;
 
             let mut input = String::new();
@@ -206,7 +234,7 @@ impl ConfigWizard {
             match input.parse::<usize>() {
                 Ok(n) if n >= 1 && n <= options.len() => return Ok(n - 1),
                 _ => {
-                    println!("请输入 1 到 {} 之间的数字", options.len());
+                    println!("{}", t!("wizard.input.range", min = 1, max = options.len()));
                 }
             }
         }
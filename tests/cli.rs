@@ -0,0 +1,268 @@
+//! 命令行端到端测试：在临时 HOME 中运行 ccn 可执行文件
+//!
+//! 配置、状态文件和后台刷新进程都在临时 HOME 中，不影响本机的 ccn
+
+#![cfg(unix)]
+
+use chrono::{TimeDelta, Utc};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::time::{Duration, Instant};
+
+/// 隔离的 HOME 目录，结束时删除
+struct TestHome {
+    path: PathBuf,
+}
+
+impl TestHome {
+    fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("ccn-it-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        Self { path }
+    }
+
+    /// 全局配置文件所在目录
+    fn config_dir(&self) -> PathBuf {
+        #[cfg(target_os = "macos")]
+        return self.path.join("Library").join("Application Support").join("claude-code-notify");
+        #[cfg(not(target_os = "macos"))]
+        return self.path.join(".config").join("claude-code-notify");
+    }
+
+    /// 写入全局配置文件
+    fn write_config(&self, yaml: &str) {
+        let dir = self.config_dir();
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("config.yaml"), yaml).unwrap();
+    }
+
+    /// 状态文件所在目录
+    fn state_dir(&self) -> PathBuf {
+        self.path.join("claude-code-notify")
+    }
+
+    /// 通知日志中的所有行
+    fn log_lines(&self, name: &str) -> Vec<String> {
+        fs::read_to_string(self.path.join(name))
+            .unwrap_or_default()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// 以该 HOME 运行 ccn，忽略外部环境中的 CCN_* 覆盖
+    fn command(&self, args: &[&str]) -> Command {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_ccn"));
+        command
+            .args(args)
+            .env("HOME", &self.path)
+            .env("LANG", "C")
+            .env_remove("RUST_LOG")
+            .stdin(Stdio::null());
+        for (key, _) in std::env::vars().filter(|(key, _)| key.starts_with("CCN_")) {
+            command.env_remove(key);
+        }
+        command
+    }
+
+    fn ccn(&self, args: &[&str]) -> Output {
+        self.ccn_in(&self.path, args)
+    }
+
+    /// 在 `dir` 中运行 ccn，断言成功退出
+    fn ccn_in(&self, dir: &Path, args: &[&str]) -> Output {
+        let output = self.command(args).current_dir(dir).output().unwrap();
+        assert!(output.status.success(), "ccn {:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+        output
+    }
+}
+
+impl Drop for TestHome {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+/// 轮询直到条件成立，超时返回 false
+fn wait_until(timeout: Duration, mut condition: impl FnMut() -> bool) -> bool {
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        if condition() {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    condition()
+}
+
+/// defer 时段结束时，后台进程发送暂存通知的摘要，不必等下一次调用
+#[test]
+fn test_deferred_digest_is_sent_when_period_ends() {
+    let home = TestHome::new("deferred");
+    let now = Utc::now();
+    let start = (now - TimeDelta::minutes(1)).format("%H:%M:%S");
+    let end = (now + TimeDelta::seconds(3)).format("%H:%M:%S");
+    home.write_config(&format!(
+        r#"
+notifiers:
+  - type: log
+    path: ~/notify.log
+schedule:
+  timezone: UTC
+  quiet_hours:
+    - start: "{start}"
+      end: "{end}"
+      action: defer
+"#
+    ));
+
+    home.ccn(&["notify", "--status", "success", "--duration", "60", "--cmd", "cargo build"]);
+    let deferred = home.state_dir().join("deferred.json");
+    assert!(deferred.exists(), "通知应被暂存");
+    assert!(home.log_lines("notify.log").is_empty());
+
+    assert!(
+        wait_until(Duration::from_secs(20), || !home.log_lines("notify.log").is_empty()),
+        "defer 时段结束后应发送摘要"
+    );
+    let lines = home.log_lines("notify.log");
+    assert_eq!(lines.len(), 1, "{:?}", lines);
+    assert!(lines[0].contains("cmd=cargo build"), "{}", lines[0]);
+    assert!(!deferred.exists());
+}
+
+/// 聚合窗口结束时按开启窗口的项目配置发送：使用项目 `.ccn.yaml` 中的渠道和规则指定的 sinks
+#[test]
+fn test_aggregation_flush_uses_project_config_and_sinks() {
+    let home = TestHome::new("aggregate-project");
+    home.write_config(
+        r#"
+notifiers:
+  - type: log
+    path: ~/global.log
+aggregation:
+  enabled: true
+  window: 1s
+"#,
+    );
+    let project = home.path.join("work").join("app");
+    fs::create_dir_all(&project).unwrap();
+    fs::write(
+        project.join(".ccn.yaml"),
+        r#"
+notifiers:
+  - name: team
+    type: log
+    path: ~/team.log
+  - name: other
+    type: log
+    path: ~/other.log
+rules:
+  - name: builds
+    when:
+      cmd: cargo
+    action: aggregate
+    sinks: [team]
+"#,
+    )
+    .unwrap();
+
+    home.ccn_in(&project, &["notify", "--status", "success", "--duration", "60", "--cmd", "cargo build"]);
+    home.ccn_in(&project, &["notify", "--status", "success", "--duration", "60", "--cmd", "cargo test"]);
+
+    assert!(
+        wait_until(Duration::from_secs(20), || !home.log_lines("team.log").is_empty()),
+        "聚合窗口结束后应发送到项目配置的渠道"
+    );
+    let lines = home.log_lines("team.log");
+    assert_eq!(lines.len(), 1, "{:?}", lines);
+    assert!(lines[0].contains("cmd=cargo build, cargo test"), "{}", lines[0]);
+    assert!(home.log_lines("other.log").is_empty());
+    assert!(home.log_lines("global.log").is_empty());
+}
+
+/// 多个 ccn 进程同时写入同一个聚合缓冲区：每个事件恰好发送一次
+#[test]
+fn test_concurrent_processes_deliver_every_event_once() {
+    const PROCESSES: usize = 24;
+
+    let home = TestHome::new("aggregate-processes");
+    home.write_config(
+        r#"
+notifiers:
+  - type: log
+    path: ~/notify.log
+aggregation:
+  enabled: true
+  window: 2s
+  max_toasts: 5
+"#,
+    );
+
+    let children: Vec<_> = (0..PROCESSES)
+        .map(|i| {
+            let cmd = format!("event-{}", i);
+            home.command(&["notify", "--status", "success", "--duration", "60", "--cmd", &cmd])
+                .current_dir(&home.path)
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+                .unwrap()
+        })
+        .collect();
+    for mut child in children {
+        assert!(child.wait().unwrap().success());
+    }
+
+    // 聚合通知的日志行为 `... (cmd=event-1, event-7, duration=...)`
+    let delivered = || -> Vec<String> {
+        home.log_lines("notify.log")
+            .iter()
+            .filter_map(|line| line.split_once("(cmd=")?.1.rsplit_once(", duration=").map(|(cmds, _)| cmds.to_string()))
+            .flat_map(|cmds| cmds.split(", ").map(str::to_string).collect::<Vec<_>>())
+            .collect()
+    };
+    assert!(
+        wait_until(Duration::from_secs(20), || delivered().len() >= PROCESSES),
+        "只收到 {} 个事件",
+        delivered().len()
+    );
+    // 等待剩余的刷新进程结束，确认没有重复发送
+    std::thread::sleep(Duration::from_secs(3));
+
+    let mut events = delivered();
+    events.sort();
+    let mut expected: Vec<String> = (0..PROCESSES).map(|i| format!("event-{}", i)).collect();
+    expected.sort();
+    assert_eq!(events, expected);
+}
+
+/// 错误和日志使用 `--lang` 指定的语言，没有残留的中文
+#[test]
+fn test_errors_and_logs_follow_language() {
+    let home = TestHome::new("language");
+    home.write_config("aggregation:\n  window: soon\n  max_toasts: 0\ncolour: red\n");
+    let has_chinese = |text: &str| text.chars().any(|c| ('\u{4e00}'..='\u{9fff}').contains(&c));
+
+    let output = home.command(&["--lang", "en-US", "config", "validate"]).output().unwrap();
+    assert!(!output.status.success());
+    let text = format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+    assert!(text.contains("Invalid duration \"soon\""), "{}", text);
+    assert!(text.contains("aggregation.max_toasts: Must be greater than 0"), "{}", text);
+    assert!(text.contains("colour: Unknown key, ignored"), "{}", text);
+    assert!(!has_chinese(&text), "{}", text);
+
+    let output = home.command(&["--lang", "en-US", "config", "set", "aggregation.max_toasts", "5"]).output().unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Invalid config (1 error(s)):"), "{}", stderr);
+    assert!(!has_chinese(&stderr), "{}", stderr);
+
+    // 加载配置之前的日志（创建默认配置）也使用该语言
+    fs::remove_dir_all(home.config_dir()).unwrap();
+    let output = home.ccn(&["--lang", "en-US", "notify", "--status", "success", "--duration", "1", "--cmd", "true"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Config file not found, creating the default config"), "{}", stderr);
+    assert!(!has_chinese(&stderr), "{}", stderr);
+}